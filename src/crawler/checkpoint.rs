@@ -0,0 +1,212 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use fastbloom::BloomFilter;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::fs::{create_dir_all, read, remove_file, rename, try_exists, write};
+
+use crate::types::{error::AppError, structs::record::Record};
+
+// File format version written by this build. Bump it whenever `Checkpoint` changes shape
+// and teach `Checkpoint::decode` how to read the previous version.
+pub const CHECKPOINT_VERSION: u16 = 1;
+
+// State an interrupted crawl needs to carry on: what was left to crawl and what had
+// already been seen.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub task_id: String,
+    pub frontier: Vec<Queued>,
+    pub bloom_filter: Option<BloomFilter>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Queued {
+    pub record: Record,
+    pub priority: i64,
+}
+
+// Checkpoints saved before envelopes existed, which didn't keep priorities
+#[derive(Deserialize)]
+struct CheckpointV0 {
+    task_id: String,
+    frontier: Vec<Record>,
+    bloom_filter: Option<BloomFilter>,
+    timestamp: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u16,
+    checkpoint: &'a Checkpoint,
+}
+
+impl Checkpoint {
+    pub async fn load(path: &Path) -> Result<Option<Self>, AppError> {
+        if !try_exists(path).await? {
+            return Ok(None);
+        }
+
+        Ok(Some(Self::decode(&read(path).await?)?))
+    }
+
+    // Written next to `path` first and renamed over it, so a crash mid-write leaves the
+    // previous checkpoint intact.
+    pub async fn save(&self, path: &Path) -> Result<(), AppError> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        let partial = path.with_extension("partial");
+        let bytes = serde_json::to_vec(&Envelope {
+            version: CHECKPOINT_VERSION,
+            checkpoint: self,
+        })?;
+
+        write(&partial, bytes).await?;
+        rename(&partial, path).await?;
+
+        Ok(())
+    }
+
+    // Checkpoints from a newer version are rejected rather than guessed at; older ones
+    // are upgraded
+    fn decode(bytes: &[u8]) -> Result<Self, AppError> {
+        match serde_json::from_slice(bytes)? {
+            Value::Object(mut envelope) if envelope.contains_key("version") => {
+                let version = envelope
+                    .get("version")
+                    .and_then(Value::as_u64)
+                    .and_then(|v| u16::try_from(v).ok())
+                    .ok_or_else(|| AppError::MalformedRecord("invalid version".to_string()))?;
+
+                if version > CHECKPOINT_VERSION {
+                    return Err(AppError::UnsupportedCheckpointVersion {
+                        version,
+                        supported: CHECKPOINT_VERSION,
+                    });
+                }
+
+                let checkpoint = envelope
+                    .remove("checkpoint")
+                    .ok_or_else(|| AppError::MalformedRecord("missing checkpoint".to_string()))?;
+
+                Ok(serde_json::from_value(checkpoint)?)
+            }
+            bare => {
+                let v0: CheckpointV0 = serde_json::from_value(bare)?;
+
+                Ok(Self {
+                    task_id: v0.task_id,
+                    frontier: v0
+                        .frontier
+                        .into_iter()
+                        .map(|record| Queued {
+                            record,
+                            priority: 0,
+                        })
+                        .collect(),
+                    bloom_filter: v0.bloom_filter,
+                    timestamp: v0.timestamp,
+                })
+            }
+        }
+    }
+
+    pub async fn remove(path: &Path) -> Result<(), AppError> {
+        if try_exists(path).await? {
+            remove_file(path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use uuid::Uuid;
+
+    use crate::types::structs::record::HopType;
+
+    use super::*;
+
+    fn record() -> Record {
+        Record {
+            uri: "http://example.com/a".to_string(),
+            task_id: "test".to_string(),
+            metadata: vec![],
+            parent_uri: Some("http://example.com/".to_string()),
+            depth: 1,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let path = temp_dir()
+            .join(Uuid::new_v4().to_string())
+            .join("checkpoint.json");
+
+        assert!(Checkpoint::load(&path).await.unwrap().is_none());
+
+        let mut bloom_filter = BloomFilter::with_false_pos(0.001).expected_items(100);
+        bloom_filter.insert("http://example.com/");
+
+        Checkpoint {
+            task_id: "test".to_string(),
+            frontier: vec![Queued {
+                record: record(),
+                priority: 7,
+            }],
+            bloom_filter: Some(bloom_filter),
+            timestamp: Utc::now(),
+        }
+        .save(&path)
+        .await
+        .unwrap();
+
+        let checkpoint = Checkpoint::load(&path).await.unwrap().unwrap();
+
+        assert_eq!(checkpoint.frontier[0].record.depth, 1);
+        assert_eq!(checkpoint.frontier[0].priority, 7);
+        assert!(
+            checkpoint
+                .bloom_filter
+                .unwrap()
+                .contains("http://example.com/")
+        );
+
+        Checkpoint::remove(&path).await.unwrap();
+
+        assert!(Checkpoint::load(&path).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_versions() {
+        let bare = serde_json::json!({
+            "task_id": "test",
+            "frontier": [record()],
+            "bloom_filter": null,
+            "timestamp": Utc::now(),
+        });
+        let checkpoint = Checkpoint::decode(&serde_json::to_vec(&bare).unwrap()).unwrap();
+
+        assert_eq!(checkpoint.frontier[0].record.uri, "http://example.com/a");
+        assert_eq!(checkpoint.frontier[0].priority, 0);
+
+        let newer = serde_json::json!({"version": CHECKPOINT_VERSION + 1, "checkpoint": {}});
+
+        assert!(matches!(
+            Checkpoint::decode(&serde_json::to_vec(&newer).unwrap()),
+            Err(AppError::UnsupportedCheckpointVersion { .. })
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{Notify, watch};
+use tokio_util::sync::CancellationToken;
+
+use crate::types::{
+    error::AppError,
+    structs::{
+        metadata::http_response::HttpResponse,
+        queue_status::QueueStatus,
+        record::{Record, RecordMetadata},
+        seed::Seed,
+    },
+    traits::queue::Queue,
+};
+
+struct Seeds {
+    pending: Vec<Seed>,
+    // Cleared once the crawl stops taking seeds
+    open: bool,
+}
+
+// Shared between a running crawl and whoever drives it: seeds submitted while it runs,
+// pause and cancel requests, the queue its frontier runs through and the latest response
+// fetched for every uri.
+pub struct CrawlControl {
+    shutdown: CancellationToken,
+    paused: watch::Sender<bool>,
+    seeds: Mutex<Seeds>,
+    submitted: Notify,
+    responses: Mutex<HashMap<String, HttpResponse>>,
+    queue: Mutex<Option<Arc<dyn Queue>>>,
+}
+
+impl Default for CrawlControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrawlControl {
+    pub fn new() -> Self {
+        Self {
+            shutdown: CancellationToken::new(),
+            paused: watch::Sender::new(false),
+            seeds: Mutex::new(Seeds {
+                pending: vec![],
+                open: true,
+            }),
+            submitted: Notify::new(),
+            responses: Mutex::new(HashMap::new()),
+            queue: Mutex::new(None),
+        }
+    }
+
+    pub fn shutdown(&self) -> &CancellationToken {
+        &self.shutdown
+    }
+
+    // Stops dispatching new records and checkpoints the frontier, like a shutdown signal
+    pub fn cancel(&self) {
+        self.shutdown.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    // Records in flight finish, but nothing new is dispatched until resumed
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    pub(crate) fn watch_paused(&self) -> watch::Receiver<bool> {
+        self.paused.subscribe()
+    }
+
+    // Queues seeds for the running crawl. False once it has stopped taking them.
+    pub fn submit(&self, submitted: Vec<Seed>) -> bool {
+        let mut seeds = self.seeds.lock().unwrap();
+
+        if !seeds.open {
+            return false;
+        }
+
+        seeds.pending.extend(submitted);
+        self.submitted.notify_one();
+
+        true
+    }
+
+    pub(crate) async fn submitted(&self) {
+        self.submitted.notified().await;
+    }
+
+    pub(crate) fn take_seeds(&self) -> Vec<Seed> {
+        mem::take(&mut self.seeds.lock().unwrap().pending)
+    }
+
+    // Stops taking seeds unless some are pending, which are returned instead. Checked and
+    // closed under one lock so no submission can slip in between.
+    pub(crate) fn finish_unless_seeded(&self) -> Vec<Seed> {
+        let mut seeds = self.seeds.lock().unwrap();
+
+        if seeds.pending.is_empty() {
+            seeds.open = false;
+        }
+
+        mem::take(&mut seeds.pending)
+    }
+
+    // Stops taking seeds and returns whatever was still pending
+    pub(crate) fn close(&self) -> Vec<Seed> {
+        let mut seeds = self.seeds.lock().unwrap();
+
+        seeds.open = false;
+
+        mem::take(&mut seeds.pending)
+    }
+
+    pub(crate) fn record_response(&self, record: &Record) {
+        let response = record.metadata.iter().rev().find_map(|m| match m {
+            RecordMetadata::HttpResponse(r) => Some(r.as_ref()),
+            _ => None,
+        });
+
+        if let Some(response) = response {
+            self.responses
+                .lock()
+                .unwrap()
+                .insert(record.uri.clone(), response.clone());
+        }
+    }
+
+    // Latest response fetched for `uri` during this crawl
+    pub fn response(&self, uri: &str) -> Option<HttpResponse> {
+        self.responses.lock().unwrap().get(uri).cloned()
+    }
+
+    pub(crate) fn attach_queue(&self, queue: Arc<dyn Queue>) {
+        *self.queue.lock().unwrap() = Some(queue);
+    }
+
+    // Status of the crawl's frontier, once it has been built
+    pub async fn queue_status(&self) -> Option<Result<QueueStatus, AppError>> {
+        let queue = self.queue.lock().unwrap().clone()?;
+
+        Some(queue.status().await)
+    }
+}
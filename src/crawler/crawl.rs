@@ -0,0 +1,1181 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::Utc;
+use serde::Serialize;
+use tokio::{
+    sync::mpsc::channel,
+    time::{Instant, sleep_until},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, debug, info, info_span, warn};
+use uuid::Uuid;
+
+use crate::{
+    crawler::{
+        checkpoint::{Checkpoint, Queued},
+        control::CrawlControl,
+        frontier::Frontier,
+        pipeline::{Delivery, Outcome, Pipeline, Stage, Unfinished},
+    },
+    services::{
+        dead_letter::file::FileDeadLetterSink,
+        object_store::fs::FileSystemObjectStore,
+        queue::{memory::MemoryQueue, redis::RedisQueue, sqlite::SqliteQueue},
+    },
+    tasks::{
+        headless_browser_fetcher::HeadlessBrowserFetcher, http_fetcher::HttpFetcher,
+        record_filter::RecordFilter, url_extractor::UrlExtractor,
+    },
+    types::{
+        configs::{
+            crawl_config::{
+                BudgetConfig, CrawlConfig, DeadLetterConfig, ObjectStoreConfig, ShutdownConfig,
+                TaskConfig,
+            },
+            queues::queue_config::QueueConfig,
+        },
+        error::AppError,
+        structs::{
+            record::{HopType, Record, RecordMetadata},
+            seed::Seed,
+        },
+        traits::{dead_letter_sink::DeadLetterSink, queue::Queue, task::Task},
+    },
+    utils::dependencies::dependencies,
+};
+
+// How long dispatching waits before asking the queue again after finding it empty
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CrawlSummary {
+    pub pages: usize,
+    pub failures: usize,
+    pub dead_lettered: usize,
+    pub discovered: usize,
+    pub admitted: usize,
+    // Records left queued or leased by an earlier run, or picked up from its checkpoint
+    pub resumed: usize,
+    // Whether a shutdown signal cut the crawl short
+    pub interrupted: bool,
+    // Records taken back from the pipeline when draining ran out of time
+    pub requeued: usize,
+    // Records put back in the queue to wait out a host that throttled them
+    pub deferred: usize,
+    // Admitted records left out because the memory queue was full
+    pub dropped: usize,
+}
+
+pub struct Crawler<'a> {
+    task_id: String,
+    budget: &'a BudgetConfig,
+    shutdown: &'a ShutdownConfig,
+    pipeline: Pipeline<'a>,
+    frontier: Frontier,
+    dead_letters: Option<Box<dyn DeadLetterSink>>,
+    resumed: usize,
+}
+
+impl<'a> Crawler<'a> {
+    pub async fn new(config: &'a CrawlConfig) -> Result<Self, AppError> {
+        register_object_stores(&config.object_stores).await?;
+
+        let mut stages = vec![];
+
+        for stage in &config.tasks {
+            let task: Box<dyn Task + Send + Sync + 'a> = match &stage.task {
+                TaskConfig::HttpFetcher(c) => Box::new(HttpFetcher::new(c).await?),
+                TaskConfig::HeadlessBrowser(c) => Box::new(HeadlessBrowserFetcher::new(c).await?),
+                TaskConfig::UrlExtractor(c) => Box::new(UrlExtractor::new(c).await?),
+                TaskConfig::RecordFilter(c) => Box::new(RecordFilter::new(c).await?),
+            };
+
+            stages.push(Stage {
+                name: stage.task.name(),
+                task,
+                workers: stage.workers,
+                buffer: stage.buffer,
+                retry: stage.retry.clone(),
+            });
+        }
+
+        let mut task_id = config.task_id.clone();
+        let mut checkpoint = None;
+
+        if let Some(path) = &config.shutdown.checkpoint
+            && let Some(saved) = Checkpoint::load(path).await?
+        {
+            if task_id.as_ref().is_some_and(|t| *t != saved.task_id) {
+                return Err(AppError::Generic(format!(
+                    "checkpoint {} belongs to task {}",
+                    path.display(),
+                    saved.task_id
+                )));
+            }
+
+            task_id = Some(saved.task_id.clone());
+            checkpoint = Some(saved);
+        }
+
+        // Known before the frontier is built so its dedup entries land in this crawl's
+        // namespace
+        let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let mut frontier = Frontier::new(config.filters.clone(), &config.queue, &task_id).await?;
+
+        if let Some(checkpoint) = checkpoint {
+            frontier
+                .restore(
+                    checkpoint
+                        .frontier
+                        .into_iter()
+                        .map(|q| (q.record, q.priority))
+                        .collect(),
+                    checkpoint.bloom_filter,
+                )
+                .await?;
+        }
+
+        let resumed = frontier.remaining().await? as usize;
+
+        let dead_letters: Option<Box<dyn DeadLetterSink>> = match &config.dead_letter {
+            Some(DeadLetterConfig::File { path }) => {
+                Some(Box::new(FileDeadLetterSink::new(path.clone()).await?))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            task_id,
+            budget: &config.budget,
+            shutdown: &config.shutdown,
+            pipeline: Pipeline::new(stages),
+            frontier,
+            dead_letters,
+            resumed,
+        })
+    }
+
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    // Feeds the pipeline from the frontier and admits the links, or child records, of every
+    // page that comes out of it, until the frontier is empty and nothing is in flight.
+    //
+    // Seeds submitted through `control` while running are admitted like the initial
+    // ones, and while paused nothing new is dispatched.
+    //
+    // Once cancelled nothing new is dispatched either. In-flight records get
+    // `drain_timeout` to finish and the rest are put back in the frontier. A frontier kept
+    // in memory is checkpointed if configured; durable queues hold on to their records.
+    pub async fn run(
+        self,
+        seeds: Vec<Seed>,
+        control: &CrawlControl,
+    ) -> Result<CrawlSummary, AppError> {
+        let Crawler {
+            task_id,
+            budget,
+            shutdown: shutdown_config,
+            pipeline,
+            mut frontier,
+            dead_letters,
+            resumed,
+        } = self;
+
+        info!(
+            task_id = %task_id,
+            seeds = seeds.len(),
+            resumed,
+            "crawl started"
+        );
+
+        control.attach_queue(frontier.queue());
+        admit_seeds(seeds, &mut frontier, &task_id).await?;
+
+        let (input, stage_input) = pipeline.input();
+        let (outcomes, mut results) = channel::<Outcome>(pipeline.workers());
+        let abort = CancellationToken::new();
+        let dead_letters = dead_letters.as_deref();
+        let mut leases = HashMap::new();
+        let mut summary = CrawlSummary {
+            resumed,
+            ..CrawlSummary::default()
+        };
+
+        let drive = async {
+            let driven = async {
+                let input = input;
+                let mut deadline = None;
+                // Set while the queue had nothing visible, until it's worth asking again
+                let mut starved = None;
+                let mut paused = control.watch_paused();
+
+                loop {
+                    let is_paused = *paused.borrow_and_update();
+                    let dispatch = deadline.is_none()
+                        && !is_paused
+                        && budget.max_pages.is_none_or(|max| summary.pages < max);
+
+                    // A paused crawl idles until resumed instead of finishing. Other processes
+                    // working on the crawl may still add to the queue while they hold leases.
+                    if leases.is_empty()
+                        && (deadline.is_some() || !is_paused)
+                        && (!dispatch || frontier.remaining().await? == 0)
+                    {
+                        let seeds = match deadline {
+                            Some(_) => vec![],
+                            None => control.finish_unless_seeded(),
+                        };
+
+                        if seeds.is_empty() {
+                            return Ok(());
+                        }
+
+                        admit_seeds(seeds, &mut frontier, &task_id).await?;
+                        starved = None;
+                        continue;
+                    }
+
+                    tokio::select! {
+                        _ = control.shutdown().cancelled(), if deadline.is_none() => {
+                            info!(task_id = %task_id, in_flight = leases.len(), "draining in-flight records");
+                            summary.interrupted = true;
+                            deadline = Some(
+                                Instant::now() + Duration::from_secs(shutdown_config.drain_timeout),
+                            );
+                        }
+                        _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                            break;
+                        }
+                        _ = sleep_until(starved.unwrap_or_else(Instant::now)), if starved.is_some() => {
+                            starved = None;
+                        }
+                        permit = input.reserve(), if dispatch && starved.is_none() => {
+                            let permit = permit.map_err(|_| AppError::from("pipeline stopped"))?;
+
+                            match frontier.pop().await? {
+                                Some(message) => {
+                                    // A deferred record was counted when it first went out
+                                    if message.record.deferrals == 0 {
+                                        summary.pages += 1;
+                                    }
+
+                                    leases.insert(message.id.clone(), Lease {
+                                        pieces: 1,
+                                        redelivered: message.attempts > 1,
+                                        priority: message.priority,
+                                    });
+                                    permit.send(Delivery {
+                                        lease: message.id,
+                                        record: message.record,
+                                    });
+                                }
+                                None => starved = Some(Instant::now() + POLL_INTERVAL),
+                            }
+                        }
+                        Some(outcome) = results.recv() => {
+                            settle(outcome, &mut frontier, &mut leases, &mut summary, budget, dead_letters, control).await?;
+                            starved = None;
+                        }
+                        _ = paused.changed(), if deadline.is_none() => {
+                            info!(task_id = %task_id, paused = *paused.borrow(), "crawl paused or resumed");
+                        }
+                        _ = control.submitted(), if deadline.is_none() => {
+                            admit_seeds(control.take_seeds(), &mut frontier, &task_id).await?;
+                            starved = None;
+                        }
+                        else => return Err(AppError::from("pipeline stopped")),
+                    }
+                }
+
+                // Out of time: every worker finishes what it holds and the rest is requeued
+                warn!(task_id = %task_id, in_flight = leases.len(), "drain timed out, requeuing the rest");
+                abort.cancel();
+                drop(input);
+
+                while let Some(outcome) = results.recv().await {
+                    settle(
+                        outcome,
+                        &mut frontier,
+                        &mut leases,
+                        &mut summary,
+                        budget,
+                        dead_letters,
+                        control,
+                    )
+                    .await?;
+                }
+
+                Ok(())
+            }
+            .await;
+
+            // Workers still hold records when the crawl fails, so they're stopped and their
+            // results thrown away rather than left waiting to be collected. Their leases
+            // aren't acked, so the records are crawled again.
+            if let Err(e) = &driven {
+                warn!(task_id = %task_id, error = %e, in_flight = leases.len(), "crawl failed, stopping the pipeline");
+                abort.cancel();
+
+                while results.recv().await.is_some() {}
+            }
+
+            driven
+        };
+
+        let (unfinished, driven) = tokio::join!(pipeline.run(stage_input, outcomes, &abort), drive);
+        let closed = pipeline.close().await;
+        // Seeds that came in while shutting down are kept for the checkpoint
+        let late = control.close();
+
+        let requeued = async {
+            admit_seeds(late, &mut frontier, &task_id).await?;
+
+            for Unfinished {
+                stage,
+                delivery: Delivery { lease, record },
+            } in unfinished
+            {
+                summary.requeued += 1;
+
+                // Children spawned mid-pipeline were never admitted; anything else starts over
+                if stage > 0 && is_unfetched(&record) {
+                    let redelivered = leases.get(&lease).is_some_and(|l| l.redelivered);
+                    admit_child(record, &mut frontier, &mut summary, budget, redelivered).await?;
+                } else {
+                    frontier
+                        .push(Record {
+                            metadata: vec![],
+                            ..record
+                        })
+                        .await?;
+                }
+
+                release(&lease, &frontier, &mut leases).await?;
+            }
+
+            Ok::<_, AppError>(())
+        }
+        .await;
+
+        // A failed crawl is checkpointed like an interrupted one so it can be resumed
+        let failed = driven.is_err() || requeued.is_err();
+        let saved = match &shutdown_config.checkpoint {
+            Some(path) if summary.interrupted || failed => {
+                Checkpoint {
+                    task_id: task_id.clone(),
+                    frontier: frontier
+                        .take_records()
+                        .into_iter()
+                        .map(|(record, priority)| Queued { record, priority })
+                        .collect(),
+                    bloom_filter: frontier.bloom_filter().cloned(),
+                    timestamp: Utc::now(),
+                }
+                .save(path)
+                .await
+            }
+            Some(path) => Checkpoint::remove(path).await,
+            None => Ok(()),
+        };
+
+        driven?;
+        requeued?;
+        saved?;
+        closed?;
+
+        info!(
+            task_id = %task_id,
+            pages = summary.pages,
+            failures = summary.failures,
+            admitted = summary.admitted,
+            requeued = summary.requeued,
+            deferred = summary.deferred,
+            dropped = summary.dropped,
+            interrupted = summary.interrupted,
+            "crawl finished"
+        );
+
+        Ok(summary)
+    }
+}
+
+// A queue message whose record, or the records it fanned out into, is still in the
+// pipeline
+struct Lease {
+    pieces: usize,
+    // Delivered before, to a run that may have queued some of what it found
+    redelivered: bool,
+    priority: i64,
+}
+
+// Accounts for one outcome of the pipeline and admits whatever it discovered
+async fn settle(
+    outcome: Outcome,
+    frontier: &mut Frontier,
+    leases: &mut HashMap<String, Lease>,
+    summary: &mut CrawlSummary,
+    budget: &BudgetConfig,
+    dead_letters: Option<&dyn DeadLetterSink>,
+    control: &CrawlControl,
+) -> Result<(), AppError> {
+    let Delivery { lease, record } = match outcome {
+        Outcome::Completed(delivery) => {
+            control.record_response(&delivery.record);
+            delivery
+        }
+        Outcome::Failed { lease, dead_letter } => {
+            summary.failures += 1;
+
+            if let Some(sink) = dead_letters {
+                sink.put(dead_letter).await?;
+                summary.dead_lettered += 1;
+            }
+
+            return release(&lease, frontier, leases).await;
+        }
+        Outcome::Deferred {
+            delivery: Delivery { lease, record },
+            delay,
+        } => {
+            summary.deferred += 1;
+
+            let priority = leases.get(&lease).map_or(0, |l| l.priority);
+
+            // Starts over like a record taken back on shutdown, keeping count of how often
+            // it was sent back
+            frontier
+                .push_delayed(
+                    Record {
+                        metadata: vec![],
+                        deferrals: record.deferrals + 1,
+                        ..record
+                    },
+                    priority,
+                    delay,
+                )
+                .await?;
+
+            return release(&lease, frontier, leases).await;
+        }
+        Outcome::FannedOut { lease, records } => {
+            if let Some(l) = leases.get_mut(&lease) {
+                l.pieces += records;
+            }
+
+            return release(&lease, frontier, leases).await;
+        }
+    };
+
+    let redelivered = leases.get(&lease).is_some_and(|l| l.redelivered);
+
+    discover(record, frontier, summary, budget, redelivered).await?;
+    release(&lease, frontier, leases).await
+}
+
+// Settles one piece of a leased message, acking it after the last one
+async fn release(
+    lease: &str,
+    frontier: &Frontier,
+    leases: &mut HashMap<String, Lease>,
+) -> Result<(), AppError> {
+    let Some(l) = leases.get_mut(lease) else {
+        return Ok(());
+    };
+
+    l.pieces -= 1;
+
+    if l.pieces == 0 {
+        leases.remove(lease);
+        frontier.ack(lease).await?;
+    }
+
+    Ok(())
+}
+
+// Queues the links found on a record, or the record itself if it's a child still to
+// be fetched
+async fn discover(
+    record: Record,
+    frontier: &mut Frontier,
+    summary: &mut CrawlSummary,
+    budget: &BudgetConfig,
+    redelivered: bool,
+) -> Result<(), AppError> {
+    // Child records spawned by a fan-out stage still need fetching
+    if is_unfetched(&record) {
+        return admit_child(record, frontier, summary, budget, redelivered).await;
+    }
+
+    if max_depth(budget, &record).is_some_and(|max| record.depth >= max) {
+        return Ok(());
+    }
+
+    let mut uris = extracted_uris(&record);
+    let redirect = unfollowed_redirect(&record);
+
+    // A redirect the fetcher wouldn't follow is crawled as a page of its own
+    if let Some(redirect) = &redirect
+        && !uris.contains(redirect)
+    {
+        uris.push(redirect.clone());
+    }
+
+    summary.discovered += uris.len();
+
+    let span = info_span!("admit", uri = %record.uri, task_id = %record.task_id);
+    let admitted = frontier
+        .admit(uris, &record.uri, redelivered)
+        .instrument(span.clone())
+        .await?;
+    summary.admitted += admitted.len();
+
+    for uri in admitted {
+        span.in_scope(|| debug!(child = %uri, "admitted"));
+        let hop = match redirect.as_ref() == Some(&uri) {
+            true => HopType::Redirect,
+            false => HopType::Link,
+        };
+
+        push_child(record.child(uri, hop), frontier, summary).await?;
+    }
+
+    Ok(())
+}
+
+async fn admit_child(
+    record: Record,
+    frontier: &mut Frontier,
+    summary: &mut CrawlSummary,
+    budget: &BudgetConfig,
+    redelivered: bool,
+) -> Result<(), AppError> {
+    if max_depth(budget, &record).is_some_and(|max| record.depth > max) {
+        return Ok(());
+    }
+
+    summary.discovered += 1;
+
+    let origin = record.parent_uri.clone().unwrap_or_default();
+    let span = info_span!("admit", uri = %origin, task_id = %record.task_id);
+
+    if !frontier
+        .admit(vec![record.uri.clone()], &origin, redelivered)
+        .instrument(span.clone())
+        .await?
+        .is_empty()
+    {
+        span.in_scope(|| debug!(child = %record.uri, "admitted"));
+        summary.admitted += 1;
+        push_child(record, frontier, summary).await?;
+    }
+
+    Ok(())
+}
+
+// Leaves out a record the memory queue has no room for rather than failing the crawl
+async fn push_child(
+    record: Record,
+    frontier: &Frontier,
+    summary: &mut CrawlSummary,
+) -> Result<(), AppError> {
+    match frontier.push(record).await {
+        Err(AppError::QueueFull(uri)) => {
+            warn!(uri = %uri, "memory queue is full, dropping record");
+            summary.dropped += 1;
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+// Seeds go into the frontier highest priority first
+async fn admit_seeds(
+    mut seeds: Vec<Seed>,
+    frontier: &mut Frontier,
+    task_id: &str,
+) -> Result<(), AppError> {
+    if seeds.is_empty() {
+        return Ok(());
+    }
+
+    seeds.sort_by_key(|seed| Reverse(seed.priority));
+
+    let uris = seeds.iter().map(|seed| seed.uri.clone()).collect();
+    let admitted: HashSet<String> = frontier.admit(uris, "", false).await?.into_iter().collect();
+
+    for seed in seeds {
+        if admitted.contains(&seed.uri) {
+            debug!(uri = %seed.uri, task_id = %task_id, "seed admitted");
+            frontier
+                .push_with_priority(seed.record(task_id), seed.priority)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+// The tighter of the budget's depth limit and the one of the record's seed
+fn max_depth(budget: &BudgetConfig, record: &Record) -> Option<u32> {
+    match (budget.max_depth, record.max_depth) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+pub async fn register_object_stores(configs: &[ObjectStoreConfig]) -> Result<(), AppError> {
+    let mut deps = dependencies().lock().await;
+
+    for config in configs {
+        match config {
+            ObjectStoreConfig::FileSystem { name, path } => {
+                let store = FileSystemObjectStore::new(path.clone()).await?;
+                deps.set_object_store(name, Arc::new(store))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// A queue opened for a task only hands out and reports on that task's messages
+pub async fn open_queue(
+    config: &QueueConfig,
+    task_id: Option<&str>,
+) -> Result<Arc<dyn Queue>, AppError> {
+    Ok(match config.clone() {
+        QueueConfig::Memory(c) => Arc::new(MemoryQueue::new(c)),
+        QueueConfig::Sqlite(c) => Arc::new(SqliteQueue::new(c, task_id).await?),
+        QueueConfig::Redis(c) => Arc::new(RedisQueue::new(c, task_id).await?),
+    })
+}
+
+// The configured task id, else the one of the checkpoint being resumed, else a new one
+pub async fn resolve_task_id(config: &CrawlConfig) -> Result<String, AppError> {
+    if let Some(task_id) = &config.task_id {
+        return Ok(task_id.clone());
+    }
+
+    if let Some(path) = &config.shutdown.checkpoint
+        && let Some(checkpoint) = Checkpoint::load(path).await?
+    {
+        return Ok(checkpoint.task_id);
+    }
+
+    Ok(Uuid::new_v4().to_string())
+}
+
+fn is_unfetched(record: &Record) -> bool {
+    record.parent_uri.is_some()
+        && !record
+            .metadata
+            .iter()
+            .any(|m| matches!(m, RecordMetadata::HttpResponse(_)))
+}
+
+fn unfollowed_redirect(record: &Record) -> Option<String> {
+    let response = record.metadata.iter().rev().find_map(|m| match m {
+        RecordMetadata::HttpResponse(r) => Some(r),
+        _ => None,
+    })?;
+
+    response.unfollowed_redirect().map(str::to_string)
+}
+
+fn extracted_uris(record: &Record) -> Vec<String> {
+    let mut seen = HashSet::new();
+
+    record
+        .metadata
+        .iter()
+        .filter_map(|m| match m {
+            RecordMetadata::Uris(u) => Some(&u.uris),
+            _ => None,
+        })
+        .flatten()
+        .filter(|u| seen.insert(u.as_str()))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use httpmock::{Method::GET, MockServer};
+    use sqlx::{SqlitePool, query};
+    use tokio::time::{sleep, timeout};
+
+    use crate::types::{
+        configs::{
+            admin_config::AdminConfig,
+            crawl_config::{FrontierFiltersConfig, StageConfig},
+            filters::unique_filter_config::{
+                BloomFilterConfig, HashSetConfig, SqliteHashSetConfig, UniqueFilterConfig,
+            },
+            job_store_config::JobStoreConfig,
+            logging_config::LoggingConfig,
+            queues::{
+                memory_queue_config::MemoryQueueConfig, sqlite_queue_config::SqliteQueueConfig,
+            },
+            tasks::{
+                content_config::ContentConfig, http_fetcher_config::HttpFetcherConfig,
+                record_filter_config::RecordFilterConfig, redirect_config::RedirectConfig,
+                retry_config::RetryConfig, url_extractor_config::UrlExtractorConfig,
+            },
+        },
+        structs::record::RecordEncoding,
+    };
+
+    use super::*;
+
+    fn crawl_config(max_pages: Option<usize>, max_depth: Option<u32>) -> CrawlConfig {
+        let store_name = Uuid::new_v4().to_string();
+
+        CrawlConfig {
+            task_id: None,
+            budget: BudgetConfig {
+                max_pages,
+                max_depth,
+            },
+            object_stores: vec![ObjectStoreConfig::FileSystem {
+                name: store_name.clone(),
+                path: temp_dir().join(Uuid::new_v4().to_string()),
+            }],
+            queue: QueueConfig::default(),
+            dead_letter: None,
+            shutdown: ShutdownConfig::default(),
+            logging: LoggingConfig::default(),
+            admin: AdminConfig::default(),
+            jobs: JobStoreConfig::default(),
+            metrics: None,
+            tasks: vec![
+                StageConfig {
+                    task: TaskConfig::HttpFetcher(HttpFetcherConfig {
+                        proxy_server: None,
+                        object_store: store_name.clone(),
+                        timeout: 30,
+                        user_agent: None,
+                        revalidate: true,
+                        redirects: RedirectConfig::default(),
+                        content: ContentConfig::default(),
+                        politeness: None,
+                    }),
+                    workers: 4,
+                    buffer: 2,
+                    retry: RetryConfig::default(),
+                },
+                StageConfig {
+                    task: TaskConfig::UrlExtractor(UrlExtractorConfig {
+                        object_store: store_name,
+                        children: false,
+                    }),
+                    workers: 2,
+                    buffer: 2,
+                    retry: RetryConfig::default(),
+                },
+            ],
+            filters: FrontierFiltersConfig {
+                scope: None,
+                robots: None,
+                unique: Some(UniqueFilterConfig {
+                    bloom_filter: BloomFilterConfig {
+                        enable: true,
+                        false_positive_rate: 0.001,
+                        expected_size: 1000,
+                    },
+                    hash_set: HashSetConfig::Empty,
+                }),
+            },
+        }
+    }
+
+    fn mock_site(server: &MockServer) {
+        server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(200)
+                .body(r#"<a href="/a">a</a> <a href="/b">b</a>"#);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/a");
+            then.status(200)
+                .body(r#"<a href="/b">b</a> <a href="/">home</a>"#);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/b");
+            then.status(200).body(r#"<a href="/c">c</a>"#);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/c");
+            then.status(200).body("The end.");
+        });
+    }
+
+    #[tokio::test]
+    async fn test_crawl_until_frontier_empty() {
+        let server = MockServer::start();
+        mock_site(&server);
+
+        let config = crawl_config(None, None);
+        let crawler = Crawler::new(&config).await.unwrap();
+        let summary = crawler
+            .run(vec![Seed::new(&server.url("/"))], &CrawlControl::new())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages, 4);
+        assert_eq!(summary.failures, 0);
+        assert_eq!(summary.admitted, 3);
+    }
+
+    #[tokio::test]
+    async fn test_crawl_budget() {
+        let server = MockServer::start();
+        mock_site(&server);
+
+        let config = crawl_config(None, Some(1));
+        let crawler = Crawler::new(&config).await.unwrap();
+        let summary = crawler
+            .run(vec![Seed::new(&server.url("/"))], &CrawlControl::new())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages, 3);
+
+        // A seed's own depth limit applies to everything found from it
+        let config = crawl_config(None, None);
+        let crawler = Crawler::new(&config).await.unwrap();
+        let seed = Seed {
+            max_depth: Some(1),
+            ..Seed::new(&server.url("/"))
+        };
+        let summary = crawler.run(vec![seed], &CrawlControl::new()).await.unwrap();
+
+        assert_eq!(summary.pages, 3);
+
+        let config = crawl_config(Some(2), None);
+        let crawler = Crawler::new(&config).await.unwrap();
+        let summary = crawler
+            .run(vec![Seed::new(&server.url("/"))], &CrawlControl::new())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages, 2);
+    }
+
+    #[tokio::test]
+    async fn test_crawl_child_records() {
+        let server = MockServer::start();
+        mock_site(&server);
+
+        let mut config = crawl_config(None, None);
+
+        if let TaskConfig::UrlExtractor(c) = &mut config.tasks[1].task {
+            c.children = true;
+        }
+
+        config.tasks.push(StageConfig {
+            task: TaskConfig::RecordFilter(RecordFilterConfig {
+                max_depth: Some(1),
+                same_host: true,
+            }),
+            workers: 1,
+            buffer: 2,
+            retry: RetryConfig::default(),
+        });
+
+        let crawler = Crawler::new(&config).await.unwrap();
+        let summary = crawler
+            .run(vec![Seed::new(&server.url("/"))], &CrawlControl::new())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages, 3);
+        assert_eq!(summary.admitted, 2);
+    }
+
+    #[tokio::test]
+    async fn test_seeds_submitted_while_paused() {
+        let server = MockServer::start();
+        mock_site(&server);
+
+        let config = crawl_config(None, None);
+        let control = CrawlControl::new();
+
+        control.pause();
+
+        let crawler = Crawler::new(&config).await.unwrap();
+        let drive = async {
+            assert!(control.submit(vec![Seed::new(&server.url("/"))]));
+            sleep(Duration::from_millis(100)).await;
+            control.resume();
+        };
+        let (summary, _) = tokio::join!(crawler.run(vec![], &control), drive);
+
+        assert_eq!(summary.unwrap().pages, 4);
+        assert!(!control.submit(vec![Seed::new(&server.url("/a"))]));
+        assert_eq!(
+            control.response(&server.url("/c")).unwrap().status,
+            Some(200)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_checkpoints_and_resumes() {
+        let server = MockServer::start();
+        mock_site(&server);
+
+        let checkpoint = temp_dir()
+            .join(Uuid::new_v4().to_string())
+            .join("checkpoint.json");
+        let mut config = crawl_config(None, None);
+
+        config.shutdown = ShutdownConfig {
+            drain_timeout: 0,
+            checkpoint: Some(checkpoint.clone()),
+        };
+
+        let control = CrawlControl::new();
+        control.cancel();
+
+        let crawler = Crawler::new(&config).await.unwrap();
+        let task_id = crawler.task_id().to_string();
+        let first = crawler
+            .run(vec![Seed::new(&server.url("/"))], &control)
+            .await
+            .unwrap();
+
+        assert!(first.interrupted);
+        assert!(Checkpoint::load(&checkpoint).await.unwrap().is_some());
+
+        let crawler = Crawler::new(&config).await.unwrap();
+
+        assert_eq!(crawler.task_id(), task_id);
+
+        let second = crawler
+            .run(vec![Seed::new(&server.url("/"))], &CrawlControl::new())
+            .await
+            .unwrap();
+
+        assert!(!second.interrupted);
+        assert_eq!(first.pages - first.requeued + second.pages, 4);
+        assert!(Checkpoint::load(&checkpoint).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_killed_crawl_resumes_from_queue() {
+        let server = MockServer::start();
+        let root = server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(200)
+                .body(r#"<a href="/a">a</a> <a href="/b">b</a>"#);
+        });
+        // Still being fetched when the crawl is killed
+        let a = server.mock(|when, then| {
+            when.method(GET).path("/a");
+            then.status(200)
+                .delay(Duration::from_secs(1))
+                .body(r#"<a href="/c">c</a>"#);
+        });
+        let b = server.mock(|when, then| {
+            when.method(GET).path("/b");
+            then.status(200).body("The end.");
+        });
+        let c = server.mock(|when, then| {
+            when.method(GET).path("/c");
+            then.status(200).body("The end.");
+        });
+
+        let temp_db = || {
+            temp_dir()
+                .join(format!("{}.db", Uuid::new_v4()))
+                .to_string_lossy()
+                .to_string()
+        };
+        let mut config = crawl_config(None, None);
+
+        config.task_id = Some("resume".to_string());
+        config.queue = QueueConfig::Sqlite(SqliteQueueConfig {
+            path: temp_db(),
+            visibility_timeout: 1,
+            max_attempts: 5,
+            encoding: RecordEncoding::default(),
+        });
+        config.filters.unique = Some(UniqueFilterConfig {
+            bloom_filter: BloomFilterConfig {
+                enable: false,
+                ..BloomFilterConfig::default()
+            },
+            hash_set: HashSetConfig::Sqlite(SqliteHashSetConfig {
+                path: format!("sqlite://{}?mode=rwc", temp_db()),
+            }),
+        });
+
+        let crawler = Crawler::new(&config).await.unwrap();
+        let killed = timeout(
+            Duration::from_millis(500),
+            crawler.run(vec![Seed::new(&server.url("/"))], &CrawlControl::new()),
+        )
+        .await;
+
+        assert!(killed.is_err());
+
+        // The lease on /a runs out and it's fetched again, along with what it links to
+        let crawler = Crawler::new(&config).await.unwrap();
+        let summary = crawler.run(vec![], &CrawlControl::new()).await.unwrap();
+
+        assert_eq!(summary.resumed, 1);
+        assert_eq!(summary.pages, 2);
+        root.assert_calls(1);
+        a.assert_calls(2);
+        b.assert_calls(1);
+        c.assert_calls(1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_push_stops_the_crawl() {
+        let server = MockServer::start();
+        // Slow enough for the pipeline to fill up before the first page is done
+        server.mock(|when, then| {
+            when.method(GET);
+            then.status(200)
+                .delay(Duration::from_millis(200))
+                .body(r#"<a href="/more">more</a>"#);
+        });
+
+        let path = temp_dir()
+            .join(format!("{}.db", Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        let checkpoint = temp_dir()
+            .join(Uuid::new_v4().to_string())
+            .join("checkpoint.json");
+        let mut config = crawl_config(None, None);
+
+        config.task_id = Some("failing".to_string());
+        config.queue = QueueConfig::Sqlite(SqliteQueueConfig {
+            path: path.clone(),
+            visibility_timeout: 60,
+            max_attempts: 5,
+            encoding: RecordEncoding::default(),
+        });
+        config.shutdown.checkpoint = Some(checkpoint.clone());
+        config.tasks[0].workers = 8;
+        config.tasks[0].buffer = 8;
+        config.tasks[1].buffer = 8;
+
+        let crawler = Crawler::new(&config).await.unwrap();
+        let queue = open_queue(&config.queue, Some("failing")).await.unwrap();
+
+        // More pages than the pipeline hands results back for at once
+        for i in 0..24 {
+            let seed = Seed::new(&server.url(format!("/{i}")));
+            queue.put(seed.record("failing")).await.unwrap();
+        }
+
+        // Everything found from here on fails to be queued
+        let db = SqlitePool::connect(&path).await.unwrap();
+        query("CREATE TRIGGER full BEFORE INSERT ON queue BEGIN SELECT RAISE(FAIL, 'full'); END")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let crawled = timeout(
+            Duration::from_secs(5),
+            crawler.run(vec![], &CrawlControl::new()),
+        )
+        .await
+        .expect("crawl hung after failing");
+
+        assert!(crawled.is_err());
+
+        let saved = Checkpoint::load(&checkpoint).await.unwrap().unwrap();
+
+        assert_eq!(saved.task_id, "failing");
+    }
+
+    #[tokio::test]
+    async fn test_full_memory_queue_drops_children() {
+        let server = MockServer::start();
+        mock_site(&server);
+
+        let mut config = crawl_config(None, None);
+
+        // The seed's lease takes the only slot
+        config.queue = QueueConfig::Memory(MemoryQueueConfig {
+            capacity: Some(1),
+            ..MemoryQueueConfig::default()
+        });
+
+        let crawler = Crawler::new(&config).await.unwrap();
+        let summary = crawler
+            .run(vec![Seed::new(&server.url("/"))], &CrawlControl::new())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages, 1);
+        assert_eq!(summary.admitted, 2);
+        assert_eq!(summary.dropped, 2);
+    }
+
+    #[tokio::test]
+    async fn test_throttled_page_is_given_up_on() {
+        let server = MockServer::start();
+        let throttled = server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(429).header("retry-after", "0");
+        });
+
+        let config = crawl_config(None, None);
+        let crawler = Crawler::new(&config).await.unwrap();
+        let summary = timeout(
+            Duration::from_secs(10),
+            crawler.run(vec![Seed::new(&server.url("/"))], &CrawlControl::new()),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        // Sent back to the queue until its attempts ran out, and only counted once
+        assert_eq!(summary.pages, 1);
+        assert_eq!(summary.deferred, 2);
+        assert_eq!(summary.failures, 1);
+        throttled.assert_calls(3);
+    }
+
+    #[tokio::test]
+    async fn test_unfollowed_redirect_is_crawled() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(301).header("location", "/a");
+        });
+        let a = server.mock(|when, then| {
+            when.method(GET).path("/a");
+            then.status(200).body("The end.");
+        });
+
+        let mut config = crawl_config(None, None);
+
+        if let TaskConfig::HttpFetcher(c) = &mut config.tasks[0].task {
+            c.redirects.max_hops = 0;
+        }
+
+        let crawler = Crawler::new(&config).await.unwrap();
+        let summary = crawler
+            .run(vec![Seed::new(&server.url("/"))], &CrawlControl::new())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.pages, 2);
+        assert_eq!(summary.admitted, 1);
+        a.assert_calls(1);
+    }
+}
@@ -0,0 +1,237 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use fastbloom::BloomFilter;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::{
+    crawler::crawl::open_queue,
+    services::queue::memory::MemoryQueue,
+    tasks::frontier::filters::{robots::RobotsFilter, scope::ScopeFilter, unique::UniqueFilter},
+    types::{
+        configs::{crawl_config::FrontierFiltersConfig, queues::queue_config::QueueConfig},
+        error::AppError,
+        structs::{queue_message::QueueMessage, record::Record},
+        traits::{frontier_filter::FrontierFilter, queue::Queue},
+    },
+    utils::{
+        metrics::{FILTER_DECISIONS, metrics},
+        web::extract_host,
+    },
+};
+
+// Admitted records wait in the configured queue, which only hands out this crawl's
+// messages. A message stays leased while its record is in the pipeline and is acked once
+// everything found on it is queued, so a crawl that dies picks its leased records up
+// again when it restarts.
+pub struct Frontier {
+    queue: Arc<dyn Queue>,
+    // Set when the queue lives in this process, so its records must be checkpointed
+    memory: Option<Arc<MemoryQueue>>,
+    scope_filter: Option<ScopeFilter>,
+    robots_filter: Option<RobotsFilter>,
+    unique_filter: Option<UniqueFilter>,
+}
+
+impl Frontier {
+    // Entries the unique filter and queue keep outside the process are namespaced by
+    // `task_id`
+    pub async fn new(
+        config: FrontierFiltersConfig,
+        queue: &QueueConfig,
+        task_id: &str,
+    ) -> Result<Self, AppError> {
+        let robots_filter = match config.robots {
+            Some(c) => Some(RobotsFilter::new(c)?),
+            None => None,
+        };
+
+        let unique_filter = match config.unique {
+            Some(c) => Some(UniqueFilter::new(c, task_id).await?),
+            None => None,
+        };
+
+        let (queue, memory): (Arc<dyn Queue>, _) = match queue {
+            QueueConfig::Memory(c) => {
+                let memory = Arc::new(MemoryQueue::new(c.clone()));
+                (memory.clone(), Some(memory))
+            }
+            config => (open_queue(config, Some(task_id)).await?, None),
+        };
+
+        Ok(Self {
+            queue,
+            memory,
+            scope_filter: config.scope.map(ScopeFilter::new),
+            robots_filter,
+            unique_filter,
+        })
+    }
+
+    pub async fn push(&self, record: Record) -> Result<(), AppError> {
+        self.push_with_priority(record, 0).await
+    }
+
+    pub async fn push_with_priority(&self, record: Record, priority: i64) -> Result<(), AppError> {
+        // Nothing else consumes a crawl's memory queue, so waiting for room would hang
+        if self.memory.as_ref().is_some_and(|q| q.is_full()) {
+            return Err(AppError::QueueFull(record.uri));
+        }
+
+        self.queue.put_with_priority(record, priority).await
+    }
+
+    // Queues a record that isn't handed out again before `delay` is up
+    pub async fn push_delayed(
+        &self,
+        record: Record,
+        priority: i64,
+        delay: Duration,
+    ) -> Result<(), AppError> {
+        if self.memory.as_ref().is_some_and(|q| q.is_full()) {
+            return Err(AppError::QueueFull(record.uri));
+        }
+
+        self.queue.put_delayed(record, priority, delay).await
+    }
+
+    // Leases the next record if one is visible
+    pub async fn pop(&self) -> Result<Option<QueueMessage>, AppError> {
+        self.queue.get(Duration::ZERO).await
+    }
+
+    // Done with a leased record. A lease that ran out was handed to someone else, who
+    // will ack it instead.
+    pub async fn ack(&self, lease: &str) -> Result<(), AppError> {
+        match self.queue.ack(lease).await {
+            Err(AppError::QueueLeaseNotFound(_)) => {
+                warn!(lease, "lease expired before its record was done");
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    pub fn queue(&self) -> Arc<dyn Queue> {
+        self.queue.clone()
+    }
+
+    // Records queued or leased, by this process or any other working on the crawl
+    pub async fn remaining(&self) -> Result<u64, AppError> {
+        let status = self.queue.status().await?;
+
+        Ok(status.depth + status.in_flight)
+    }
+
+    // Empties an in-process queue for a checkpoint. Durable queues keep their records.
+    pub fn take_records(&self) -> Vec<(Record, i64)> {
+        self.memory.as_ref().map(|q| q.drain()).unwrap_or_default()
+    }
+
+    pub fn bloom_filter(&self) -> Option<&BloomFilter> {
+        self.unique_filter.as_ref()?.bloom_filter()
+    }
+
+    // Picks up where a checkpointed run left off
+    pub async fn restore(
+        &mut self,
+        records: Vec<(Record, i64)>,
+        bloom_filter: Option<BloomFilter>,
+    ) -> Result<(), AppError> {
+        for (record, priority) in records {
+            self.push_with_priority(record, priority).await?;
+        }
+
+        if let (Some(filter), Some(bloom_filter)) = (&mut self.unique_filter, bloom_filter) {
+            filter.restore_bloom_filter(bloom_filter);
+        }
+
+        Ok(())
+    }
+
+    // Runs uris through the configured filters and returns the ones that may be crawled.
+    //
+    // Links of a record that was `redelivered` may have been marked as seen by a run that
+    // died before queueing them, so the unique filter records them but lets them through.
+    pub async fn admit(
+        &mut self,
+        uris: Vec<String>,
+        origin: &str,
+        redelivered: bool,
+    ) -> Result<Vec<String>, AppError> {
+        let mut uris: Vec<String> = uris
+            .into_iter()
+            .filter(|u| Url::parse(u).is_ok_and(|u| u.has_host()))
+            .collect();
+
+        // Out of scope uris are dropped before they can be marked as seen
+        if let Some(filter) = &mut self.scope_filter {
+            uris = admitted("scope", filter.perform(uris, origin).await?);
+        }
+
+        if let Some(filter) = &mut self.robots_filter {
+            let mut buckets: HashMap<String, Vec<String>> = HashMap::new();
+
+            for uri in uris {
+                let host = extract_host(&Url::parse(&uri)?)?;
+                buckets.entry(host).or_default().push(uri);
+            }
+
+            uris = vec![];
+
+            // Hosts whose robots.txt can't be read are skipped rather than failing the batch.
+            for (_, bucket) in buckets {
+                let rejected = bucket.len();
+
+                match filter.perform(bucket, origin).await {
+                    Ok(results) => uris.extend(admitted("robots", results)),
+                    Err(e) => {
+                        warn!(error = %e, uris = rejected, "skipping uris whose robots.txt can't be read");
+                        count_decisions("robots", 0, rejected);
+                    }
+                }
+            }
+        }
+
+        // Runs last so a uri dropped for an unreadable robots.txt isn't marked as seen and is
+        // admitted again the next time it's linked to
+        if let Some(filter) = &mut self.unique_filter {
+            let results = filter.perform(uris, origin).await?;
+
+            uris = match redelivered {
+                true => results.into_iter().map(|(uri, _)| uri).collect(),
+                false => admitted("unique", results),
+            };
+        }
+
+        Ok(uris)
+    }
+}
+
+fn admitted(filter: &str, results: Vec<(String, bool)>) -> Vec<String> {
+    let total = results.len();
+    let uris: Vec<String> = results
+        .into_iter()
+        .filter_map(|(uri, admit)| match admit {
+            true => Some(uri),
+            false => {
+                debug!(filter, candidate = %uri, "rejected");
+                None
+            }
+        })
+        .collect();
+
+    count_decisions(filter, uris.len(), total - uris.len());
+
+    uris
+}
+
+fn count_decisions(filter: &str, accepted: usize, rejected: usize) {
+    for (decision, count) in [("accepted", accepted), ("rejected", rejected)] {
+        metrics().increment(
+            &FILTER_DECISIONS,
+            &[("filter", filter), ("decision", decision)],
+            count as f64,
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use tracing::{info, warn};
+
+use crate::{
+    crawler::{
+        checkpoint::Checkpoint,
+        control::CrawlControl,
+        crawl::{CrawlSummary, Crawler, open_queue, register_object_stores},
+    },
+    services::job_store::{memory::MemoryJobStore, sqlite::SqliteJobStore},
+    tasks::frontier::filters::unique::UniqueFilter,
+    types::{
+        configs::{
+            crawl_config::CrawlConfig, job_config::JobConfig, job_store_config::JobStoreConfig,
+        },
+        error::AppError,
+        signals::{
+            domain_authority_prior::DomainAuthorityPrior, domain_coverage::DomainCoverage,
+            host_gate::HostGate, host_stats_stripe::HostStatsStripe, inlink_agg::InlinkAgg,
+            prefix_stats::PrefixStats, url_depth::UrlDepth, url_state::UrlState,
+        },
+        structs::{
+            crawl_job::{CrawlJob, JobState},
+            seed::Seed,
+        },
+        traits::{
+            job_store::JobStore,
+            signal::{DbSession, Signal},
+        },
+    },
+    utils::{
+        dependencies::dependencies,
+        namespace::{object_prefix, validate_task_id},
+    },
+};
+
+pub async fn open_job_store(config: &JobStoreConfig) -> Result<Arc<dyn JobStore>, AppError> {
+    Ok(match config {
+        JobStoreConfig::Memory => Arc::new(MemoryJobStore::new()),
+        JobStoreConfig::Sqlite { path } => Arc::new(SqliteJobStore::new(path).await?),
+    })
+}
+
+// Runs one crawl of a job, creating the job if it doesn't exist yet
+pub async fn run_job(
+    jobs: &dyn JobStore,
+    base: &CrawlConfig,
+    task_id: &str,
+    seeds: Vec<Seed>,
+    control: &CrawlControl,
+) -> Result<CrawlSummary, AppError> {
+    let config = start_job(jobs, base, task_id).await?;
+    let result = crawl(&config, seeds, control).await;
+
+    finish_job(jobs, task_id, &result).await?;
+
+    result
+}
+
+// Marks a job as running and returns the config its crawl runs with: the shared config
+// with the job's overrides applied.
+pub async fn start_job(
+    jobs: &dyn JobStore,
+    base: &CrawlConfig,
+    task_id: &str,
+) -> Result<CrawlConfig, AppError> {
+    let mut job = match jobs.get(task_id).await? {
+        Some(job) => job,
+        None => CrawlJob::new(task_id, JobConfig::default()),
+    };
+    let config = job.config.apply(base, task_id)?;
+
+    if job.is_active() {
+        warn!(task_id, state = %job.state, "taking over a job left active by a stopped process");
+        abandon(&mut job)?;
+    }
+
+    job.transition(JobState::Running)?;
+    jobs.put(&job).await?;
+
+    Ok(config)
+}
+
+pub async fn crawl(
+    config: &CrawlConfig,
+    seeds: Vec<Seed>,
+    control: &CrawlControl,
+) -> Result<CrawlSummary, AppError> {
+    Crawler::new(config).await?.run(seeds, control).await
+}
+
+// Records how a run ended. The job is read again so changes made while it ran, such as
+// pausing, are kept.
+pub async fn finish_job(
+    jobs: &dyn JobStore,
+    task_id: &str,
+    result: &Result<CrawlSummary, AppError>,
+) -> Result<(), AppError> {
+    let Some(mut job) = jobs.get(task_id).await? else {
+        return Ok(());
+    };
+
+    match result {
+        Ok(summary) => {
+            job.counters.pages += summary.pages as u64;
+            job.counters.failures += summary.failures as u64;
+            job.counters.dead_lettered += summary.dead_lettered as u64;
+            job.counters.discovered += summary.discovered as u64;
+            job.counters.admitted += summary.admitted as u64;
+            job.interrupted = summary.interrupted;
+            job.transition(JobState::Finished)?;
+        }
+        Err(e) => {
+            job.error = Some(e.to_string());
+            job.transition(JobState::Failed)?;
+        }
+    }
+
+    jobs.put(&job).await
+}
+
+// Fails a job whose crawl is no longer running in any process
+pub fn abandon(job: &mut CrawlJob) -> Result<(), AppError> {
+    job.error = Some(format!("abandoned while {}", job.state));
+    job.transition(JobState::Failed)
+}
+
+// Deletes everything a crawl left behind: its entries in the dedup hash set, the records
+// still queued for it, the bodies it stored, its checkpoint and its job. Jobs that are still active are left alone.
+pub async fn purge_job(
+    jobs: &dyn JobStore,
+    config: &CrawlConfig,
+    task_id: &str,
+) -> Result<(), AppError> {
+    // The task id names a directory in every object store
+    validate_task_id(task_id)?;
+
+    if let Some(job) = jobs.get(task_id).await?
+        && job.is_active()
+    {
+        return Err(AppError::ActiveJob(task_id.to_string()));
+    }
+
+    if let Some(unique) = &config.filters.unique
+        && let Some(hash_set) = UniqueFilter::get_hash_set(unique.hash_set.clone()).await?
+    {
+        hash_set.purge(task_id).await?;
+    }
+
+    open_queue(&config.queue, None)
+        .await?
+        .purge(task_id)
+        .await?;
+    register_object_stores(&config.object_stores).await?;
+
+    for store in &config.object_stores {
+        let store = dependencies().lock().await.get_object_store(store.name())?;
+
+        store.delete_prefix(&object_prefix(task_id)).await?;
+    }
+
+    if let Some(path) = &config.shutdown.checkpoint
+        && let Some(checkpoint) = Checkpoint::load(path).await?
+        && checkpoint.task_id == task_id
+    {
+        Checkpoint::remove(path).await?;
+    }
+
+    jobs.delete(task_id).await?;
+    info!(task_id, "purged crawl");
+
+    Ok(())
+}
+
+// Deletes a crawl's rows from every signal table
+pub async fn purge_signals(session: Arc<DbSession>, task_id: &str) -> Result<(), AppError> {
+    DomainAuthorityPrior::purge(session.clone(), task_id).await?;
+    DomainCoverage::purge(session.clone(), task_id).await?;
+    HostGate::purge(session.clone(), task_id).await?;
+    HostStatsStripe::purge(session.clone(), task_id).await?;
+    InlinkAgg::purge(session.clone(), task_id).await?;
+    PrefixStats::purge(session.clone(), task_id).await?;
+    UrlDepth::purge(session.clone(), task_id).await?;
+    UrlState::purge(session, task_id).await
+}
@@ -0,0 +1,7 @@
+pub mod checkpoint;
+pub mod control;
+pub mod crawl;
+pub mod frontier;
+pub mod job;
+pub mod pipeline;
+pub mod seeds;
@@ -0,0 +1,982 @@
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use futures::future::join_all;
+use tokio::{
+    select,
+    sync::{
+        Mutex,
+        mpsc::{Receiver, Sender, channel},
+    },
+    time::sleep,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, debug, info_span, warn};
+
+use crate::{
+    types::{
+        configs::tasks::retry_config::RetryConfig,
+        error::AppError,
+        structs::{
+            dead_letter::{AttemptError, DeadLetter},
+            record::Record,
+        },
+        traits::task::Task,
+    },
+    utils::metrics::{TASK_DURATION, TASK_RECORDS, metrics},
+};
+
+// A record together with the lease of the queue message it was delivered in. Records a
+// stage fans out into share the lease of the one they came from.
+#[derive(Debug)]
+pub struct Delivery {
+    pub lease: String,
+    pub record: Record,
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    // A record made it through every stage
+    Completed(Delivery),
+    // A stage gave up on a record
+    Failed {
+        lease: String,
+        dead_letter: DeadLetter,
+    },
+    // A stage was throttled on a record, which is to be tried again once `delay` is up
+    Deferred {
+        delivery: Delivery,
+        delay: Duration,
+    },
+    // A stage turned a record into this many, none if it dropped it. Sent before any of
+    // them is passed on, and each produces outcomes of its own.
+    FannedOut {
+        lease: String,
+        records: usize,
+    },
+}
+
+// A record still waiting in front of a stage when the pipeline was aborted
+#[derive(Debug)]
+pub struct Unfinished {
+    pub stage: usize,
+    pub delivery: Delivery,
+}
+
+pub struct Stage<'a> {
+    pub name: &'static str,
+    pub task: Box<dyn Task + Send + Sync + 'a>,
+    pub workers: usize,
+    // Capacity of the channel feeding this stage
+    pub buffer: usize,
+    pub retry: RetryConfig,
+}
+
+// Chain of stages joined by bounded channels. Records that make it through every stage,
+// and dead letters for records a stage gave up on, come out as outcomes. Workers are
+// futures polled by `run` rather than spawned tasks, so stages can borrow their configs.
+//
+// Once `abort` is cancelled every worker finishes the record it holds and stops, and
+// records that had not reached the end are handed back from `run`.
+pub struct Pipeline<'a> {
+    stages: Vec<Stage<'a>>,
+}
+
+impl<'a> Pipeline<'a> {
+    pub fn new(stages: Vec<Stage<'a>>) -> Self {
+        Self { stages }
+    }
+
+    // Channel for feeding the first stage
+    pub fn input(&self) -> (Sender<Delivery>, Receiver<Delivery>) {
+        channel(self.stages.first().map_or(1, |s| s.buffer))
+    }
+
+    pub fn workers(&self) -> usize {
+        self.stages.iter().map(|s| s.workers).sum()
+    }
+
+    // Processes records until `input` is closed and every stage has drained, or until
+    // aborted.
+    pub async fn run(
+        &self,
+        input: Receiver<Delivery>,
+        outcomes: Sender<Outcome>,
+        abort: &CancellationToken,
+    ) -> Vec<Unfinished> {
+        let unfinished = Mutex::new(vec![]);
+        let mut stages = vec![];
+        let mut input = Some(input);
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            let (next, next_input) = match self.stages.get(i + 1) {
+                Some(s) => {
+                    let (tx, rx) = channel(s.buffer);
+                    (Some(tx), Some(rx))
+                }
+                None => (None, None),
+            };
+
+            if let Some(input) = input.take() {
+                stages.push(run_stage(
+                    i,
+                    stage,
+                    input,
+                    next,
+                    outcomes.clone(),
+                    abort,
+                    &unfinished,
+                ));
+            }
+
+            input = next_input;
+        }
+
+        // A pipeline without stages passes records straight through
+        if let Some(input) = input {
+            let input = Mutex::new(input);
+
+            while let Some(delivery) = receive(&input, abort).await {
+                if outcomes.send(Outcome::Completed(delivery)).await.is_err() {
+                    break;
+                }
+            }
+
+            drain(0, input.into_inner(), &unfinished).await;
+        }
+
+        drop(outcomes);
+
+        // Stages are drained only once every worker has stopped, so nothing can be sent
+        // into a buffer after it was emptied
+        for (i, input) in join_all(stages).await.into_iter().enumerate() {
+            drain(i, input, &unfinished).await;
+        }
+
+        unfinished.into_inner()
+    }
+
+    pub async fn close(&self) -> Result<(), AppError> {
+        for stage in &self.stages {
+            stage.task.close().await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn drain(stage: usize, mut input: Receiver<Delivery>, unfinished: &Mutex<Vec<Unfinished>>) {
+    while let Ok(delivery) = input.try_recv() {
+        unfinished.lock().await.push(Unfinished { stage, delivery });
+    }
+}
+
+async fn receive(input: &Mutex<Receiver<Delivery>>, abort: &CancellationToken) -> Option<Delivery> {
+    select! {
+        biased;
+        _ = abort.cancelled() => None,
+        delivery = async { input.lock().await.recv().await } => delivery,
+    }
+}
+
+// Senders are owned here so the next stage sees its input close once every worker of
+// this one has finished.
+async fn run_stage(
+    index: usize,
+    stage: &Stage<'_>,
+    input: Receiver<Delivery>,
+    next: Option<Sender<Delivery>>,
+    outcomes: Sender<Outcome>,
+    abort: &CancellationToken,
+    unfinished: &Mutex<Vec<Unfinished>>,
+) -> Receiver<Delivery> {
+    let input = Mutex::new(input);
+    let next = next.map(|next| (index + 1, next));
+
+    join_all(
+        (0..stage.workers)
+            .map(|_| work(stage, &input, next.as_ref(), &outcomes, abort, unfinished)),
+    )
+    .await;
+
+    input.into_inner()
+}
+
+async fn work(
+    stage: &Stage<'_>,
+    input: &Mutex<Receiver<Delivery>>,
+    next: Option<&(usize, Sender<Delivery>)>,
+    outcomes: &Sender<Outcome>,
+    abort: &CancellationToken,
+    unfinished: &Mutex<Vec<Unfinished>>,
+) {
+    loop {
+        let Some(Delivery { lease, record }) = receive(input, abort).await else {
+            return;
+        };
+
+        // Everything logged while a task works on a record carries its uri and task id
+        let span = info_span!(
+            "task",
+            task = stage.name,
+            uri = %record.uri,
+            task_id = %record.task_id
+        );
+        let started = Instant::now();
+        let result = attempt(stage, record).instrument(span.clone()).await;
+        let elapsed = started.elapsed();
+        let outcome = match &result {
+            Attempt::Done(records) if records.is_empty() => "dropped",
+            Attempt::Done(_) => "completed",
+            Attempt::Deferred { .. } => "deferred",
+            Attempt::GaveUp(_) => "dead_lettered",
+        };
+
+        span.in_scope(|| match &result {
+            Attempt::Done(records) => debug!(
+                outcome,
+                records = records.len(),
+                elapsed_ms = elapsed.as_millis() as u64,
+                "task finished"
+            ),
+            Attempt::Deferred { delay, .. } => debug!(
+                outcome,
+                delay_ms = delay.as_millis() as u64,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "task deferred"
+            ),
+            Attempt::GaveUp(dead_letter) => warn!(
+                attempts = dead_letter.attempts,
+                error = %dead_letter.reason,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "task gave up"
+            ),
+        });
+
+        metrics().observe(
+            &TASK_DURATION,
+            &[("task", stage.name)],
+            elapsed.as_secs_f64(),
+        );
+        metrics().increment(
+            &TASK_RECORDS,
+            &[("task", stage.name), ("outcome", outcome)],
+            1.0,
+        );
+
+        let sent = match result {
+            Attempt::Done(records) => {
+                forward(lease, records, next, outcomes, abort, unfinished).await
+            }
+            Attempt::Deferred { record, delay } => outcomes
+                .send(Outcome::Deferred {
+                    delivery: Delivery { lease, record },
+                    delay,
+                })
+                .await
+                .is_ok(),
+            Attempt::GaveUp(dead_letter) => outcomes
+                .send(Outcome::Failed { lease, dead_letter })
+                .await
+                .is_ok(),
+        };
+
+        if !sent {
+            return;
+        }
+    }
+}
+
+// Passes the records a stage produced on, returning false once the receiving side is gone
+async fn forward(
+    lease: String,
+    records: Vec<Record>,
+    next: Option<&(usize, Sender<Delivery>)>,
+    outcomes: &Sender<Outcome>,
+    abort: &CancellationToken,
+    unfinished: &Mutex<Vec<Unfinished>>,
+) -> bool {
+    if records.len() != 1
+        && outcomes
+            .send(Outcome::FannedOut {
+                lease: lease.clone(),
+                records: records.len(),
+            })
+            .await
+            .is_err()
+    {
+        return false;
+    }
+
+    for record in records {
+        let delivery = Delivery {
+            lease: lease.clone(),
+            record,
+        };
+        let sent = match next {
+            Some((stage, next)) => select! {
+                permit = next.reserve() => match permit {
+                    Ok(permit) => {
+                        permit.send(delivery);
+                        true
+                    }
+                    Err(_) => false,
+                },
+                // The next stage may have stopped taking records already
+                _ = abort.cancelled() => {
+                    unfinished.lock().await.push(Unfinished { stage: *stage, delivery });
+                    true
+                }
+            },
+            None => outcomes.send(Outcome::Completed(delivery)).await.is_ok(),
+        };
+
+        if !sent {
+            return false;
+        }
+    }
+
+    true
+}
+
+// How a stage finished with a record
+enum Attempt {
+    Done(Vec<Record>),
+    Deferred { record: Record, delay: Duration },
+    GaveUp(DeadLetter),
+}
+
+// Runs the task of a stage on a record, retrying retryable errors after a backoff.
+// Throttled records aren't waited on here; they go back to the queue to be tried again
+// after the server's `Retry-After`. Each time counts as an attempt, so a host that never
+// stops throttling is given up on too.
+async fn attempt(stage: &Stage<'_>, record: Record) -> Attempt {
+    let mut errors = vec![];
+
+    for attempt in 1..=stage.retry.max_attempts {
+        let e = match stage.task.fan_out(record.clone()).await {
+            // A failure left on the record is kept there once attempts run out
+            Ok(records) => match records.iter().find_map(|r| stage.task.recorded_failure(r)) {
+                Some(e) if e.is_retryable() && attempt < stage.retry.max_attempts => e,
+                _ => return Attempt::Done(records),
+            },
+            Err(e @ AppError::Throttled { .. })
+                if record.deferrals + attempt < stage.retry.max_attempts =>
+            {
+                let delay = e.retry_after().map_or_else(
+                    || backoff(&stage.retry, record.deferrals + attempt),
+                    |after| after.min(Duration::from_millis(stage.retry.max_retry_after_ms)),
+                );
+
+                debug!(attempt, error = %e, "attempt throttled");
+
+                return Attempt::Deferred { record, delay };
+            }
+            Err(e) => e,
+        };
+        let retryable = e.is_retryable();
+
+        debug!(attempt, retryable, error = %e, "attempt failed");
+
+        errors.push(AttemptError {
+            task: stage.name.to_string(),
+            attempt,
+            error: e.to_string(),
+            retryable,
+            timestamp: Utc::now(),
+        });
+
+        if !retryable || matches!(e, AppError::Throttled { .. }) {
+            break;
+        }
+
+        if attempt < stage.retry.max_attempts {
+            metrics().increment(
+                &TASK_RECORDS,
+                &[("task", stage.name), ("outcome", "retried")],
+                1.0,
+            );
+            sleep(backoff(&stage.retry, attempt)).await;
+        }
+    }
+
+    Attempt::GaveUp(DeadLetter {
+        attempts: record.deferrals + errors.len() as u32,
+        record,
+        reason: errors.last().map(|e| e.error.clone()).unwrap_or_default(),
+        errors,
+        timestamp: Utc::now(),
+    })
+}
+
+// Wait before the attempt after `attempt`
+pub fn backoff(config: &RetryConfig, attempt: u32) -> Duration {
+    let exponential = config.initial_backoff_ms as f64 * config.multiplier.powi(attempt as i32 - 1);
+    let capped = exponential.min(config.max_backoff_ms as f64);
+
+    Duration::from_millis((capped * (1.0 - config.jitter * rand::random::<f64>())) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use tokio::{
+        sync::Semaphore,
+        time::{Instant, sleep},
+    };
+
+    use crate::types::{
+        error::AppError,
+        structs::{
+            metadata::http_response::{FetchError, FetchErrorKind, HttpResponse},
+            record::{HopType, RecordMetadata},
+        },
+    };
+
+    use super::*;
+
+    struct Counter {
+        seen: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Task for Counter {
+        async fn on_message(&self, message: Record) -> Result<Record, AppError> {
+            self.seen.fetch_add(1, Ordering::SeqCst);
+            Ok(message)
+        }
+    }
+
+    // Holds every record until a permit is released
+    struct Gate {
+        permits: Arc<Semaphore>,
+    }
+
+    #[async_trait]
+    impl Task for Gate {
+        async fn on_message(&self, message: Record) -> Result<Record, AppError> {
+            self.permits.acquire().await.unwrap().forget();
+            Ok(message)
+        }
+    }
+
+    struct Sleep;
+
+    #[async_trait]
+    impl Task for Sleep {
+        async fn on_message(&self, message: Record) -> Result<Record, AppError> {
+            sleep(Duration::from_millis(100)).await;
+
+            match message.uri.as_str() {
+                "fail" => Err(AppError::Generic("failed".to_string())),
+                _ => Ok(message),
+            }
+        }
+    }
+
+    // Fails with a retryable error until it has been called `failures` times
+    struct Flaky {
+        calls: AtomicUsize,
+        failures: usize,
+    }
+
+    #[async_trait]
+    impl Task for Flaky {
+        async fn on_message(&self, message: Record) -> Result<Record, AppError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(AppError::FetchError(503, message.uri));
+            }
+
+            match message.uri.as_str() {
+                "invalid" => Err(AppError::ParseError("invalid")),
+                _ => Ok(message),
+            }
+        }
+    }
+
+    // Always throttled, asking to be retried after the uri's number of milliseconds if
+    // it is one
+    struct Throttled {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Task for Throttled {
+        async fn on_message(&self, message: Record) -> Result<Record, AppError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            Err(AppError::Throttled {
+                status: 429,
+                retry_after: message.uri.parse().ok().map(Duration::from_millis),
+                uri: message.uri,
+            })
+        }
+    }
+
+    // Records a failed fetch on every record, a lookup failure for "dns" and a refused
+    // connection for anything else
+    struct Unreachable {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Task for Unreachable {
+        async fn on_message(&self, mut message: Record) -> Result<Record, AppError> {
+            let kind = match message.uri.as_str() {
+                "dns" => FetchErrorKind::Dns,
+                _ => FetchErrorKind::ConnectionRefused,
+            };
+
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            message.metadata.push(RecordMetadata::HttpResponse(Box::new(
+                HttpResponse::failed(FetchError::new(kind, "unreachable"), Utc::now()),
+            )));
+
+            Ok(message)
+        }
+
+        fn recorded_failure(&self, record: &Record) -> Option<AppError> {
+            record.fetch_error().cloned().map(AppError::FetchFailed)
+        }
+    }
+
+    // Spawns two children per record and drops records named "drop"
+    struct Split;
+
+    #[async_trait]
+    impl Task for Split {
+        async fn on_message(&self, message: Record) -> Result<Record, AppError> {
+            Ok(message)
+        }
+
+        async fn fan_out(&self, message: Record) -> Result<Vec<Record>, AppError> {
+            match message.uri.as_str() {
+                "drop" => Ok(vec![]),
+                uri => Ok((1..=2)
+                    .map(|i| message.child(format!("{}/{}", uri, i), HopType::Link))
+                    .collect()),
+            }
+        }
+    }
+
+    fn stage<'a>(
+        task: Box<dyn Task + Send + Sync + 'a>,
+        workers: usize,
+        buffer: usize,
+    ) -> Stage<'a> {
+        Stage {
+            name: "test",
+            task,
+            workers,
+            buffer,
+            retry: RetryConfig {
+                max_attempts: 3,
+                initial_backoff_ms: 1,
+                max_backoff_ms: 10,
+                multiplier: 2.0,
+                jitter: 0.5,
+                max_retry_after_ms: 1_000,
+            },
+        }
+    }
+
+    async fn run_one(pipeline: &Pipeline<'_>, uri: &str) -> Outcome {
+        let (input, rx) = pipeline.input();
+        let (outcomes, mut results) = channel::<Outcome>(1);
+        let abort = CancellationToken::new();
+
+        input.send(delivery(uri)).await.unwrap();
+        drop(input);
+
+        let (_, outcome) = tokio::join!(pipeline.run(rx, outcomes, &abort), results.recv());
+
+        outcome.unwrap()
+    }
+
+    fn delivery(uri: &str) -> Delivery {
+        Delivery {
+            lease: uri.to_string(),
+            record: record(uri),
+        }
+    }
+
+    fn record(uri: &str) -> Record {
+        Record {
+            uri: uri.to_string(),
+            task_id: "test".to_string(),
+            metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_stage_applies_backpressure() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let permits = Arc::new(Semaphore::new(0));
+        let pipeline = Pipeline::new(vec![
+            stage(Box::new(Counter { seen: seen.clone() }), 1, 1),
+            stage(
+                Box::new(Gate {
+                    permits: permits.clone(),
+                }),
+                1,
+                1,
+            ),
+        ]);
+
+        let (input, rx) = pipeline.input();
+        let (outcomes, mut results) = channel::<Outcome>(1);
+        let abort = CancellationToken::new();
+
+        let feed = async {
+            for i in 0..10 {
+                input.send(delivery(&i.to_string())).await.unwrap();
+            }
+
+            drop(input);
+        };
+
+        let check = async {
+            sleep(Duration::from_millis(100)).await;
+
+            // One record held by the gate, one waiting in front of it and one waiting
+            // for room after the counter
+            assert_eq!(seen.load(Ordering::SeqCst), 3);
+
+            permits.add_permits(10);
+
+            let mut done = 0;
+
+            while let Some(outcome) = results.recv().await {
+                assert!(matches!(outcome, Outcome::Completed(_)));
+                done += 1;
+            }
+
+            assert_eq!(done, 10);
+        };
+
+        tokio::join!(pipeline.run(rx, outcomes, &abort), feed, check);
+    }
+
+    #[tokio::test]
+    async fn test_workers_run_concurrently() {
+        let pipeline = Pipeline::new(vec![stage(Box::new(Sleep), 4, 4)]);
+
+        let (input, rx) = pipeline.input();
+        let (outcomes, mut results) = channel::<Outcome>(pipeline.workers());
+        let abort = CancellationToken::new();
+        let started = Instant::now();
+
+        let feed = async {
+            for uri in ["a", "b", "fail", "c"] {
+                input.send(delivery(uri)).await.unwrap();
+            }
+
+            drop(input);
+        };
+
+        let collect = async {
+            let mut failures = 0;
+
+            while let Some(outcome) = results.recv().await {
+                failures += matches!(outcome, Outcome::Failed { .. }) as usize;
+            }
+
+            failures
+        };
+
+        let (_, _, failures) = tokio::join!(pipeline.run(rx, outcomes, &abort), feed, collect);
+
+        assert_eq!(failures, 1);
+        assert!(started.elapsed() < Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_retries_then_succeeds() {
+        let pipeline = Pipeline::new(vec![stage(
+            Box::new(Flaky {
+                calls: AtomicUsize::new(0),
+                failures: 2,
+            }),
+            1,
+            1,
+        )]);
+
+        assert!(matches!(
+            run_one(&pipeline, "a").await,
+            Outcome::Completed(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_keeps_error_history() {
+        let pipeline = Pipeline::new(vec![stage(
+            Box::new(Flaky {
+                calls: AtomicUsize::new(0),
+                failures: 5,
+            }),
+            1,
+            1,
+        )]);
+
+        let Outcome::Failed { dead_letter, .. } = run_one(&pipeline, "a").await else {
+            panic!("record was not dead lettered");
+        };
+
+        assert_eq!(dead_letter.attempts, 3);
+        assert_eq!(dead_letter.errors.len(), 3);
+        assert!(
+            dead_letter
+                .errors
+                .iter()
+                .all(|e| e.retryable && e.task == "test")
+        );
+        assert_eq!(dead_letter.reason, "HTTP 503: a");
+
+        // Permanent errors are not retried
+        let pipeline = Pipeline::new(vec![stage(
+            Box::new(Flaky {
+                calls: AtomicUsize::new(0),
+                failures: 0,
+            }),
+            1,
+            1,
+        )]);
+
+        let Outcome::Failed { dead_letter, .. } = run_one(&pipeline, "invalid").await else {
+            panic!("record was not dead lettered");
+        };
+
+        assert_eq!(dead_letter.attempts, 1);
+        assert!(!dead_letter.errors[0].retryable);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_failures() {
+        for (uri, calls) in [("refused", 3), ("dns", 1)] {
+            let seen = Arc::new(AtomicUsize::new(0));
+            let pipeline = Pipeline::new(vec![stage(
+                Box::new(Unreachable {
+                    calls: seen.clone(),
+                }),
+                1,
+                1,
+            )]);
+
+            // The record keeps the error of its last attempt instead of being dead lettered
+            let Outcome::Completed(delivery) = run_one(&pipeline, uri).await else {
+                panic!("record did not complete");
+            };
+
+            assert_eq!(delivery.record.metadata.len(), 1);
+            assert!(delivery.record.fetch_error().is_some());
+            assert_eq!(seen.load(Ordering::SeqCst), calls);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_after() {
+        // Retry-After is capped by the stage, and the backoff stands in when there is none
+        for (uri, min, max) in [("200", 200, 200), ("5000", 1_000, 1_000), ("none", 0, 10)] {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let pipeline = Pipeline::new(vec![stage(
+                Box::new(Throttled {
+                    calls: calls.clone(),
+                }),
+                1,
+                1,
+            )]);
+            let started = Instant::now();
+
+            // Handed back instead of waited out or dead lettered
+            let Outcome::Deferred { delivery, delay } = run_one(&pipeline, uri).await else {
+                panic!("record was not deferred");
+            };
+
+            assert!(started.elapsed() < Duration::from_millis(100));
+            assert_eq!(delivery.record.uri, uri);
+            assert!(delay >= Duration::from_millis(min) && delay <= Duration::from_millis(max));
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deferrals_count_as_attempts() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let pipeline = Pipeline::new(vec![stage(
+            Box::new(Throttled {
+                calls: calls.clone(),
+            }),
+            1,
+            1,
+        )]);
+        let (input, rx) = pipeline.input();
+        let (outcomes, mut results) = channel::<Outcome>(1);
+        let abort = CancellationToken::new();
+
+        // Sent back twice already, so the third throttle is its last attempt
+        input
+            .send(Delivery {
+                lease: "a".to_string(),
+                record: Record {
+                    deferrals: 2,
+                    ..record("a")
+                },
+            })
+            .await
+            .unwrap();
+        drop(input);
+
+        let (_, outcome) = tokio::join!(pipeline.run(rx, outcomes, &abort), results.recv());
+        let Some(Outcome::Failed { dead_letter, .. }) = outcome else {
+            panic!("record was not dead lettered");
+        };
+
+        assert_eq!(dead_letter.attempts, 3);
+        assert_eq!(dead_letter.errors.len(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let config = RetryConfig {
+            max_attempts: 10,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_retry_after_ms: 0,
+        };
+
+        assert_eq!(backoff(&config, 1), Duration::from_millis(100));
+        assert_eq!(backoff(&config, 3), Duration::from_millis(400));
+        assert_eq!(backoff(&config, 8), Duration::from_millis(1000));
+
+        let config = RetryConfig {
+            jitter: 0.5,
+            ..config
+        };
+
+        for _ in 0..100 {
+            let delay = backoff(&config, 2);
+
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fan_out() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let pipeline = Pipeline::new(vec![
+            stage(Box::new(Split), 1, 1),
+            stage(Box::new(Counter { seen: seen.clone() }), 1, 1),
+        ]);
+
+        let (input, rx) = pipeline.input();
+        let (outcomes, mut results) = channel::<Outcome>(4);
+        let abort = CancellationToken::new();
+
+        let feed = async {
+            input.send(delivery("a")).await.unwrap();
+            input.send(delivery("drop")).await.unwrap();
+
+            drop(input);
+        };
+
+        let collect = async {
+            let mut all = vec![];
+
+            while let Some(outcome) = results.recv().await {
+                all.push(outcome);
+            }
+
+            all
+        };
+
+        let (_, _, all) = tokio::join!(pipeline.run(rx, outcomes, &abort), feed, collect);
+
+        assert!(matches!(&all[0], Outcome::FannedOut { lease, records: 2 } if lease == "a"));
+        assert!(
+            all.iter()
+                .any(|o| matches!(o, Outcome::FannedOut { lease, records: 0 } if lease == "drop"))
+        );
+
+        let children: Vec<&Delivery> = all
+            .iter()
+            .filter_map(|o| match o {
+                Outcome::Completed(d) => Some(d),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(children.len(), 2);
+        assert_eq!(seen.load(Ordering::SeqCst), 2);
+        assert!(children.iter().all(|d| d.lease == "a"
+            && d.record.depth == 1
+            && d.record.parent_uri.as_deref() == Some("a")
+            && d.record.task_id == "test"));
+    }
+
+    #[tokio::test]
+    async fn test_abort_returns_unfinished() {
+        let permits = Arc::new(Semaphore::new(0));
+        let pipeline = Pipeline::new(vec![
+            stage(
+                Box::new(Gate {
+                    permits: permits.clone(),
+                }),
+                1,
+                4,
+            ),
+            stage(Box::new(Sleep), 1, 4),
+        ]);
+
+        let (input, rx) = pipeline.input();
+        let (outcomes, mut results) = channel::<Outcome>(4);
+        let abort = CancellationToken::new();
+
+        let feed = async {
+            for i in 0..3 {
+                input.send(delivery(&i.to_string())).await.unwrap();
+            }
+
+            sleep(Duration::from_millis(50)).await;
+            abort.cancel();
+
+            // The record held by the gate is finished and passed on
+            permits.add_permits(1);
+        };
+
+        let collect = async {
+            let mut completed = 0;
+
+            while let Some(outcome) = results.recv().await {
+                completed += matches!(outcome, Outcome::Completed(_)) as usize;
+            }
+
+            completed
+        };
+
+        let (unfinished, _, completed) =
+            tokio::join!(pipeline.run(rx, outcomes, &abort), feed, collect);
+
+        assert_eq!(completed, 0);
+        assert_eq!(unfinished.len(), 3);
+        assert_eq!(unfinished.iter().filter(|u| u.stage == 1).count(), 1);
+        assert_eq!(unfinished.iter().filter(|u| u.stage == 0).count(), 2);
+    }
+}
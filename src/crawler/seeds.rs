@@ -0,0 +1,355 @@
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fmt::{self, Display},
+    path::PathBuf,
+};
+
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::{
+    fs::read_to_string,
+    io::{AsyncReadExt, stdin},
+};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::{
+    types::{
+        error::AppError,
+        structs::{record::HopType, seed::Seed},
+    },
+    utils::web::{fetch_http_simple, get_user_agent, normalize_url},
+};
+
+// Seeds without a scheme are resolved against this, so `example.com/a` becomes
+// `https://example.com/a`. Anything that still points at it was relative.
+const NO_ORIGIN: &str = "https://seed.invalid/";
+
+// Sitemap indexes nested deeper than this are not followed
+const MAX_SITEMAP_DEPTH: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeedSource {
+    // One url per line, `#` starts a comment
+    List(PathBuf),
+    // A header row naming a `url` column, and optionally `priority` and `max_depth`
+    Csv(PathBuf),
+    // A sitemap or sitemap index, expanded recursively
+    Sitemap(String),
+    // A url list read from standard input
+    Stdin,
+}
+
+impl SeedSource {
+    // `-` is stdin, http(s) urls are sitemaps, `.csv` files are CSV and anything else is
+    // a url list
+    pub fn parse(source: &str) -> Self {
+        if source == "-" {
+            return SeedSource::Stdin;
+        }
+
+        if source.starts_with("http://") || source.starts_with("https://") {
+            return SeedSource::Sitemap(source.to_string());
+        }
+
+        let path = PathBuf::from(source);
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => SeedSource::Csv(path),
+            _ => SeedSource::List(path),
+        }
+    }
+}
+
+impl Display for SeedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedSource::List(path) | SeedSource::Csv(path) => write!(f, "{}", path.display()),
+            SeedSource::Sitemap(url) => write!(f, "{}", url),
+            SeedSource::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CsvSeed {
+    #[serde(alias = "uri")]
+    url: String,
+    #[serde(default)]
+    priority: Option<i64>,
+    #[serde(default)]
+    max_depth: Option<u32>,
+}
+
+// Reads seeds from every source, normalized and without duplicates, highest priority
+// first
+pub async fn load_seeds(sources: &[SeedSource]) -> Result<Vec<Seed>, AppError> {
+    let client = Client::builder().user_agent(get_user_agent(None)).build()?;
+    let mut seeds = vec![];
+
+    for source in sources {
+        let loaded = match source {
+            SeedSource::List(path) => parse_list(&read_to_string(path).await?),
+            SeedSource::Csv(path) => parse_csv(&read_to_string(path).await?)?,
+            SeedSource::Sitemap(url) => expand_sitemap(&client, url).await?,
+            SeedSource::Stdin => {
+                let mut contents = String::new();
+
+                stdin().read_to_string(&mut contents).await?;
+                parse_list(&contents)
+            }
+        };
+
+        debug!(source = %source, seeds = loaded.len(), "loaded seeds");
+        seeds.extend(loaded);
+    }
+
+    Ok(prepare(seeds))
+}
+
+// Normalizes seeds, drops the ones that aren't crawlable urls and all but the first of
+// each duplicate, and orders the rest by priority
+pub fn prepare(seeds: Vec<Seed>) -> Vec<Seed> {
+    let mut seen = HashSet::new();
+    let mut prepared: Vec<Seed> = seeds
+        .into_iter()
+        .filter_map(|seed| match normalize_seed(&seed.uri, None) {
+            Some(uri) => Some(Seed { uri, ..seed }),
+            None => {
+                warn!(uri = %seed.uri, "skipping seed that isn't an absolute http(s) url");
+                None
+            }
+        })
+        .filter(|seed| seen.insert(seed.uri.clone()))
+        .collect();
+
+    // Stable, so seeds of equal priority keep the order they were read in
+    prepared.sort_by_key(|seed| Reverse(seed.priority));
+
+    prepared
+}
+
+fn normalize_seed(uri: &str, origin: Option<&Url>) -> Option<String> {
+    let no_origin = Url::parse(NO_ORIGIN).ok()?;
+    let mut url = normalize_url(origin.unwrap_or(&no_origin), uri).ok()?;
+
+    if url.host() == no_origin.host() || !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    // Fragments never reach the server, so they would only make duplicates look distinct
+    url.set_fragment(None);
+
+    Some(url.to_string())
+}
+
+fn parse_list(contents: &str) -> Vec<Seed> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(Seed::new)
+        .collect()
+}
+
+fn parse_csv(contents: &str) -> Result<Vec<Seed>, AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(contents.as_bytes());
+
+    reader
+        .deserialize::<CsvSeed>()
+        .map(|row| {
+            let row = row.map_err(|e| AppError::Generic(format!("invalid seed csv: {}", e)))?;
+
+            Ok(Seed {
+                uri: row.url,
+                priority: row.priority.unwrap_or_default(),
+                max_depth: row.max_depth,
+                hop: HopType::Seed,
+            })
+        })
+        .collect()
+}
+
+async fn expand_sitemap(client: &Client, url: &str) -> Result<Vec<Seed>, AppError> {
+    let mut visited = HashSet::new();
+    let mut seeds = vec![];
+
+    collect_sitemap(client, url.to_string(), 0, &mut visited, &mut seeds).await?;
+
+    Ok(seeds)
+}
+
+// Nested sitemaps that can't be read are skipped, only the one asked for must load
+fn collect_sitemap<'a>(
+    client: &'a Client,
+    url: String,
+    depth: usize,
+    visited: &'a mut HashSet<String>,
+    seeds: &'a mut Vec<Seed>,
+) -> BoxFuture<'a, Result<(), AppError>> {
+    Box::pin(async move {
+        if !visited.insert(url.clone()) {
+            return Ok(());
+        }
+
+        let body = fetch_http_simple(client.clone(), &url).await?;
+        let origin = Url::parse(&url)?;
+        let (nested, pages) = parse_sitemap(&String::from_utf8_lossy(&body), &origin)?;
+
+        seeds.extend(pages.iter().map(|uri| Seed {
+            hop: HopType::Sitemap,
+            ..Seed::new(uri)
+        }));
+
+        if depth >= MAX_SITEMAP_DEPTH {
+            if !nested.is_empty() {
+                warn!(sitemap = %url, "not following sitemaps nested this deep");
+            }
+
+            return Ok(());
+        }
+
+        for nested in nested {
+            if let Err(e) = collect_sitemap(client, nested.clone(), depth + 1, visited, seeds).await
+            {
+                warn!(sitemap = %nested, error = %e, "skipping sitemap that can't be read");
+            }
+        }
+
+        Ok(())
+    })
+}
+
+// Returns the sitemaps listed by an index and the pages listed by a url set
+fn parse_sitemap(xml: &str, origin: &Url) -> Result<(Vec<String>, Vec<String>), AppError> {
+    let document = roxmltree::Document::parse(xml)
+        .map_err(|e| AppError::Generic(format!("invalid sitemap {}: {}", origin, e)))?;
+    let mut sitemaps = vec![];
+    let mut pages = vec![];
+
+    for loc in document.descendants().filter(|n| n.has_tag_name("loc")) {
+        let Some(uri) = loc
+            .text()
+            .and_then(|text| normalize_seed(text, Some(origin)))
+        else {
+            continue;
+        };
+
+        match loc.parent_element().map(|p| p.tag_name().name()) {
+            Some("sitemap") => sitemaps.push(uri),
+            Some("url") => pages.push(uri),
+            _ => {}
+        }
+    }
+
+    Ok((sitemaps, pages))
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::{Method::GET, MockServer};
+
+    use super::*;
+
+    #[test]
+    fn test_prepare_seeds() {
+        let seeds = parse_list(
+            r#"
+            # docs
+            https://example.com/a#intro
+            example.com/b
+            https://example.com/a
+            /relative
+            ftp://example.com/file
+            "#,
+        );
+        let uris: Vec<String> = prepare(seeds).into_iter().map(|s| s.uri).collect();
+
+        assert_eq!(uris, vec!["https://example.com/a", "https://example.com/b"]);
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let seeds = parse_csv(
+            "url,priority,max_depth\n\
+             https://example.com/low,,\n\
+             https://example.com/high,10,2\n\
+             https://example.com/last,-9223372036854775808,\n",
+        )
+        .unwrap();
+        let seeds = prepare(seeds);
+
+        assert_eq!(seeds[0].uri, "https://example.com/high");
+        assert_eq!(seeds[0].priority, 10);
+        assert_eq!(seeds[0].max_depth, Some(2));
+        assert_eq!(seeds[1].priority, 0);
+        assert_eq!(seeds[1].max_depth, None);
+        assert_eq!(seeds[2].priority, i64::MIN);
+
+        assert!(parse_csv("priority\n1\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(SeedSource::parse("-"), SeedSource::Stdin);
+        assert_eq!(
+            SeedSource::parse("seeds.CSV"),
+            SeedSource::Csv(PathBuf::from("seeds.CSV"))
+        );
+        assert_eq!(
+            SeedSource::parse("seeds.txt"),
+            SeedSource::List(PathBuf::from("seeds.txt"))
+        );
+        assert_eq!(
+            SeedSource::parse("https://example.com/sitemap.xml"),
+            SeedSource::Sitemap("https://example.com/sitemap.xml".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expand_sitemap_index() {
+        let server = MockServer::start();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/sitemap.xml");
+            then.status(200).body(format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+                <sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+                    <sitemap><loc>{}</loc></sitemap>
+                    <sitemap><loc>/missing.xml</loc></sitemap>
+                    <sitemap><loc>/sitemap.xml</loc></sitemap>
+                </sitemapindex>"#,
+                server.url("/pages.xml")
+            ));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/pages.xml");
+            then.status(200).body(
+                r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+                    <url><loc>/a</loc><lastmod>2024-01-01</lastmod></url>
+                    <url><loc>/b</loc></url>
+                </urlset>"#,
+            );
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/missing.xml");
+            then.status(404);
+        });
+
+        let seeds = load_seeds(&[
+            SeedSource::Sitemap(server.url("/sitemap.xml")),
+            SeedSource::Sitemap(server.url("/pages.xml")),
+        ])
+        .await
+        .unwrap();
+        let uris: Vec<String> = seeds.iter().map(|s| s.uri.clone()).collect();
+
+        assert!(seeds.iter().all(|s| s.hop == HopType::Sitemap));
+        assert_eq!(uris, vec![server.url("/a"), server.url("/b")]);
+    }
+}
@@ -1,4 +1,5 @@
-mod services;
-mod tasks;
-mod types;
-mod utils;
+pub mod crawler;
+pub mod services;
+pub mod tasks;
+pub mod types;
+pub mod utils;
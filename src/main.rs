@@ -1,3 +1,218 @@
-fn main() {
-    println!("Hello, world!");
+use std::{path::PathBuf, sync::Arc};
+
+use aetherscope::{
+    crawler::{
+        control::CrawlControl,
+        crawl::{open_queue, resolve_task_id},
+        job::{open_job_store, purge_job, run_job},
+        seeds::{SeedSource, load_seeds},
+    },
+    services::http::{
+        admin::{Admin, serve as serve_admin},
+        metrics::start as start_metrics,
+    },
+    types::{
+        configs::{crawl_config::CrawlConfig, queues::queue_config::QueueConfig},
+        error::AppError,
+    },
+    utils::logging::init_logging,
+};
+use clap::{Parser, Subcommand};
+use tokio::{
+    select,
+    signal::{
+        ctrl_c,
+        unix::{SignalKind, signal},
+    },
+    spawn,
+};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Crawl outward from a list of seed urls
+    Crawl {
+        /// Path to the crawl config file
+        #[arg(long)]
+        config: PathBuf,
+        /// Where to read seeds from: a file with one url per line, a .csv file with url,
+        /// priority and max_depth columns, a sitemap url, or - for stdin. May be repeated.
+        #[arg(long, required = true)]
+        seeds: Vec<String>,
+        /// Overrides the task id from the config file
+        #[arg(long)]
+        task_id: Option<String>,
+    },
+    /// Serve the admin API, starting a crawl for each task id seeds are submitted under
+    Serve {
+        /// Path to the crawl config file shared by every crawl
+        #[arg(long)]
+        config: PathBuf,
+    },
+    /// Delete a crawl's job, dedup entries, stored bodies and checkpoint
+    Purge {
+        /// Path to the crawl config file
+        #[arg(long)]
+        config: PathBuf,
+        /// Task id of the crawl to delete
+        #[arg(long)]
+        task_id: String,
+    },
+    /// Print depth, rates and per-task counts of the durable queue crawls share. A memory
+    /// queue lives inside its crawl, so ask the admin API of the process running it instead.
+    Status {
+        /// Path to the crawl config file
+        #[arg(long)]
+        config: PathBuf,
+        /// Only count the messages of this crawl
+        #[arg(long)]
+        task_id: Option<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), AppError> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Crawl {
+            config,
+            seeds,
+            task_id,
+        } => {
+            let mut config = CrawlConfig::from_file(&config).await?;
+
+            if task_id.is_some() {
+                config.task_id = task_id;
+            }
+
+            init_logging(&config.logging)?;
+
+            let sources: Vec<SeedSource> = seeds.iter().map(|s| SeedSource::parse(s)).collect();
+            let seeds = load_seeds(&sources).await?;
+            let task_id = resolve_task_id(&config).await?;
+            let jobs = open_job_store(&config.jobs).await?;
+            let control = CrawlControl::new();
+
+            spawn(cancel_on_signal(control.shutdown().clone()));
+
+            let metrics = match &config.metrics {
+                Some(metrics) => Some(start_metrics(metrics.listen).await?),
+                None => None,
+            };
+
+            let summary = run_job(jobs.as_ref(), &config, &task_id, seeds, &control).await;
+
+            if let Some(metrics) = metrics {
+                metrics.abort();
+            }
+
+            let summary = summary?;
+            let state = match summary.interrupted {
+                true => "stopped",
+                false => "finished",
+            };
+
+            println!(
+                "crawl {} {}: {} pages, {} failures ({} dead lettered), {} uris discovered, {} admitted",
+                task_id,
+                state,
+                summary.pages,
+                summary.failures,
+                summary.dead_lettered,
+                summary.discovered,
+                summary.admitted
+            );
+
+            if summary.resumed > 0 || summary.requeued > 0 {
+                println!(
+                    "{} records resumed from a checkpoint, {} requeued",
+                    summary.resumed, summary.requeued
+                );
+            }
+        }
+        Command::Serve { config } => {
+            let config = CrawlConfig::from_file(&config).await?;
+
+            init_logging(&config.logging)?;
+
+            let shutdown = CancellationToken::new();
+
+            spawn(cancel_on_signal(shutdown.clone()));
+
+            let metrics = match &config.metrics {
+                Some(metrics) => Some(start_metrics(metrics.listen).await?),
+                None => None,
+            };
+
+            let listen = config.admin.listen;
+            let admin = Arc::new(Admin::new(config).await?);
+
+            let served = serve_admin(admin.clone(), listen, shutdown).await;
+
+            admin.shutdown().await;
+
+            if let Some(metrics) = metrics {
+                metrics.abort();
+            }
+
+            served?;
+        }
+        Command::Purge { config, task_id } => {
+            let config = CrawlConfig::from_file(&config).await?;
+
+            init_logging(&config.logging)?;
+
+            let jobs = open_job_store(&config.jobs).await?;
+
+            purge_job(jobs.as_ref(), &config, &task_id).await?;
+            println!("purged crawl {}", task_id);
+        }
+        Command::Status { config, task_id } => {
+            let config = CrawlConfig::from_file(&config).await?;
+
+            init_logging(&config.logging)?;
+
+            if let QueueConfig::Memory(_) = config.queue {
+                return Err(AppError::InvalidConfig {
+                    field: "queue".to_string(),
+                    message: "a memory queue only exists inside the crawl using it, read its status from the admin API".to_string(),
+                });
+            }
+
+            let queue = open_queue(&config.queue, task_id.as_deref()).await?;
+
+            println!("{}", queue.status().await?);
+        }
+    }
+
+    Ok(())
+}
+
+// Starts a graceful shutdown on the first SIGINT or SIGTERM. Later signals are ignored
+// so a second Ctrl-C can't cut the checkpoint short.
+async fn cancel_on_signal(shutdown: CancellationToken) -> Result<(), AppError> {
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    loop {
+        select! {
+            signal = ctrl_c() => signal?,
+            _ = terminate.recv() => {}
+        }
+
+        match shutdown.is_cancelled() {
+            true => eprintln!("already shutting down"),
+            false => {
+                eprintln!("shutting down, waiting for in-flight records");
+                shutdown.cancel();
+            }
+        }
+    }
 }
@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use tokio::{
+    fs::{File, OpenOptions, create_dir_all},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+
+use crate::types::{
+    error::AppError, structs::dead_letter::DeadLetter, traits::dead_letter_sink::DeadLetterSink,
+};
+
+// Appends dead letters to a file as JSON lines, so they can be inspected with standard
+// tools and replayed later.
+pub struct FileDeadLetterSink {
+    file: Mutex<File>,
+}
+
+impl FileDeadLetterSink {
+    pub async fn new(path: PathBuf) -> Result<Self, AppError> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+#[async_trait]
+impl DeadLetterSink for FileDeadLetterSink {
+    async fn put(&self, dead_letter: DeadLetter) -> Result<(), AppError> {
+        let mut line = serde_json::to_vec(&dead_letter)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().await;
+
+        file.write_all(&line).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use chrono::Utc;
+    use tokio::fs::read_to_string;
+    use uuid::Uuid;
+
+    use crate::types::structs::{
+        dead_letter::AttemptError,
+        record::{HopType, Record},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_appends_json_lines() {
+        let path = temp_dir()
+            .join(Uuid::new_v4().to_string())
+            .join("dead_letters.jsonl");
+        let sink = FileDeadLetterSink::new(path.clone()).await.unwrap();
+
+        for uri in ["http://example.com/a", "http://example.com/b"] {
+            sink.put(DeadLetter {
+                record: Record {
+                    uri: uri.to_string(),
+                    task_id: "test".to_string(),
+                    metadata: vec![],
+                    parent_uri: None,
+                    depth: 0,
+                    max_depth: None,
+                    seed_uri: None,
+                    hop: HopType::Seed,
+                    discovered_at: None,
+                    deferrals: 0,
+                },
+                attempts: 1,
+                reason: "boom".to_string(),
+                errors: vec![AttemptError {
+                    task: "http_fetcher".to_string(),
+                    attempt: 1,
+                    error: "boom".to_string(),
+                    retryable: false,
+                    timestamp: Utc::now(),
+                }],
+                timestamp: Utc::now(),
+            })
+            .await
+            .unwrap();
+        }
+
+        let contents = read_to_string(&path).await.unwrap();
+        let dead_letters: Vec<DeadLetter> = contents
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[1].record.uri, "http://example.com/b");
+        assert_eq!(dead_letters[1].errors[0].task, "http_fetcher");
+    }
+}
@@ -0,0 +1,833 @@
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::Utc;
+use futures::future::join_all;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{net::TcpListener, spawn, sync::Mutex, task::JoinHandle};
+use tokio_util::{io::ReaderStream, sync::CancellationToken};
+use tracing::{error, info};
+
+use crate::{
+    crawler::{
+        control::CrawlControl,
+        crawl::open_queue,
+        job::{abandon, crawl, finish_job, open_job_store, purge_job, start_job},
+        seeds::prepare,
+    },
+    types::{
+        configs::{
+            crawl_config::{CrawlConfig, TaskConfig},
+            job_config::JobConfig,
+            queues::queue_config::QueueConfig,
+        },
+        error::AppError,
+        structs::{
+            crawl_job::{CrawlJob, JobState},
+            metadata::http_response::HttpResponse,
+            queue_status::QueueStatus,
+            seed::Seed,
+        },
+        traits::{job_store::JobStore, queue::Queue, validate::Validate},
+    },
+    utils::dependencies::dependencies,
+};
+
+// The latest crawl of a job started by this process
+struct Run {
+    control: Arc<CrawlControl>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SeedsRequest {
+    #[serde(default)]
+    pub uris: Vec<String>,
+    // Seeds with a priority or depth limit of their own
+    #[serde(default)]
+    pub seeds: Vec<Seed>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UriQuery {
+    pub uri: String,
+}
+
+// Runs crawl jobs on behalf of the admin API. Every job starts from the same config,
+// with its own overrides applied. Seeds submitted for a job with a running crawl are fed
+// into it, otherwise a new crawl of the job is started with them.
+pub struct Admin {
+    config: CrawlConfig,
+    // The durable queue crawls share. A memory queue lives inside each crawl instead.
+    queue: Option<Arc<dyn Queue>>,
+    jobs: Arc<dyn JobStore>,
+    runs: Mutex<HashMap<String, Run>>,
+}
+
+impl Admin {
+    // Jobs left running by a process that stopped without finishing them are failed
+    pub async fn new(config: CrawlConfig) -> Result<Self, AppError> {
+        let queue = match &config.queue {
+            QueueConfig::Memory(_) => None,
+            queue => Some(open_queue(queue, None).await?),
+        };
+        let jobs = open_job_store(&config.jobs).await?;
+
+        for mut job in jobs.list().await? {
+            if job.is_active() {
+                abandon(&mut job)?;
+                jobs.put(&job).await?;
+            }
+        }
+
+        Ok(Self {
+            config,
+            queue,
+            jobs,
+            runs: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // Creates a job, or replaces the overrides of one that isn't running
+    pub async fn configure(
+        &self,
+        task_id: &str,
+        config: JobConfig,
+    ) -> Result<(bool, CrawlJob), AppError> {
+        config.validate()?;
+        config.apply(&self.config, task_id)?;
+
+        let _runs = self.runs.lock().await;
+        let (created, job) = match self.jobs.get(task_id).await? {
+            Some(job) if job.is_active() => {
+                return Err(AppError::InvalidJobTransition {
+                    task_id: task_id.to_string(),
+                    from: job.state,
+                    to: JobState::Created,
+                });
+            }
+            Some(mut job) => {
+                job.config = config;
+                job.updated_at = Utc::now();
+                (false, job)
+            }
+            None => (true, CrawlJob::new(task_id, config)),
+        };
+
+        self.jobs.put(&job).await?;
+
+        Ok((created, job))
+    }
+
+    pub async fn submit(&self, task_id: &str, seeds: Vec<Seed>) -> Result<CrawlJob, AppError> {
+        let mut runs = self.runs.lock().await;
+
+        if let Some(run) = runs.get(task_id)
+            && run.control.submit(seeds.clone())
+        {
+            return self.job(task_id).await;
+        }
+
+        let config = start_job(self.jobs.as_ref(), &self.config, task_id).await?;
+        let control = Arc::new(CrawlControl::new());
+        let handle = spawn(run_job(
+            self.jobs.clone(),
+            config,
+            task_id.to_string(),
+            seeds,
+            control.clone(),
+        ));
+
+        runs.insert(
+            task_id.to_string(),
+            Run {
+                control,
+                handle: Some(handle),
+            },
+        );
+
+        self.job(task_id).await
+    }
+
+    pub async fn job(&self, task_id: &str) -> Result<CrawlJob, AppError> {
+        self.jobs
+            .get(task_id)
+            .await?
+            .ok_or_else(|| AppError::UnknownJob(task_id.to_string()))
+    }
+
+    pub async fn jobs(&self) -> Result<Vec<CrawlJob>, AppError> {
+        self.jobs.list().await
+    }
+
+    pub async fn pause(&self, task_id: &str) -> Result<CrawlJob, AppError> {
+        self.set_paused(task_id, true).await
+    }
+
+    pub async fn resume(&self, task_id: &str) -> Result<CrawlJob, AppError> {
+        self.set_paused(task_id, false).await
+    }
+
+    async fn set_paused(&self, task_id: &str, paused: bool) -> Result<CrawlJob, AppError> {
+        let runs = self.runs.lock().await;
+        let mut job = self.job(task_id).await?;
+
+        match paused {
+            true => job.transition(JobState::Paused)?,
+            false => job.transition(JobState::Running)?,
+        }
+
+        if let Some(run) = runs.get(task_id) {
+            match paused {
+                true => run.control.pause(),
+                false => run.control.resume(),
+            }
+        }
+
+        self.jobs.put(&job).await?;
+
+        Ok(job)
+    }
+
+    // The job stays active until its in-flight records have drained
+    pub async fn cancel(&self, task_id: &str) -> Result<CrawlJob, AppError> {
+        let runs = self.runs.lock().await;
+        let job = self.job(task_id).await?;
+
+        if !job.is_active() {
+            return Err(AppError::InvalidJobTransition {
+                task_id: task_id.to_string(),
+                from: job.state,
+                to: JobState::Finished,
+            });
+        }
+
+        if let Some(run) = runs.get(task_id) {
+            run.control.cancel();
+        }
+
+        Ok(job)
+    }
+
+    // Deletes a job that isn't running along with everything its crawls stored
+    pub async fn purge(&self, task_id: &str) -> Result<(), AppError> {
+        let mut runs = self.runs.lock().await;
+
+        self.job(task_id).await?;
+        purge_job(self.jobs.as_ref(), &self.config, task_id).await?;
+        runs.remove(task_id);
+
+        Ok(())
+    }
+
+    // Latest response fetched for `uri` by the job's latest crawl in this process
+    pub async fn response(&self, task_id: &str, uri: &str) -> Option<HttpResponse> {
+        self.runs.lock().await.get(task_id)?.control.response(uri)
+    }
+
+    // Object stores the fetch stages write bodies to
+    fn body_stores(&self) -> Vec<&str> {
+        self.config
+            .tasks
+            .iter()
+            .filter(|t| {
+                matches!(
+                    t.task,
+                    TaskConfig::HttpFetcher(_) | TaskConfig::HeadlessBrowser(_)
+                )
+            })
+            .filter_map(|t| t.task.object_store())
+            .collect()
+    }
+
+    // Counts of the shared queue, or summed over the frontiers of this process's crawls
+    pub async fn status(&self) -> Result<QueueStatus, AppError> {
+        if let Some(queue) = &self.queue {
+            return queue.status().await;
+        }
+
+        let controls: Vec<Arc<CrawlControl>> = self
+            .runs
+            .lock()
+            .await
+            .values()
+            .map(|run| run.control.clone())
+            .collect();
+        let mut status = QueueStatus::default();
+
+        for control in controls {
+            if let Some(run_status) = control.queue_status().await {
+                status.merge(run_status?);
+            }
+        }
+
+        Ok(status)
+    }
+
+    // Cancels every crawl and waits for them to drain and checkpoint
+    pub async fn shutdown(&self) {
+        let handles: Vec<JoinHandle<()>> = {
+            let mut runs = self.runs.lock().await;
+
+            runs.values_mut()
+                .filter_map(|run| {
+                    run.control.cancel();
+                    run.handle.take()
+                })
+                .collect()
+        };
+
+        join_all(handles).await;
+    }
+}
+
+async fn run_job(
+    jobs: Arc<dyn JobStore>,
+    config: CrawlConfig,
+    task_id: String,
+    seeds: Vec<Seed>,
+    control: Arc<CrawlControl>,
+) {
+    let result = crawl(&config, seeds, &control).await;
+
+    // A crawl that failed to start never closed its seeds
+    control.close();
+
+    match &result {
+        Ok(summary) => info!(task_id = %task_id, pages = summary.pages, "job crawl returned"),
+        Err(e) => error!(task_id = %task_id, error = %e, "job crawl failed"),
+    }
+
+    if let Err(e) = finish_job(jobs.as_ref(), &task_id, &result).await {
+        error!(task_id = %task_id, error = %e, "failed to record the end of a job crawl");
+    }
+}
+
+pub struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn not_found(message: String) -> Self {
+        ApiError(StatusCode::NOT_FOUND, message)
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(e: AppError) -> Self {
+        let status = match e {
+            AppError::UnknownJob(_) => StatusCode::NOT_FOUND,
+            AppError::InvalidJobTransition { .. } | AppError::ActiveJob(_) => StatusCode::CONFLICT,
+            AppError::InvalidConfig { .. } => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        ApiError(status, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+pub fn router(admin: Arc<Admin>) -> Router {
+    Router::new()
+        .route("/crawls", get(list_jobs))
+        .route(
+            "/crawls/{task_id}",
+            get(get_job).put(configure_job).delete(purge),
+        )
+        .route("/crawls/{task_id}/seeds", post(submit_seeds))
+        .route("/crawls/{task_id}/pause", post(pause))
+        .route("/crawls/{task_id}/resume", post(resume))
+        .route("/crawls/{task_id}/cancel", post(cancel))
+        .route("/crawls/{task_id}/response", get(get_response))
+        .route("/crawls/{task_id}/body", get(get_body))
+        .route("/queue/status", get(queue_status))
+        .with_state(admin)
+}
+
+// Serves the admin API until `shutdown` is cancelled
+pub async fn serve(
+    admin: Arc<Admin>,
+    listen: SocketAddr,
+    shutdown: CancellationToken,
+) -> Result<(), AppError> {
+    let listener = TcpListener::bind(listen).await?;
+
+    info!(listen = %listen, "serving admin api");
+
+    axum::serve(listener, router(admin))
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await?;
+
+    Ok(())
+}
+
+async fn list_jobs(State(admin): State<Arc<Admin>>) -> Result<Json<Vec<CrawlJob>>, ApiError> {
+    Ok(Json(admin.jobs().await?))
+}
+
+async fn get_job(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+) -> Result<Json<CrawlJob>, ApiError> {
+    Ok(Json(admin.job(&task_id).await?))
+}
+
+async fn configure_job(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+    Json(config): Json<JobConfig>,
+) -> Result<(StatusCode, Json<CrawlJob>), ApiError> {
+    let (created, job) = admin.configure(&task_id, config).await?;
+    let status = match created {
+        true => StatusCode::CREATED,
+        false => StatusCode::OK,
+    };
+
+    Ok((status, Json(job)))
+}
+
+async fn purge(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    admin.purge(&task_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn submit_seeds(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+    Json(request): Json<SeedsRequest>,
+) -> Result<(StatusCode, Json<CrawlJob>), ApiError> {
+    if request.uris.is_empty() && request.seeds.is_empty() {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "uris and seeds must not both be empty".to_string(),
+        ));
+    }
+
+    let mut seeds = request.seeds;
+
+    seeds.extend(request.uris.iter().map(|uri| Seed::new(uri)));
+
+    let seeds = prepare(seeds);
+
+    if seeds.is_empty() {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "no seed is an absolute http(s) url".to_string(),
+        ));
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(admin.submit(&task_id, seeds).await?),
+    ))
+}
+
+async fn pause(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+) -> Result<Json<CrawlJob>, ApiError> {
+    Ok(Json(admin.pause(&task_id).await?))
+}
+
+async fn resume(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+) -> Result<Json<CrawlJob>, ApiError> {
+    Ok(Json(admin.resume(&task_id).await?))
+}
+
+async fn cancel(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+) -> Result<Json<CrawlJob>, ApiError> {
+    Ok(Json(admin.cancel(&task_id).await?))
+}
+
+async fn get_response(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+    Query(query): Query<UriQuery>,
+) -> Result<Json<HttpResponse>, ApiError> {
+    admin
+        .response(&task_id, &query.uri)
+        .await
+        .map(Json)
+        .ok_or_else(|| unknown_response(&task_id, &query.uri))
+}
+
+// Streams the stored body of the latest response for a uri
+async fn get_body(
+    State(admin): State<Arc<Admin>>,
+    Path(task_id): Path<String>,
+    Query(query): Query<UriQuery>,
+) -> Result<Response, ApiError> {
+    let response = admin
+        .response(&task_id, &query.uri)
+        .await
+        .ok_or_else(|| unknown_response(&task_id, &query.uri))?;
+    let key = response
+        .key
+        .ok_or_else(|| ApiError::not_found(format!("no body was stored for {}", query.uri)))?;
+    let content_type = response
+        .response_headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+        .map_or("application/octet-stream".to_string(), |(_, v)| v.clone());
+
+    for name in admin.body_stores() {
+        let store = dependencies().lock().await.get_object_store(name)?;
+
+        if let Ok(reader) = store.get_stream(&key).await {
+            let body = Body::from_stream(ReaderStream::new(reader));
+
+            return Ok(([(CONTENT_TYPE, content_type)], body).into_response());
+        }
+    }
+
+    Err(ApiError::not_found(format!(
+        "body {} is in none of the object stores",
+        key
+    )))
+}
+
+async fn queue_status(State(admin): State<Arc<Admin>>) -> Result<Json<QueueStatus>, ApiError> {
+    Ok(Json(admin.status().await?))
+}
+
+fn unknown_response(task_id: &str, uri: &str) -> ApiError {
+    ApiError::not_found(format!("crawl {} has not fetched {}", task_id, uri))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, path::PathBuf, time::Duration};
+
+    use httpmock::{Method::GET, MockServer};
+    use tokio::time::sleep;
+    use uuid::Uuid;
+
+    use super::*;
+
+    // Base url of the API and the directory bodies are stored in
+    async fn start(server: &MockServer) -> (String, PathBuf) {
+        let store = Uuid::new_v4().to_string();
+        let path = temp_dir().join(&store);
+        let config: CrawlConfig = toml::from_str(&format!(
+            r#"
+            [[object_stores]]
+            type = "file_system"
+            name = "{store}"
+            path = "{path}"
+
+            [[tasks]]
+            type = "http_fetcher"
+            object_store = "{store}"
+
+            [[tasks]]
+            type = "url_extractor"
+            object_store = "{store}"
+
+            [filters.unique.bloom_filter]
+            "#,
+            path = path.display()
+        ))
+        .unwrap();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(200)
+                .header("content-type", "text/html")
+                .body(r#"<a href="/a">a</a>"#);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/a");
+            then.status(200).body("The end.");
+        });
+
+        let admin = Arc::new(Admin::new(config).await.unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        spawn(async move { axum::serve(listener, router(admin)).await });
+
+        (format!("http://{}", addr), path)
+    }
+
+    async fn wait_until_done(client: &reqwest::Client, url: &str) -> serde_json::Value {
+        loop {
+            let crawl: serde_json::Value =
+                client.get(url).send().await.unwrap().json().await.unwrap();
+
+            if crawl["state"] != "running" {
+                return crawl;
+            }
+
+            sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seed_and_inspect_crawl() {
+        let server = MockServer::start();
+        let (base, _) = start(&server).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(format!("{}/crawls/docs/seeds", base))
+            .json(&json!({ "uris": [server.url("/")] }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let crawl = wait_until_done(&client, &format!("{}/crawls/docs", base)).await;
+
+        assert_eq!(crawl["state"], "finished");
+        assert_eq!(crawl["counters"]["pages"], 2);
+
+        let response: HttpResponse = client
+            .get(format!("{}/crawls/docs/response", base))
+            .query(&[("uri", server.url("/"))])
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, Some(200));
+
+        let body = client
+            .get(format!("{}/crawls/docs/body", base))
+            .query(&[("uri", server.url("/"))])
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(body.headers()[CONTENT_TYPE.as_str()], "text/html");
+        assert_eq!(body.text().await.unwrap(), r#"<a href="/a">a</a>"#);
+
+        let status = client
+            .get(format!("{}/queue/status", base))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(status.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_cancel() {
+        let server = MockServer::start();
+        let (base, _) = start(&server).await;
+        let client = reqwest::Client::new();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/slow");
+            then.status(200)
+                .delay(Duration::from_millis(500))
+                .body("zzz");
+        });
+
+        let response = client
+            .post(format!("{}/crawls/missing/pause", base))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        client
+            .post(format!("{}/crawls/docs/seeds", base))
+            .json(&json!({ "uris": [server.url("/slow")] }))
+            .send()
+            .await
+            .unwrap();
+
+        let job: serde_json::Value = client
+            .post(format!("{}/crawls/docs/pause", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(job["state"], "paused");
+
+        // The slow page is queued or being fetched by the crawl's own frontier
+        loop {
+            let status: serde_json::Value = client
+                .get(format!("{}/queue/status", base))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            let task = &status["tasks"]["docs"];
+
+            if task.is_object() {
+                assert_eq!(
+                    task["depth"].as_u64().unwrap() + task["in_flight"].as_u64().unwrap(),
+                    1
+                );
+                break;
+            }
+
+            sleep(Duration::from_millis(50)).await;
+        }
+
+        let response = client
+            .post(format!("{}/crawls/docs/pause", base))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        client
+            .post(format!("{}/crawls/docs/cancel", base))
+            .send()
+            .await
+            .unwrap();
+
+        let job = loop {
+            let job: serde_json::Value = client
+                .get(format!("{}/crawls/docs", base))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+
+            if job["state"] != "paused" {
+                break job;
+            }
+
+            sleep(Duration::from_millis(50)).await;
+        };
+
+        assert_eq!(job["state"], "finished");
+        assert_eq!(job["interrupted"], true);
+    }
+
+    #[tokio::test]
+    async fn test_job_overrides() {
+        let server = MockServer::start();
+        let (base, _) = start(&server).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .put(format!("{}/crawls/docs", base))
+            .json(&json!({ "fetcher": "headless_browser" }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = client
+            .put(format!("{}/crawls/docs", base))
+            .json(&json!({ "scope": { "hosts": ["example.org"] } }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        client
+            .post(format!("{}/crawls/docs/seeds", base))
+            .json(&json!({ "uris": [server.url("/")] }))
+            .send()
+            .await
+            .unwrap();
+
+        let job = wait_until_done(&client, &format!("{}/crawls/docs", base)).await;
+
+        assert_eq!(job["state"], "finished");
+        assert_eq!(job["counters"]["runs"], 1);
+        assert_eq!(job["counters"]["pages"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_purge_crawl() {
+        let server = MockServer::start();
+        let (base, path) = start(&server).await;
+        let client = reqwest::Client::new();
+
+        for task_id in ["docs", "blog"] {
+            client
+                .post(format!("{}/crawls/{}/seeds", base, task_id))
+                .json(&json!({ "uris": [server.url("/")] }))
+                .send()
+                .await
+                .unwrap();
+
+            let job = wait_until_done(&client, &format!("{}/crawls/{}", base, task_id)).await;
+
+            // Each crawl dedups on its own, so both fetch every page
+            assert_eq!(job["counters"]["pages"], 2);
+        }
+
+        let response: HttpResponse = client
+            .get(format!("{}/crawls/docs/response", base))
+            .query(&[("uri", server.url("/"))])
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let key = response.key.unwrap();
+
+        assert!(key.starts_with("docs/"));
+        assert!(path.join(&key).exists());
+
+        let response = client
+            .delete(format!("{}/crawls/docs", base))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(!path.join("docs").exists());
+        assert!(path.join("blog").exists());
+
+        for task_id in ["docs", "missing"] {
+            let response = client
+                .delete(format!("{}/crawls/{}", base, task_id))
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        let body = client
+            .get(format!("{}/crawls/blog/body", base))
+            .query(&[("uri", server.url("/"))])
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(body.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,72 @@
+use std::net::SocketAddr;
+
+use axum::{Router, http::header::CONTENT_TYPE, response::IntoResponse, routing::get};
+use tokio::{net::TcpListener, spawn, task::JoinHandle};
+use tracing::{error, info};
+
+use crate::{types::error::AppError, utils::metrics::metrics};
+
+pub fn router() -> Router {
+    Router::new().route("/metrics", get(render))
+}
+
+// Binds before returning, so a taken port fails the command before any crawling starts,
+// then serves in the background until the handle is aborted
+pub async fn start(listen: SocketAddr) -> Result<JoinHandle<()>, AppError> {
+    let listener = TcpListener::bind(listen).await?;
+
+    info!(listen = %listen, "serving metrics");
+
+    Ok(spawn(async move {
+        if let Err(e) = axum::serve(listener, router()).await {
+            error!(error = %e, "metrics server stopped");
+        }
+    }))
+}
+
+async fn render() -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics().render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::metrics::EXTRACTED_URLS;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_serves_metrics() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move { axum::serve(listener, router()).await });
+
+        metrics().observe(&EXTRACTED_URLS, &[], 3.0);
+
+        let response = reqwest::get(format!("http://{}/metrics", addr))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers()[CONTENT_TYPE.as_str()],
+            "text/plain; version=0.0.4"
+        );
+        assert!(
+            response
+                .text()
+                .await
+                .unwrap()
+                .contains("# TYPE aetherscope_extracted_urls histogram")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_fails_on_taken_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        assert!(start(listener.local_addr().unwrap()).await.is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+
+use crate::types::{error::AppError, structs::crawl_job::CrawlJob, traits::job_store::JobStore};
+
+#[derive(Default)]
+pub struct MemoryJobStore {
+    jobs: Mutex<HashMap<String, CrawlJob>>,
+}
+
+impl MemoryJobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl JobStore for MemoryJobStore {
+    async fn get(&self, task_id: &str) -> Result<Option<CrawlJob>, AppError> {
+        Ok(self.jobs.lock().unwrap().get(task_id).cloned())
+    }
+
+    async fn put(&self, job: &CrawlJob) -> Result<(), AppError> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .insert(job.task_id.clone(), job.clone());
+
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<CrawlJob>, AppError> {
+        let mut jobs: Vec<CrawlJob> = self.jobs.lock().unwrap().values().cloned().collect();
+
+        jobs.sort_by_key(|j| j.created_at);
+
+        Ok(jobs)
+    }
+
+    async fn delete(&self, task_id: &str) -> Result<(), AppError> {
+        self.jobs.lock().unwrap().remove(task_id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use sqlx::{Row, SqlitePool, query, sqlite::SqliteConnectOptions};
+
+use crate::types::{error::AppError, structs::crawl_job::CrawlJob, traits::job_store::JobStore};
+
+// Jobs as JSON documents keyed by task id, so they outlive the process that runs them
+pub struct SqliteJobStore {
+    db: SqlitePool,
+}
+
+impl SqliteJobStore {
+    pub async fn new(path: &str) -> Result<Self, AppError> {
+        let options = SqliteConnectOptions::from_str(path)?.create_if_missing(true);
+        let db = SqlitePool::connect_with(options).await?;
+
+        query(
+            r#"
+            CREATE TABLE IF NOT EXISTS crawl_job (
+                task_id    TEXT PRIMARY KEY,
+                job        TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&db)
+        .await?;
+
+        Ok(Self { db })
+    }
+}
+
+#[async_trait]
+impl JobStore for SqliteJobStore {
+    async fn get(&self, task_id: &str) -> Result<Option<CrawlJob>, AppError> {
+        let row = query("SELECT job FROM crawl_job WHERE task_id = ?")
+            .bind(task_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(serde_json::from_str(row.get("job"))?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, job: &CrawlJob) -> Result<(), AppError> {
+        query(
+            r#"
+            INSERT INTO crawl_job (task_id, job, created_at) VALUES (?, ?, ?)
+            ON CONFLICT (task_id) DO UPDATE SET job = excluded.job
+            "#,
+        )
+        .bind(&job.task_id)
+        .bind(serde_json::to_string(job)?)
+        .bind(job.created_at.timestamp_millis())
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<CrawlJob>, AppError> {
+        let rows = query("SELECT job FROM crawl_job ORDER BY created_at, task_id")
+            .fetch_all(&self.db)
+            .await?;
+
+        rows.iter()
+            .map(|row| Ok(serde_json::from_str(row.get("job"))?))
+            .collect()
+    }
+
+    async fn delete(&self, task_id: &str) -> Result<(), AppError> {
+        query("DELETE FROM crawl_job WHERE task_id = ?")
+            .bind(task_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use uuid::Uuid;
+
+    use crate::types::{
+        configs::job_config::JobConfig,
+        structs::crawl_job::{CrawlJob, JobState},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_get_list() {
+        let path = temp_dir()
+            .join(format!("{}.db", Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        let store = SqliteJobStore::new(&path).await.unwrap();
+        let mut job = CrawlJob::new("docs", JobConfig::default());
+
+        store.put(&job).await.unwrap();
+        job.transition(JobState::Running).unwrap();
+        store.put(&job).await.unwrap();
+        store
+            .put(&CrawlJob::new("blog", JobConfig::default()))
+            .await
+            .unwrap();
+
+        // Jobs survive reopening the database
+        let store = SqliteJobStore::new(&path).await.unwrap();
+        let stored = store.get("docs").await.unwrap().unwrap();
+
+        assert_eq!(stored.state, JobState::Running);
+        assert_eq!(stored.counters.runs, 1);
+        assert!(store.get("missing").await.unwrap().is_none());
+
+        let task_ids: Vec<String> = store
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|j| j.task_id)
+            .collect();
+
+        assert_eq!(task_ids, vec!["docs", "blog"]);
+
+        store.delete("docs").await.unwrap();
+
+        assert!(store.get("docs").await.unwrap().is_none());
+    }
+}
@@ -1 +1,6 @@
+pub mod dead_letter;
+pub mod http;
+pub mod job_store;
 pub mod object_store;
+pub mod politeness;
+pub mod queue;
@@ -8,7 +8,7 @@ use futures::StreamExt;
 use futures::stream::BoxStream;
 use minhash_rs::prelude::MinHash;
 use tokio::fs::File;
-use tokio::fs::{create_dir_all, read, remove_file, write};
+use tokio::fs::{create_dir_all, read, read_dir, remove_dir_all, remove_file, write};
 use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
 use xxhash_rust::xxh3::xxh3_64;
 
@@ -22,6 +22,17 @@ impl FileSystemObjectStore {
 
         Ok(Self { path })
     }
+
+    // Keys may contain `/`, which become directories under the root
+    async fn create_parent(&self, key: &str) -> Result<PathBuf, AppError> {
+        let path = self.path.join(key);
+
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        Ok(path)
+    }
 }
 
 #[async_trait]
@@ -30,9 +41,9 @@ impl ObjectStore for FileSystemObjectStore {
         Ok(read(self.path.join(key)).await?)
     }
     async fn put(&self, key: &str, data: &[u8]) -> Result<PutResponse, AppError> {
-        write(self.path.join(key), data).await?;
+        write(self.create_parent(key).await?, data).await?;
 
-        let mh: MinHash<u64, 128> = data.into_iter().collect();
+        let mh: MinHash<u64, 128> = data.iter().collect();
         let digest: Vec<u64> = mh.iter().copied().collect();
 
         Ok(PutResponse { minhash: digest })
@@ -46,12 +57,48 @@ impl ObjectStore for FileSystemObjectStore {
         }
     }
 
+    async fn delete_prefix(&self, prefix: &str) -> Result<(), AppError> {
+        // The part up to the last `/` names a directory, the rest is matched against the
+        // names in it
+        let (dir, name) = match prefix.rsplit_once('/') {
+            Some((dir, name)) => (self.path.join(dir), name),
+            None => (self.path.clone(), prefix),
+        };
+
+        if name.is_empty() {
+            return match remove_dir_all(&dir).await {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(e.into()),
+            };
+        }
+
+        let mut entries = match read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            if !entry.file_name().to_string_lossy().starts_with(name) {
+                continue;
+            }
+
+            match entry.file_type().await?.is_dir() {
+                true => remove_dir_all(entry.path()).await?,
+                false => remove_file(entry.path()).await?,
+            }
+        }
+
+        Ok(())
+    }
+
     async fn put_stream(
         &self,
         key: &str,
         mut stream: BoxStream<'_, Result<Bytes, AppError>>,
     ) -> Result<PutResponse, AppError> {
-        let file = File::create(self.path.join(key)).await?;
+        let file = File::create(self.create_parent(key).await?).await?;
         let mut writer = BufWriter::new(file);
         let mut mh = MinHash::<u64, 128>::new();
 
@@ -111,4 +158,23 @@ mod tests {
             "No such file or directory (os error 2)"
         );
     }
+
+    #[tokio::test]
+    async fn test_delete_prefix() {
+        let path = temp_dir().join(Uuid::new_v4().to_string());
+        let store = FileSystemObjectStore::new(path).await.unwrap();
+
+        store.put("docs/a", b"a").await.unwrap();
+        store.put("docs/b", b"b").await.unwrap();
+        store.put("blog/a", b"a").await.unwrap();
+
+        store.delete_prefix("docs/").await.unwrap();
+
+        assert!(store.get("docs/a").await.is_err());
+        assert!(store.get("docs/b").await.is_err());
+        assert_eq!(store.get("blog/a").await.unwrap(), b"a");
+
+        // Nothing stored under the prefix is fine
+        store.delete_prefix("missing/").await.unwrap();
+    }
 }
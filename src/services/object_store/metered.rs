@@ -0,0 +1,84 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{StreamExt, stream::BoxStream};
+
+use crate::{
+    types::{
+        error::AppError,
+        traits::object_store::{AsyncReadSeek, ObjectStore, PutResponse},
+    },
+    utils::metrics::{STORED_BYTES, metrics},
+};
+
+// Counts the bytes written through another store under the name it was registered as
+pub struct MeteredObjectStore {
+    name: String,
+    inner: Arc<dyn ObjectStore>,
+}
+
+impl MeteredObjectStore {
+    pub fn new(name: &str, inner: Arc<dyn ObjectStore>) -> Self {
+        Self {
+            name: name.to_string(),
+            inner,
+        }
+    }
+
+    fn record(&self, bytes: u64) {
+        metrics().increment(&STORED_BYTES, &[("store", &self.name)], bytes as f64);
+    }
+}
+
+#[async_trait]
+impl ObjectStore for MeteredObjectStore {
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AppError> {
+        self.inner.get(key).await
+    }
+
+    async fn put(&self, key: &str, data: &[u8]) -> Result<PutResponse, AppError> {
+        let response = self.inner.put(key, data).await?;
+        self.record(data.len() as u64);
+
+        Ok(response)
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: BoxStream<'_, Result<Bytes, AppError>>,
+    ) -> Result<PutResponse, AppError> {
+        let written = AtomicU64::new(0);
+        let stream = stream
+            .inspect(|chunk| {
+                if let Ok(chunk) = chunk {
+                    written.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                }
+            })
+            .boxed();
+
+        let response = self.inner.put_stream(key, stream).await;
+        self.record(written.load(Ordering::Relaxed));
+
+        response
+    }
+
+    async fn get_stream(
+        &self,
+        key: &str,
+    ) -> Result<Box<dyn AsyncReadSeek + Send + Unpin>, AppError> {
+        self.inner.get_stream(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.inner.delete(key).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<(), AppError> {
+        self.inner.delete_prefix(prefix).await
+    }
+}
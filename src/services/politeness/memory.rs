@@ -0,0 +1,137 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use xxhrs::XXH3_128;
+
+use crate::types::{
+    error::AppError,
+    signals::host_gate::HostGate,
+    traits::politeness_gate::{Lease, PolitenessGate},
+};
+
+// Leases are only seen by workers of this process
+#[derive(Default)]
+pub struct MemoryPolitenessGate {
+    gates: Mutex<HashMap<(String, Vec<u8>), HostGate>>,
+}
+
+impl MemoryPolitenessGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn host_key(host: &str) -> Vec<u8> {
+    XXH3_128::hash(host.as_bytes()).to_be_bytes().to_vec()
+}
+
+fn after(now: DateTime<Utc>, delay: Duration) -> DateTime<Utc> {
+    now + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX)
+}
+
+#[async_trait]
+impl PolitenessGate for MemoryPolitenessGate {
+    async fn try_acquire(
+        &self,
+        namespace: &str,
+        host: &str,
+        owner: &str,
+        lease: Duration,
+    ) -> Result<Lease, AppError> {
+        let now = Utc::now();
+        let mut gates = self.gates.lock().unwrap();
+        let gate = gates
+            .entry((namespace.to_string(), host_key(host)))
+            .or_insert_with(|| HostGate {
+                task_id: namespace.to_string(),
+                host_key: host_key(host),
+                next_allowed_ts: DateTime::UNIX_EPOCH,
+                lease_until_ts: DateTime::UNIX_EPOCH,
+                lease_owner: String::new(),
+            });
+
+        if gate.lease_until_ts > now && gate.lease_owner != owner {
+            return Ok(Lease::Wait(
+                (gate.lease_until_ts - now).to_std().unwrap_or_default(),
+            ));
+        }
+
+        if gate.next_allowed_ts > now {
+            return Ok(Lease::Wait(
+                (gate.next_allowed_ts - now).to_std().unwrap_or_default(),
+            ));
+        }
+
+        gate.lease_until_ts = after(now, lease);
+        gate.lease_owner = owner.to_string();
+
+        Ok(Lease::Granted)
+    }
+
+    async fn release(
+        &self,
+        namespace: &str,
+        host: &str,
+        owner: &str,
+        delay: Duration,
+    ) -> Result<(), AppError> {
+        let now = Utc::now();
+        let mut gates = self.gates.lock().unwrap();
+        let Some(gate) = gates.get_mut(&(namespace.to_string(), host_key(host))) else {
+            return Ok(());
+        };
+
+        gate.next_allowed_ts = gate.next_allowed_ts.max(after(now, delay));
+
+        if gate.lease_owner == owner {
+            gate.lease_until_ts = now;
+            gate.lease_owner.clear();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lease_and_delay() {
+        let gate = MemoryPolitenessGate::new();
+        let lease = Duration::from_secs(60);
+        let host = "https://example.com:443";
+
+        assert_eq!(
+            gate.try_acquire("a", host, "w1", lease).await.unwrap(),
+            Lease::Granted
+        );
+        assert!(matches!(
+            gate.try_acquire("a", host, "w2", lease).await.unwrap(),
+            Lease::Wait(d) if d > Duration::from_secs(59)
+        ));
+
+        // Other crawls don't share the host's lease
+        assert_eq!(
+            gate.try_acquire("b", host, "w2", lease).await.unwrap(),
+            Lease::Granted
+        );
+
+        gate.release("a", host, "w1", Duration::from_millis(50))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            gate.try_acquire("a", host, "w2", lease).await.unwrap(),
+            Lease::Wait(d) if d <= Duration::from_millis(50)
+        ));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        assert_eq!(
+            gate.try_acquire("a", host, "w2", lease).await.unwrap(),
+            Lease::Granted
+        );
+    }
+}
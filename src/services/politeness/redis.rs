@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::{Client, Script, aio::ConnectionManager};
+
+use crate::types::{
+    configs::tasks::politeness_config::RedisHostGateConfig,
+    error::AppError,
+    traits::politeness_gate::{Lease, PolitenessGate},
+};
+
+// Both scripts read the clock of the redis server, so workers with skewed clocks still
+// agree on when a host is free. Times are unix milliseconds, in a hash with the fields of
+// `HostGate`. Keys expire once nothing about the host is pending any more.
+const ACQUIRE: &str = r#"
+local t = redis.call('TIME')
+local now = t[1] * 1000 + math.floor(t[2] / 1000)
+local gate = redis.call('HMGET', KEYS[1], 'next_allowed_ts', 'lease_until_ts', 'lease_owner')
+local next_allowed = tonumber(gate[1]) or 0
+local lease_until = tonumber(gate[2]) or 0
+
+if lease_until > now and gate[3] ~= ARGV[1] then
+    return lease_until - now
+end
+
+if next_allowed > now then
+    return next_allowed - now
+end
+
+redis.call('HSET', KEYS[1], 'lease_until_ts', now + ARGV[2], 'lease_owner', ARGV[1])
+redis.call('PEXPIRE', KEYS[1], ARGV[2])
+
+return 0
+"#;
+
+const RELEASE: &str = r#"
+local t = redis.call('TIME')
+local now = t[1] * 1000 + math.floor(t[2] / 1000)
+local next_allowed = now + ARGV[2]
+local gate = redis.call('HMGET', KEYS[1], 'next_allowed_ts', 'lease_until_ts', 'lease_owner')
+local lease_until = tonumber(gate[2]) or 0
+
+next_allowed = math.max(next_allowed, tonumber(gate[1]) or 0)
+redis.call('HSET', KEYS[1], 'next_allowed_ts', next_allowed)
+
+if gate[3] == ARGV[1] then
+    lease_until = now
+    redis.call('HSET', KEYS[1], 'lease_until_ts', now, 'lease_owner', '')
+end
+
+redis.call('PEXPIRE', KEYS[1], math.max(next_allowed, lease_until) - now + 1)
+
+return 0
+"#;
+
+pub struct RedisPolitenessGate {
+    conn: ConnectionManager,
+    acquire: Script,
+    release: Script,
+}
+
+impl RedisPolitenessGate {
+    pub async fn new(config: RedisHostGateConfig) -> Result<Self, AppError> {
+        let client = Client::open(config.uri)?;
+        let conn = ConnectionManager::new(client).await?;
+
+        Ok(Self {
+            conn,
+            acquire: Script::new(ACQUIRE),
+            release: Script::new(RELEASE),
+        })
+    }
+}
+
+fn gate_key(namespace: &str, host: &str) -> String {
+    format!("aetherscope:host_gate:{}:{}", namespace, host)
+}
+
+#[async_trait]
+impl PolitenessGate for RedisPolitenessGate {
+    async fn try_acquire(
+        &self,
+        namespace: &str,
+        host: &str,
+        owner: &str,
+        lease: Duration,
+    ) -> Result<Lease, AppError> {
+        let mut conn = self.conn.clone();
+        let wait_ms: u64 = self
+            .acquire
+            .key(gate_key(namespace, host))
+            .arg(owner)
+            .arg(lease.as_millis().max(1) as u64)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(AppError::from)?;
+
+        Ok(match wait_ms {
+            0 => Lease::Granted,
+            ms => Lease::Wait(Duration::from_millis(ms)),
+        })
+    }
+
+    async fn release(
+        &self,
+        namespace: &str,
+        host: &str,
+        owner: &str,
+        delay: Duration,
+    ) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let _: i64 = self
+            .release
+            .key(gate_key(namespace, host))
+            .arg(owner)
+            .arg(delay.as_millis() as u64)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(AppError::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lease_and_delay() {
+        let gate = RedisPolitenessGate::new(RedisHostGateConfig {
+            uri: "redis://localhost:6379".to_string(),
+        })
+        .await
+        .unwrap();
+        let namespace = Uuid::new_v4().to_string();
+        let lease = Duration::from_secs(60);
+        let host = "https://example.com:443";
+
+        assert_eq!(
+            gate.try_acquire(&namespace, host, "w1", lease)
+                .await
+                .unwrap(),
+            Lease::Granted
+        );
+        assert!(matches!(
+            gate.try_acquire(&namespace, host, "w2", lease)
+                .await
+                .unwrap(),
+            Lease::Wait(_)
+        ));
+
+        gate.release(&namespace, host, "w1", Duration::from_millis(50))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        assert_eq!(
+            gate.try_acquire(&namespace, host, "w2", lease)
+                .await
+                .unwrap(),
+            Lease::Granted
+        );
+    }
+}
@@ -0,0 +1,557 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tokio::{
+    sync::{Notify, Semaphore},
+    time::{Instant, sleep_until},
+};
+use uuid::Uuid;
+
+use crate::types::{
+    configs::queues::memory_queue_config::MemoryQueueConfig,
+    error::AppError,
+    structs::{
+        queue_message::QueueMessage,
+        queue_status::{QueueStatus, RATE_WINDOW},
+        record::Record,
+    },
+    traits::queue::Queue,
+};
+
+struct Pending {
+    record: Record,
+    priority: i64,
+    attempts: u32,
+    enqueued_at: Instant,
+    visible_at: Instant,
+}
+
+struct Leased {
+    record: Record,
+    priority: i64,
+    attempts: u32,
+    enqueued_at: Instant,
+    lease_until: Instant,
+}
+
+enum Lease {
+    Leased(QueueMessage),
+    // Nothing is visible yet; holds the next time that may change
+    Wait(Option<Instant>),
+}
+
+// Event counts bucketed by second since `origin`
+struct RateWindow {
+    origin: Instant,
+    buckets: VecDeque<(u64, u64)>,
+}
+
+impl RateWindow {
+    fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            buckets: VecDeque::new(),
+        }
+    }
+
+    fn record(&mut self, now: Instant) {
+        let second = now.duration_since(self.origin).as_secs();
+
+        match self.buckets.back_mut() {
+            Some((s, count)) if *s == second => *count += 1,
+            _ => self.buckets.push_back((second, 1)),
+        }
+
+        self.prune(second);
+    }
+
+    fn rate(&mut self, now: Instant) -> f64 {
+        self.prune(now.duration_since(self.origin).as_secs());
+
+        let total: u64 = self.buckets.iter().map(|(_, count)| count).sum();
+
+        total as f64 / RATE_WINDOW.as_secs_f64()
+    }
+
+    fn prune(&mut self, second: u64) {
+        while self
+            .buckets
+            .front()
+            .is_some_and(|(s, _)| s + RATE_WINDOW.as_secs() <= second)
+        {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+struct MemoryQueueState {
+    pending: VecDeque<Pending>,
+    leased: HashMap<String, Leased>,
+    enqueued: RateWindow,
+    dequeued: RateWindow,
+}
+
+impl MemoryQueueState {
+    fn reclaim_expired(&mut self, now: Instant) {
+        let expired: Vec<String> = self
+            .leased
+            .iter()
+            .filter(|(_, l)| l.lease_until <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in expired {
+            if let Some(l) = self.leased.remove(&id) {
+                self.pending.push_front(Pending {
+                    record: l.record,
+                    priority: l.priority,
+                    attempts: l.attempts,
+                    enqueued_at: l.enqueued_at,
+                    visible_at: now,
+                });
+            }
+        }
+    }
+
+    fn next_change(&self) -> Option<Instant> {
+        let pending = self.pending.iter().map(|p| p.visible_at);
+        let leased = self.leased.values().map(|l| l.lease_until);
+
+        pending.chain(leased).min()
+    }
+}
+
+// In-process queue delivering the highest priority visible message first, and messages
+// of equal priority in the order they were queued. When bounded, `put` waits while the
+// queue holds `capacity` messages, counting both pending and leased ones.
+pub struct MemoryQueue {
+    state: Mutex<MemoryQueueState>,
+    notify: Notify,
+    capacity: Option<Semaphore>,
+    visibility_timeout: Duration,
+}
+
+impl MemoryQueue {
+    pub fn new(config: MemoryQueueConfig) -> Self {
+        Self {
+            state: Mutex::new(MemoryQueueState {
+                pending: VecDeque::new(),
+                leased: HashMap::new(),
+                enqueued: RateWindow::new(),
+                dequeued: RateWindow::new(),
+            }),
+            notify: Notify::new(),
+            capacity: config.capacity.map(Semaphore::new),
+            visibility_timeout: Duration::from_secs(config.visibility_timeout),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.capacity
+            .as_ref()
+            .is_some_and(|c| c.available_permits() == 0)
+    }
+
+    // Empties the queue, returning the leased messages' records followed by the pending
+    // ones in delivery order, each with its priority
+    pub fn drain(&self) -> Vec<(Record, i64)> {
+        let mut state = self.state.lock().unwrap();
+        let leased: Vec<Leased> = state.leased.drain().map(|(_, l)| l).collect();
+        let mut pending: Vec<Pending> = state.pending.drain(..).collect();
+
+        pending.sort_by_key(|p| Reverse(p.priority));
+
+        self.release_capacity(leased.len() + pending.len());
+
+        leased
+            .into_iter()
+            .map(|l| (l.record, l.priority))
+            .chain(pending.into_iter().map(|p| (p.record, p.priority)))
+            .collect()
+    }
+
+    fn release_capacity(&self, messages: usize) {
+        if let Some(capacity) = &self.capacity {
+            capacity.add_permits(messages);
+        }
+    }
+
+    async fn enqueue(
+        &self,
+        record: Record,
+        priority: i64,
+        delay: Duration,
+    ) -> Result<(), AppError> {
+        if let Some(capacity) = &self.capacity {
+            capacity
+                .acquire()
+                .await
+                .map_err(|e| AppError::Generic(e.to_string()))?
+                .forget();
+        }
+
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        state.pending.push_back(Pending {
+            record,
+            priority,
+            attempts: 0,
+            enqueued_at: now,
+            visible_at: now + delay,
+        });
+        state.enqueued.record(now);
+        drop(state);
+        self.notify.notify_waiters();
+
+        Ok(())
+    }
+
+    fn try_lease(&self) -> Lease {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        state.reclaim_expired(now);
+
+        let Some(pending) = state
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.visible_at <= now)
+            .max_by_key(|(pos, p)| (p.priority, Reverse(*pos)))
+            .map(|(pos, _)| pos)
+            .and_then(|pos| state.pending.remove(pos))
+        else {
+            return Lease::Wait(state.next_change());
+        };
+
+        let id = Uuid::new_v4().to_string();
+        let attempts = pending.attempts + 1;
+
+        state.leased.insert(
+            id.clone(),
+            Leased {
+                record: pending.record.clone(),
+                priority: pending.priority,
+                attempts,
+                enqueued_at: pending.enqueued_at,
+                lease_until: now + self.visibility_timeout,
+            },
+        );
+        state.dequeued.record(now);
+
+        Lease::Leased(QueueMessage {
+            id,
+            record: pending.record,
+            attempts,
+            priority: pending.priority,
+        })
+    }
+}
+
+#[async_trait]
+impl Queue for MemoryQueue {
+    async fn put(&self, record: Record) -> Result<(), AppError> {
+        self.put_with_priority(record, 0).await
+    }
+
+    async fn put_with_priority(&self, record: Record, priority: i64) -> Result<(), AppError> {
+        self.enqueue(record, priority, Duration::ZERO).await
+    }
+
+    async fn put_delayed(
+        &self,
+        record: Record,
+        priority: i64,
+        delay: Duration,
+    ) -> Result<(), AppError> {
+        self.enqueue(record, priority, delay).await
+    }
+
+    async fn get(&self, wait: Duration) -> Result<Option<QueueMessage>, AppError> {
+        let deadline = Instant::now() + wait;
+
+        loop {
+            let notified = self.notify.notified();
+
+            let wake = match self.try_lease() {
+                Lease::Leased(message) => return Ok(Some(message)),
+                Lease::Wait(next) => next.map_or(deadline, |n| n.min(deadline)),
+            };
+
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+
+            tokio::select! {
+                _ = notified => {}
+                _ = sleep_until(wake) => {}
+            }
+        }
+    }
+
+    async fn ack(&self, id: &str) -> Result<(), AppError> {
+        self.state
+            .lock()
+            .unwrap()
+            .leased
+            .remove(id)
+            .ok_or_else(|| AppError::QueueLeaseNotFound(id.to_string()))?;
+        self.release_capacity(1);
+
+        Ok(())
+    }
+
+    async fn nack(&self, id: &str, delay: Duration) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        let leased = state
+            .leased
+            .remove(id)
+            .ok_or_else(|| AppError::QueueLeaseNotFound(id.to_string()))?;
+
+        state.pending.push_back(Pending {
+            record: leased.record,
+            priority: leased.priority,
+            attempts: leased.attempts,
+            enqueued_at: leased.enqueued_at,
+            visible_at: Instant::now() + delay,
+        });
+        drop(state);
+        self.notify.notify_waiters();
+
+        Ok(())
+    }
+
+    async fn status(&self) -> Result<QueueStatus, AppError> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        state.reclaim_expired(now);
+
+        // Nothing is ever dead lettered in memory; messages are redelivered until acked
+        let mut status = QueueStatus {
+            depth: state.pending.len() as u64,
+            in_flight: state.leased.len() as u64,
+            enqueue_rate: state.enqueued.rate(now),
+            dequeue_rate: state.dequeued.rate(now),
+            ..Default::default()
+        };
+
+        for p in &state.pending {
+            status.task_mut(&p.record.task_id).depth += 1;
+            status.observe_age(now.duration_since(p.enqueued_at));
+        }
+
+        for l in state.leased.values() {
+            status.task_mut(&l.record.task_id).in_flight += 1;
+            status.observe_age(now.duration_since(l.enqueued_at));
+        }
+
+        Ok(status)
+    }
+
+    async fn purge(&self, task_id: &str) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.pending.len() + state.leased.len();
+
+        state.pending.retain(|p| p.record.task_id != task_id);
+        state.leased.retain(|_, l| l.record.task_id != task_id);
+
+        let purged = before - state.pending.len() - state.leased.len();
+        drop(state);
+        self.release_capacity(purged);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::timeout;
+
+    use crate::types::structs::{queue_status::TaskQueueStatus, record::HopType};
+
+    use super::*;
+
+    fn record(uri: &str) -> Record {
+        Record {
+            uri: uri.to_string(),
+            task_id: "test".to_string(),
+            metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        }
+    }
+
+    fn queue(capacity: Option<usize>, visibility_timeout: u64) -> MemoryQueue {
+        MemoryQueue::new(MemoryQueueConfig {
+            capacity,
+            visibility_timeout,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_put_get_ack() {
+        let queue = queue(None, 30);
+
+        queue.put(record("http://example.com/a")).await.unwrap();
+        queue.put(record("http://example.com/b")).await.unwrap();
+
+        let message = queue.get(Duration::ZERO).await.unwrap().unwrap();
+
+        assert_eq!(message.record.uri, "http://example.com/a");
+        assert_eq!(message.attempts, 1);
+
+        let status = queue.status().await.unwrap();
+
+        assert_eq!((status.depth, status.in_flight), (1, 1));
+        assert_eq!(status.enqueue_rate, 2.0 / 60.0);
+        assert_eq!(status.dequeue_rate, 1.0 / 60.0);
+        assert!(status.oldest_age.is_some());
+        assert_eq!(
+            status.tasks["test"],
+            TaskQueueStatus {
+                depth: 1,
+                in_flight: 1,
+                dead_lettered: 0
+            }
+        );
+
+        queue.ack(&message.id).await.unwrap();
+
+        assert!(matches!(
+            queue.ack(&message.id).await.unwrap_err(),
+            AppError::QueueLeaseNotFound(_)
+        ));
+        assert_eq!(queue.status().await.unwrap().in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn test_priority_order() {
+        let queue = queue(None, 30);
+
+        queue.put(record("http://example.com/low")).await.unwrap();
+        queue
+            .put_with_priority(record("http://example.com/high"), 10)
+            .await
+            .unwrap();
+        queue.put(record("http://example.com/next")).await.unwrap();
+        queue
+            .put_with_priority(record("http://example.com/min"), i64::MIN)
+            .await
+            .unwrap();
+
+        let mut uris = vec![];
+
+        while let Some(message) = queue.get(Duration::ZERO).await.unwrap() {
+            queue.ack(&message.id).await.unwrap();
+            uris.push(message.record.uri);
+        }
+
+        assert_eq!(
+            uris,
+            vec![
+                "http://example.com/high",
+                "http://example.com/low",
+                "http://example.com/next",
+                "http://example.com/min"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_empty_waits() {
+        let queue = queue(None, 30);
+        let started = Instant::now();
+
+        assert!(
+            queue
+                .get(Duration::from_millis(50))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_nack_redelivers_after_delay() {
+        let queue = queue(None, 30);
+
+        queue.put(record("http://example.com/a")).await.unwrap();
+
+        let message = queue.get(Duration::ZERO).await.unwrap().unwrap();
+        queue
+            .nack(&message.id, Duration::from_millis(50))
+            .await
+            .unwrap();
+
+        assert!(queue.get(Duration::ZERO).await.unwrap().is_none());
+
+        let message = queue.get(Duration::from_secs(1)).await.unwrap().unwrap();
+
+        assert_eq!(message.attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_put_delayed() {
+        let queue = queue(None, 30);
+
+        queue
+            .put_delayed(record("http://example.com/a"), 0, Duration::from_millis(50))
+            .await
+            .unwrap();
+
+        assert!(queue.get(Duration::ZERO).await.unwrap().is_none());
+        assert_eq!(queue.status().await.unwrap().depth, 1);
+
+        let message = queue.get(Duration::from_secs(1)).await.unwrap().unwrap();
+
+        assert_eq!(message.record.uri, "http://example.com/a");
+        assert_eq!(message.attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_visibility_timeout_redelivers() {
+        let queue = queue(None, 1);
+
+        queue.put(record("http://example.com/a")).await.unwrap();
+
+        let first = queue.get(Duration::ZERO).await.unwrap().unwrap();
+        let second = queue.get(Duration::from_secs(2)).await.unwrap().unwrap();
+
+        assert_eq!(second.record.uri, first.record.uri);
+        assert_eq!(second.attempts, 2);
+        assert!(queue.ack(&first.id).await.is_err());
+        queue.ack(&second.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_blocks_when_full() {
+        let queue = queue(Some(1), 30);
+
+        queue.put(record("http://example.com/a")).await.unwrap();
+
+        let blocked = timeout(
+            Duration::from_millis(50),
+            queue.put(record("http://example.com/b")),
+        )
+        .await;
+
+        assert!(blocked.is_err());
+
+        let message = queue.get(Duration::ZERO).await.unwrap().unwrap();
+        queue.ack(&message.id).await.unwrap();
+        queue.put(record("http://example.com/b")).await.unwrap();
+    }
+}
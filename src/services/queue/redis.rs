@@ -0,0 +1,837 @@
+use std::{collections::HashSet, sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use redis::{
+    AsyncCommands, Client, Script,
+    aio::ConnectionManager,
+    cmd, pipe,
+    streams::{
+        StreamAutoClaimOptions, StreamAutoClaimReply, StreamId, StreamPendingCountReply,
+        StreamPendingReply, StreamRangeReply, StreamReadOptions, StreamReadReply,
+    },
+};
+use tokio::time::{Instant, sleep};
+use uuid::Uuid;
+
+use crate::types::{
+    configs::queues::redis_queue_config::RedisQueueConfig,
+    error::AppError,
+    structs::{
+        dead_letter::DeadLetter,
+        queue_message::QueueMessage,
+        queue_status::{QueueStatus, RATE_WINDOW},
+        record::{Record, RecordEncoding},
+    },
+    traits::queue::Queue,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const PROMOTE_BATCH: usize = 100;
+const SCAN_BATCH: usize = 1000;
+
+// Moves delayed messages that became visible back onto their stream. Each one is a hash
+// of stream fields, indexed by a sorted set scored by when it becomes visible; running
+// as a script, nothing can be lost or delivered twice between the two.
+const PROMOTE: &str = r#"
+local due = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', ARGV[1], 'LIMIT', 0, ARGV[2])
+
+for _, id in ipairs(due) do
+    local key = KEYS[1] .. ':' .. id
+    local fields = redis.call('HGETALL', key)
+
+    redis.call('ZREM', KEYS[1], id)
+    redis.call('DEL', key)
+
+    if #fields > 0 then
+        redis.call('XADD', KEYS[2], '*', unpack(fields))
+    end
+end
+
+return #due
+"#;
+
+// Fields stored on a stream entry. `attempts` counts deliveries used up by earlier
+// entries for the same message; Redis counts the deliveries of the current one.
+struct Entry {
+    payload: Vec<u8>,
+    task_id: String,
+    attempts: u32,
+    enqueued_at: i64,
+}
+
+impl Entry {
+    fn from_stream(entry: &StreamId) -> Self {
+        Self {
+            payload: entry.get("payload").unwrap_or_default(),
+            task_id: entry.get("task_id").unwrap_or_default(),
+            attempts: entry.get("attempts").unwrap_or_default(),
+            enqueued_at: entry.get("enqueued_at").unwrap_or_default(),
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, Vec<u8>)> {
+        vec![
+            ("payload", self.payload.clone()),
+            ("task_id", self.task_id.clone().into_bytes()),
+            ("attempts", self.attempts.to_string().into_bytes()),
+            ("enqueued_at", self.enqueued_at.to_string().into_bytes()),
+        ]
+    }
+}
+
+// Queue shared by every process in a consumer group. Each task has a stream of its own,
+// so the workers of one crawl share its messages without seeing other crawls'. Entries
+// pending longer than the visibility timeout are claimed by whichever consumer asks next.
+//
+// A queue opened for a task only leases and reports on that task's messages. Lease ids
+// name the task as well as the entry, so one opened for every task can ack them too.
+// Messages are delivered in the order they arrived; priorities are ignored.
+pub struct RedisQueue {
+    conn: ConnectionManager,
+    prefix: String,
+    task_id: Option<String>,
+    group: String,
+    consumer: String,
+    visibility_timeout: Duration,
+    max_attempts: u32,
+    encoding: RecordEncoding,
+    promote: Script,
+    // Tasks this process already created the stream and group of
+    registered: Mutex<HashSet<String>>,
+}
+
+impl RedisQueue {
+    pub async fn new(config: RedisQueueConfig, task_id: Option<&str>) -> Result<Self, AppError> {
+        let client = Client::open(config.uri)?;
+        let conn = ConnectionManager::new(client).await?;
+        let queue = Self {
+            conn,
+            prefix: config.stream,
+            task_id: task_id.map(str::to_string),
+            group: config.group,
+            consumer: config
+                .consumer
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            visibility_timeout: Duration::from_secs(config.visibility_timeout),
+            max_attempts: config.max_attempts,
+            encoding: config.encoding,
+            promote: Script::new(PROMOTE),
+            registered: Mutex::new(HashSet::new()),
+        };
+
+        if let Some(task_id) = task_id {
+            queue.register(task_id).await?;
+        }
+
+        Ok(queue)
+    }
+
+    fn stream(&self, task_id: &str) -> String {
+        format!("{}:task:{}", self.prefix, task_id)
+    }
+
+    fn delayed(&self, task_id: &str) -> String {
+        format!("{}:task:{}:delayed", self.prefix, task_id)
+    }
+
+    fn dead(&self, task_id: &str) -> String {
+        format!("{}:task:{}:dead", self.prefix, task_id)
+    }
+
+    fn tasks_key(&self) -> String {
+        format!("{}:tasks", self.prefix)
+    }
+
+    fn rate_key(&self, task_id: &str, kind: &str, second: i64) -> String {
+        format!("{}:task:{}:rate:{}:{}", self.prefix, task_id, kind, second)
+    }
+
+    // Creates a task's stream and consumer group and lists it among the queue's tasks
+    async fn register(&self, task_id: &str) -> Result<(), AppError> {
+        if self.registered.lock().unwrap().contains(task_id) {
+            return Ok(());
+        }
+
+        let mut conn = self.conn.clone();
+        let created: Result<(), _> = conn
+            .xgroup_create_mkstream(self.stream(task_id), &self.group, "0")
+            .await;
+
+        if let Err(e) = created
+            && e.code() != Some("BUSYGROUP")
+        {
+            return Err(e.into());
+        }
+
+        let _: usize = conn.sadd(self.tasks_key(), task_id).await?;
+        self.registered.lock().unwrap().insert(task_id.to_string());
+
+        Ok(())
+    }
+
+    // The task of a scoped queue, or every task that ever had a message
+    async fn tasks(&self) -> Result<Vec<String>, AppError> {
+        if let Some(task_id) = &self.task_id {
+            return Ok(vec![task_id.clone()]);
+        }
+
+        let mut conn = self.conn.clone();
+        let mut tasks: Vec<String> = conn
+            .smembers::<_, HashSet<String>>(self.tasks_key())
+            .await?
+            .into_iter()
+            .collect();
+
+        tasks.sort();
+
+        Ok(tasks)
+    }
+
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>, AppError> {
+        let mut dead_letters = vec![];
+
+        for task_id in self.tasks().await? {
+            self.scan(&self.dead(&task_id), |entry| {
+                let timestamp = entry.get::<i64>("timestamp").unwrap_or_default();
+
+                dead_letters.push(DeadLetter {
+                    record: decode_record(entry)?,
+                    attempts: entry.get("attempts").unwrap_or_default(),
+                    reason: entry.get("reason").unwrap_or_default(),
+                    errors: vec![],
+                    timestamp: DateTime::from_timestamp_millis(timestamp).unwrap_or_default(),
+                });
+
+                Ok(())
+            })
+            .await?;
+        }
+
+        Ok(dead_letters)
+    }
+
+    // Counts an enqueue or dequeue of a task's message in a per-second counter that
+    // expires once it can no longer fall inside the rate window.
+    async fn record_rate(&self, task_id: &str, kind: &str) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let key = self.rate_key(task_id, kind, Utc::now().timestamp());
+
+        let _: () = pipe()
+            .incr(&key, 1)
+            .ignore()
+            .expire(&key, 2 * RATE_WINDOW.as_secs() as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn rate(&self, tasks: &[String], kind: &str) -> Result<f64, AppError> {
+        if tasks.is_empty() {
+            return Ok(0.0);
+        }
+
+        let mut conn = self.conn.clone();
+        let now = Utc::now().timestamp();
+        let keys: Vec<String> = tasks
+            .iter()
+            .flat_map(|t| (0..RATE_WINDOW.as_secs() as i64).map(move |s| (t, now - s)))
+            .map(|(task_id, second)| self.rate_key(task_id, kind, second))
+            .collect();
+        let counts: Vec<Option<u64>> = conn.mget(&keys).await?;
+        let total: u64 = counts.into_iter().flatten().sum();
+
+        Ok(total as f64 / RATE_WINDOW.as_secs_f64())
+    }
+
+    // Walks every entry of a stream in batches
+    async fn scan<F>(&self, key: &str, mut visit: F) -> Result<(), AppError>
+    where
+        F: FnMut(&StreamId) -> Result<(), AppError>,
+    {
+        let mut conn = self.conn.clone();
+        let mut start = "-".to_string();
+
+        loop {
+            let reply: StreamRangeReply = conn.xrange_count(key, &start, "+", SCAN_BATCH).await?;
+
+            for entry in &reply.ids {
+                visit(entry)?;
+            }
+
+            match reply.ids.last() {
+                Some(last) if reply.ids.len() == SCAN_BATCH => start = format!("({}", last.id),
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    async fn promote_delayed(&self, task_id: &str) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let _: usize = self
+            .promote
+            .key(self.delayed(task_id))
+            .key(self.stream(task_id))
+            .arg(Utc::now().timestamp_millis())
+            .arg(PROMOTE_BATCH)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(AppError::from)?;
+
+        Ok(())
+    }
+
+    async fn try_lease(&self, task_id: &str) -> Result<Option<QueueMessage>, AppError> {
+        let mut conn = self.conn.clone();
+        let stream = self.stream(task_id);
+
+        loop {
+            let claimed: StreamAutoClaimReply = conn
+                .xautoclaim_options(
+                    &stream,
+                    &self.group,
+                    &self.consumer,
+                    self.visibility_timeout.as_millis() as u64,
+                    "0-0",
+                    StreamAutoClaimOptions::default().count(1),
+                )
+                .await?;
+
+            let Some(entry) = claimed.claimed.into_iter().next() else {
+                break;
+            };
+
+            // The lease ran out on its final attempt, so nobody gets to try again
+            if self.attempts(task_id, &entry).await? > self.max_attempts {
+                self.dead_letter(task_id, &entry, "lease expired on final attempt")
+                    .await?;
+                continue;
+            }
+
+            return self.lease(task_id, entry).await.map(Some);
+        }
+
+        let options = StreamReadOptions::default()
+            .group(&self.group, &self.consumer)
+            .count(1);
+        let reply: StreamReadReply = conn.xread_options(&[&stream], &[">"], &options).await?;
+
+        match reply.keys.into_iter().flat_map(|k| k.ids).next() {
+            Some(entry) => self.lease(task_id, entry).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn lease(&self, task_id: &str, entry: StreamId) -> Result<QueueMessage, AppError> {
+        self.record_rate(task_id, "dequeue").await?;
+
+        Ok(QueueMessage {
+            record: decode_record(&entry)?,
+            attempts: self.attempts(task_id, &entry).await?,
+            id: lease_id(task_id, &entry.id),
+            priority: 0,
+        })
+    }
+
+    // Attempts including the current delivery of a pending entry
+    async fn attempts(&self, task_id: &str, entry: &StreamId) -> Result<u32, AppError> {
+        let delivered = self.times_delivered(task_id, &entry.id).await?.unwrap_or(1);
+
+        Ok(Entry::from_stream(entry).attempts + delivered)
+    }
+
+    async fn times_delivered(&self, task_id: &str, id: &str) -> Result<Option<u32>, AppError> {
+        let mut conn = self.conn.clone();
+        let reply: StreamPendingCountReply = conn
+            .xpending_count(self.stream(task_id), &self.group, id, id, 1)
+            .await?;
+
+        Ok(reply.ids.first().map(|p| p.times_delivered as u32))
+    }
+
+    async fn pending_entry(&self, task_id: &str, id: &str) -> Result<StreamId, AppError> {
+        let mut conn = self.conn.clone();
+
+        if self.times_delivered(task_id, id).await?.is_none() {
+            return Err(AppError::QueueLeaseNotFound(lease_id(task_id, id)));
+        }
+
+        let reply: StreamRangeReply = conn.xrange(self.stream(task_id), id, id).await?;
+
+        reply
+            .ids
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::QueueLeaseNotFound(lease_id(task_id, id)))
+    }
+
+    async fn dead_letter(
+        &self,
+        task_id: &str,
+        entry: &StreamId,
+        reason: &str,
+    ) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let stream = self.stream(task_id);
+        let mut fields = Entry {
+            attempts: self.attempts(task_id, entry).await?,
+            ..Entry::from_stream(entry)
+        }
+        .fields();
+
+        fields.push(("reason", reason.as_bytes().to_vec()));
+        fields.push((
+            "timestamp",
+            Utc::now().timestamp_millis().to_string().into_bytes(),
+        ));
+
+        let _: () = pipe()
+            .atomic()
+            .xadd(self.dead(task_id), "*", &fields)
+            .ignore()
+            .xack(&stream, &self.group, &[&entry.id])
+            .ignore()
+            .xdel(&stream, &[&entry.id])
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    // Counts a task's messages without walking them: the stream's length less its pending
+    // entries is what waits to be delivered, and only leases that ran out are listed
+    async fn observe_task(&self, task_id: &str, status: &mut QueueStatus) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let stream = self.stream(task_id);
+        let delayed = self.delayed(task_id);
+        let now = Utc::now().timestamp_millis();
+
+        let length: usize = conn.xlen(&stream).await?;
+        let pending: StreamPendingReply = conn.xpending(&stream, &self.group).await?;
+        let expired: StreamPendingCountReply = match pending.count() {
+            0 => StreamPendingCountReply { ids: vec![] },
+            count => {
+                cmd("XPENDING")
+                    .arg(&stream)
+                    .arg(&self.group)
+                    .arg("IDLE")
+                    .arg(self.visibility_timeout.as_millis() as u64)
+                    .arg("-")
+                    .arg("+")
+                    .arg(count)
+                    .query_async(&mut conn)
+                    .await?
+            }
+        };
+        let waiting: usize = conn.zcard(&delayed).await?;
+        let dead: usize = conn.xlen(self.dead(task_id)).await?;
+
+        let in_flight = (pending.count() - expired.ids.len()) as u64;
+        let depth = (length + waiting) as u64 - in_flight;
+        let task = status.task_mut(task_id);
+
+        task.depth = depth;
+        task.in_flight = in_flight;
+        task.dead_lettered = dead as u64;
+        status.depth += depth;
+        status.in_flight += in_flight;
+        status.dead_lettered += dead as u64;
+
+        // Entries are appended in order, so the head of the stream and the first delayed
+        // message to come due are taken to be the oldest
+        let head: StreamRangeReply = conn.xrange_count(&stream, "-", "+", 1).await?;
+        let mut oldest: Vec<i64> = head
+            .ids
+            .iter()
+            .map(|e| Entry::from_stream(e).enqueued_at)
+            .collect();
+        let first: Vec<(String, f64)> = conn.zrange_withscores(&delayed, 0, 0).await?;
+
+        if let Some((id, _)) = first.first() {
+            let enqueued_at: Option<String> = conn
+                .hget(format!("{}:{}", delayed, id), "enqueued_at")
+                .await?;
+
+            oldest.extend(enqueued_at.and_then(|e| e.parse::<i64>().ok()));
+        }
+
+        if let Some(oldest) = oldest.into_iter().min() {
+            status.observe_age(Duration::from_millis(
+                now.saturating_sub(oldest).max(0) as u64
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn lease_id(task_id: &str, entry_id: &str) -> String {
+    format!("{}/{}", task_id, entry_id)
+}
+
+// Task ids can't contain `/`, so the last one separates it from the entry id
+fn parse_lease_id(id: &str) -> Result<(&str, &str), AppError> {
+    id.rsplit_once('/')
+        .ok_or_else(|| AppError::QueueLeaseNotFound(id.to_string()))
+}
+
+fn decode_record(entry: &StreamId) -> Result<Record, AppError> {
+    let payload: Vec<u8> = entry.get("payload").unwrap_or_default();
+
+    Record::decode(&payload)
+}
+
+#[async_trait]
+impl Queue for RedisQueue {
+    async fn put(&self, record: Record) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let task_id = record.task_id.clone();
+        let entry = Entry {
+            payload: record.encode(self.encoding)?,
+            task_id: record.task_id,
+            attempts: 0,
+            enqueued_at: Utc::now().timestamp_millis(),
+        };
+
+        self.register(&task_id).await?;
+
+        let _: String = conn
+            .xadd(self.stream(&task_id), "*", &entry.fields())
+            .await?;
+
+        self.record_rate(&task_id, "enqueue").await
+    }
+
+    async fn put_delayed(
+        &self,
+        record: Record,
+        _priority: i64,
+        delay: Duration,
+    ) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let task_id = record.task_id.clone();
+        let delayed = self.delayed(&task_id);
+        let entry = Entry {
+            payload: record.encode(self.encoding)?,
+            task_id: record.task_id,
+            attempts: 0,
+            enqueued_at: Utc::now().timestamp_millis(),
+        };
+        // Never on the stream yet, so it has no entry id of its own
+        let id = Uuid::new_v4().to_string();
+
+        self.register(&task_id).await?;
+
+        let _: () = pipe()
+            .atomic()
+            .hset_multiple(format!("{}:{}", delayed, id), &entry.fields())
+            .ignore()
+            .zadd(&delayed, &id, entry.enqueued_at + delay.as_millis() as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        self.record_rate(&task_id, "enqueue").await
+    }
+
+    async fn get(&self, wait: Duration) -> Result<Option<QueueMessage>, AppError> {
+        let deadline = Instant::now() + wait;
+
+        loop {
+            for task_id in self.tasks().await? {
+                self.promote_delayed(&task_id).await?;
+
+                if let Some(message) = self.try_lease(&task_id).await? {
+                    return Ok(Some(message));
+                }
+            }
+
+            let now = Instant::now();
+
+            if now >= deadline {
+                return Ok(None);
+            }
+
+            sleep(POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    async fn ack(&self, id: &str) -> Result<(), AppError> {
+        let (task_id, entry_id) = parse_lease_id(id)?;
+        let stream = self.stream(task_id);
+        let mut conn = self.conn.clone();
+        let (acked, _): (usize, usize) = pipe()
+            .atomic()
+            .xack(&stream, &self.group, &[entry_id])
+            .xdel(&stream, &[entry_id])
+            .query_async(&mut conn)
+            .await?;
+
+        match acked {
+            0 => Err(AppError::QueueLeaseNotFound(id.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    async fn nack(&self, id: &str, delay: Duration) -> Result<(), AppError> {
+        let (task_id, entry_id) = parse_lease_id(id)?;
+        let stream_entry = self.pending_entry(task_id, entry_id).await?;
+        let attempts = self.attempts(task_id, &stream_entry).await?;
+
+        if attempts >= self.max_attempts {
+            return self
+                .dead_letter(task_id, &stream_entry, "exceeded max attempts")
+                .await;
+        }
+
+        let mut conn = self.conn.clone();
+        let stream = self.stream(task_id);
+        let entry = Entry {
+            attempts,
+            ..Entry::from_stream(&stream_entry)
+        };
+        let mut pipe = pipe();
+
+        pipe.atomic()
+            .xack(&stream, &self.group, &[entry_id])
+            .ignore()
+            .xdel(&stream, &[entry_id])
+            .ignore();
+
+        if delay.is_zero() {
+            pipe.xadd(&stream, "*", &entry.fields()).ignore();
+        } else {
+            let delayed = self.delayed(task_id);
+            let visible_at = Utc::now().timestamp_millis() + delay.as_millis() as i64;
+
+            pipe.hset_multiple(format!("{}:{}", delayed, entry_id), &entry.fields())
+                .ignore()
+                .zadd(&delayed, entry_id, visible_at)
+                .ignore();
+        }
+
+        let _: () = pipe.query_async(&mut conn).await?;
+
+        Ok(())
+    }
+
+    async fn status(&self) -> Result<QueueStatus, AppError> {
+        let tasks = self.tasks().await?;
+        let mut status = QueueStatus {
+            enqueue_rate: self.rate(&tasks, "enqueue").await?,
+            dequeue_rate: self.rate(&tasks, "dequeue").await?,
+            ..Default::default()
+        };
+
+        for task_id in tasks {
+            self.observe_task(&task_id, &mut status).await?;
+        }
+
+        Ok(status)
+    }
+
+    async fn purge(&self, task_id: &str) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let delayed = self.delayed(task_id);
+        let ids: Vec<String> = conn.zrange(&delayed, 0, -1).await?;
+        let mut pipe = pipe();
+
+        pipe.atomic();
+
+        for id in ids {
+            pipe.del(format!("{}:{}", delayed, id)).ignore();
+        }
+
+        let _: () = pipe
+            .del(&[self.stream(task_id), delayed, self.dead(task_id)])
+            .ignore()
+            .srem(self.tasks_key(), task_id)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        self.registered.lock().unwrap().remove(task_id);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::structs::record::HopType;
+
+    use super::*;
+
+    fn record(uri: &str) -> Record {
+        Record {
+            uri: uri.to_string(),
+            task_id: "test".to_string(),
+            metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        }
+    }
+
+    // Each test runs against its own stream on a local redis-server
+    fn config(stream: &str, consumer: &str, visibility_timeout: u64) -> RedisQueueConfig {
+        RedisQueueConfig {
+            uri: "redis://localhost:6379".to_string(),
+            stream: stream.to_string(),
+            group: "test".to_string(),
+            consumer: Some(consumer.to_string()),
+            visibility_timeout,
+            max_attempts: 2,
+            encoding: RecordEncoding::Json,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_get_ack() {
+        let stream = Uuid::new_v4().to_string();
+        let queue = RedisQueue::new(config(&stream, "a", 30), None)
+            .await
+            .unwrap();
+
+        queue.put(record("http://example.com/a")).await.unwrap();
+        queue.put(record("http://example.com/b")).await.unwrap();
+
+        let message = queue.get(Duration::ZERO).await.unwrap().unwrap();
+
+        assert_eq!(message.record.uri, "http://example.com/a");
+        assert_eq!(message.attempts, 1);
+
+        let status = queue.status().await.unwrap();
+
+        assert_eq!((status.depth, status.in_flight), (1, 1));
+        assert_eq!(status.tasks["test"].in_flight, 1);
+        assert_eq!(status.enqueue_rate, 2.0 / 60.0);
+        assert_eq!(status.dequeue_rate, 1.0 / 60.0);
+
+        queue.ack(&message.id).await.unwrap();
+
+        assert!(matches!(
+            queue.ack(&message.id).await.unwrap_err(),
+            AppError::QueueLeaseNotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_consumers_share_stream() {
+        let stream = Uuid::new_v4().to_string();
+        let first = RedisQueue::new(config(&stream, "a", 1), None)
+            .await
+            .unwrap();
+        let second = RedisQueue::new(config(&stream, "b", 1), None)
+            .await
+            .unwrap();
+
+        first.put(record("http://example.com/a")).await.unwrap();
+
+        let leased = first.get(Duration::ZERO).await.unwrap().unwrap();
+
+        assert!(second.get(Duration::ZERO).await.unwrap().is_none());
+
+        // The first consumer never acks, so the entry is claimed by the second one
+        let reclaimed = second.get(Duration::from_secs(3)).await.unwrap().unwrap();
+
+        assert_eq!(reclaimed.id, leased.id);
+        assert_eq!(reclaimed.attempts, 2);
+        second.ack(&reclaimed.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nack_and_dead_letter() {
+        let stream = Uuid::new_v4().to_string();
+        let queue = RedisQueue::new(config(&stream, "a", 30), None)
+            .await
+            .unwrap();
+
+        queue.put(record("http://example.com/a")).await.unwrap();
+
+        let message = queue.get(Duration::ZERO).await.unwrap().unwrap();
+        queue
+            .nack(&message.id, Duration::from_millis(50))
+            .await
+            .unwrap();
+
+        assert_eq!(queue.status().await.unwrap().depth, 1);
+        assert!(queue.get(Duration::ZERO).await.unwrap().is_none());
+
+        let message = queue.get(Duration::from_secs(1)).await.unwrap().unwrap();
+
+        assert_eq!(message.attempts, 2);
+
+        queue.nack(&message.id, Duration::ZERO).await.unwrap();
+
+        let dead_letters = queue.dead_letters().await.unwrap();
+
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].attempts, 2);
+
+        let status = queue.status().await.unwrap();
+
+        assert_eq!((status.depth, status.in_flight), (0, 0));
+        assert_eq!(status.dead_lettered, 1);
+        assert_eq!(status.tasks["test"].dead_lettered, 1);
+    }
+
+    #[tokio::test]
+    async fn test_put_delayed() {
+        let stream = Uuid::new_v4().to_string();
+        let queue = RedisQueue::new(config(&stream, "a", 30), None)
+            .await
+            .unwrap();
+
+        queue
+            .put_delayed(record("http://example.com/a"), 0, Duration::from_millis(50))
+            .await
+            .unwrap();
+
+        assert!(queue.get(Duration::ZERO).await.unwrap().is_none());
+        assert_eq!(queue.status().await.unwrap().depth, 1);
+
+        let message = queue.get(Duration::from_secs(1)).await.unwrap().unwrap();
+
+        assert_eq!(message.record.uri, "http://example.com/a");
+        assert_eq!(message.attempts, 1);
+        queue.ack(&message.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_task_scoped_queues() {
+        let stream = Uuid::new_v4().to_string();
+        let crawl = RedisQueue::new(config(&stream, "a", 30), Some("test"))
+            .await
+            .unwrap();
+        let other = RedisQueue::new(config(&stream, "b", 30), Some("other"))
+            .await
+            .unwrap();
+        let all = RedisQueue::new(config(&stream, "c", 30), None)
+            .await
+            .unwrap();
+
+        crawl.put(record("http://example.com/a")).await.unwrap();
+
+        assert!(other.get(Duration::ZERO).await.unwrap().is_none());
+        assert_eq!(other.status().await.unwrap().depth, 0);
+        assert_eq!(other.status().await.unwrap().enqueue_rate, 0.0);
+        assert_eq!(crawl.status().await.unwrap().enqueue_rate, 1.0 / 60.0);
+        assert_eq!(all.status().await.unwrap().depth, 1);
+
+        // A lease taken by one worker can be acked through any queue on the same stream
+        let message = crawl.get(Duration::ZERO).await.unwrap().unwrap();
+
+        all.ack(&message.id).await.unwrap();
+        all.purge("test").await.unwrap();
+
+        assert_eq!(all.status().await.unwrap().tasks.len(), 1);
+    }
+}
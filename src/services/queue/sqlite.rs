@@ -0,0 +1,712 @@
+use std::{str::FromStr, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{
+    Pool, Row, Sqlite, SqlitePool, query,
+    sqlite::{SqliteConnectOptions, SqliteRow},
+};
+use tokio::time::{Instant, sleep};
+use uuid::Uuid;
+
+use crate::types::{
+    configs::queues::sqlite_queue_config::SqliteQueueConfig,
+    error::AppError,
+    structs::{
+        dead_letter::DeadLetter,
+        queue_message::QueueMessage,
+        queue_status::{QueueStatus, RATE_WINDOW},
+        record::{Record, RecordEncoding},
+    },
+    traits::queue::Queue,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Durable queue for a single host. Messages, leases and attempt counts live in SQLite so
+// a restarted process picks up where the last one stopped; leases held by a process that
+// died are redelivered once they expire.
+//
+// A queue opened for a task only leases and reports on that task's messages, so crawls
+// can share one database.
+pub struct SqliteQueue {
+    db: SqlitePool,
+    task_id: Option<String>,
+    visibility_timeout: Duration,
+    max_attempts: u32,
+    encoding: RecordEncoding,
+}
+
+impl SqliteQueue {
+    pub async fn new(config: SqliteQueueConfig, task_id: Option<&str>) -> Result<Self, AppError> {
+        let options = SqliteConnectOptions::from_str(&config.path)?.create_if_missing(true);
+        let db = SqlitePool::connect_with(options).await?;
+
+        Self::init_db(&db).await?;
+
+        Ok(Self {
+            db,
+            task_id: task_id.map(str::to_string),
+            visibility_timeout: Duration::from_secs(config.visibility_timeout),
+            max_attempts: config.max_attempts,
+            encoding: config.encoding,
+        })
+    }
+
+    pub async fn init_db(db: &Pool<Sqlite>) -> Result<(), AppError> {
+        query(
+            r#"
+            CREATE TABLE IF NOT EXISTS queue (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                priority    INTEGER NOT NULL,
+                task_id     TEXT NOT NULL,
+                uri         TEXT NOT NULL,
+                payload     BLOB NOT NULL,
+                attempts    INTEGER NOT NULL DEFAULT 0,
+                visible_at  INTEGER NOT NULL,
+                lease_id    TEXT UNIQUE,
+                lease_until INTEGER,
+                enqueued_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(db)
+        .await?;
+
+        query("CREATE INDEX IF NOT EXISTS queue_ready ON queue (priority DESC, id) WHERE lease_id IS NULL")
+            .execute(db)
+            .await?;
+
+        query(
+            r#"
+            CREATE TABLE IF NOT EXISTS dead_letter (
+                id               INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id          TEXT NOT NULL,
+                uri              TEXT NOT NULL,
+                payload          BLOB NOT NULL,
+                attempts         INTEGER NOT NULL,
+                reason           TEXT NOT NULL,
+                dead_lettered_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(db)
+        .await?;
+
+        // Rates used to be kept for the whole database. They only cover the last minute,
+        // so a table of that shape is dropped rather than migrated.
+        if query("SELECT task_id FROM queue_rate LIMIT 0")
+            .execute(db)
+            .await
+            .is_err()
+        {
+            query("DROP TABLE IF EXISTS queue_rate").execute(db).await?;
+        }
+
+        query(
+            r#"
+            CREATE TABLE IF NOT EXISTS queue_rate (
+                task_id TEXT NOT NULL,
+                kind    TEXT NOT NULL,
+                second  INTEGER NOT NULL,
+                count   INTEGER NOT NULL,
+                PRIMARY KEY (task_id, kind, second)
+            )
+            "#,
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>, AppError> {
+        let rows = query(
+            r#"
+            SELECT payload, attempts, reason, dead_lettered_at FROM dead_letter
+            WHERE ? IS NULL OR task_id = ?
+            ORDER BY id
+            "#,
+        )
+        .bind(&self.task_id)
+        .bind(&self.task_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let timestamp = row.get::<i64, _>("dead_lettered_at");
+
+                Ok(DeadLetter {
+                    record: decode_record(&row)?,
+                    attempts: row.get::<u32, _>("attempts"),
+                    reason: row.get("reason"),
+                    errors: vec![],
+                    timestamp: DateTime::from_timestamp_millis(timestamp).unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    // Counts an enqueue or dequeue of a task's message in the current second and forgets
+    // seconds that fell out of the rate window.
+    async fn record_rate(&self, task_id: &str, kind: &str, now: i64) -> Result<(), AppError> {
+        let second = now / 1000;
+        let mut tx = self.db.begin().await?;
+
+        query(
+            r#"
+            INSERT INTO queue_rate (task_id, kind, second, count) VALUES (?, ?, ?, 1)
+            ON CONFLICT (task_id, kind, second) DO UPDATE SET count = count + 1
+            "#,
+        )
+        .bind(task_id)
+        .bind(kind)
+        .bind(second)
+        .execute(&mut *tx)
+        .await?;
+
+        query("DELETE FROM queue_rate WHERE second <= ?")
+            .bind(second - RATE_WINDOW.as_secs() as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    // Releases expired leases, dead lettering the ones that used up their last attempt.
+    async fn reclaim_expired(&self, now: i64) -> Result<(), AppError> {
+        let mut tx = self.db.begin().await?;
+
+        query(
+            r#"
+            INSERT INTO dead_letter (task_id, uri, payload, attempts, reason, dead_lettered_at)
+            SELECT task_id, uri, payload, attempts, 'lease expired on final attempt', ?
+            FROM queue
+            WHERE lease_id IS NOT NULL AND lease_until <= ? AND attempts >= ?
+            "#,
+        )
+        .bind(now)
+        .bind(now)
+        .bind(self.max_attempts)
+        .execute(&mut *tx)
+        .await?;
+
+        query(
+            "DELETE FROM queue WHERE lease_id IS NOT NULL AND lease_until <= ? AND attempts >= ?",
+        )
+        .bind(now)
+        .bind(self.max_attempts)
+        .execute(&mut *tx)
+        .await?;
+
+        query(
+            r#"
+            UPDATE queue SET lease_id = NULL, lease_until = NULL, visible_at = ?
+            WHERE lease_id IS NOT NULL AND lease_until <= ?
+            "#,
+        )
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn insert(&self, record: Record, priority: i64, delay: Duration) -> Result<(), AppError> {
+        let now = Utc::now().timestamp_millis();
+
+        query(
+            r#"
+            INSERT INTO queue (priority, task_id, uri, payload, visible_at, enqueued_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(priority)
+        .bind(&record.task_id)
+        .bind(&record.uri)
+        .bind(record.encode(self.encoding)?)
+        .bind(now + delay.as_millis() as i64)
+        .bind(now)
+        .execute(&self.db)
+        .await?;
+
+        self.record_rate(&record.task_id, "enqueue", now).await
+    }
+
+    async fn try_lease(&self) -> Result<Option<QueueMessage>, AppError> {
+        let now = Utc::now().timestamp_millis();
+
+        self.reclaim_expired(now).await?;
+
+        let id = Uuid::new_v4().to_string();
+        let lease_until = now + self.visibility_timeout.as_millis() as i64;
+        let row = query(
+            r#"
+            UPDATE queue SET lease_id = ?, lease_until = ?, attempts = attempts + 1
+            WHERE id = (
+                SELECT id FROM queue
+                WHERE lease_id IS NULL AND visible_at <= ? AND (? IS NULL OR task_id = ?)
+                ORDER BY priority DESC, id
+                LIMIT 1
+            )
+            RETURNING task_id, payload, attempts, priority
+            "#,
+        )
+        .bind(&id)
+        .bind(lease_until)
+        .bind(now)
+        .bind(&self.task_id)
+        .bind(&self.task_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        self.record_rate(row.get("task_id"), "dequeue", now).await?;
+
+        Ok(Some(QueueMessage {
+            id,
+            record: decode_record(&row)?,
+            attempts: row.get::<u32, _>("attempts"),
+            priority: row.get::<i64, _>("priority"),
+        }))
+    }
+
+    async fn dead_letter(&self, id: &str, reason: &str) -> Result<(), AppError> {
+        let mut tx = self.db.begin().await?;
+
+        let inserted = query(
+            r#"
+            INSERT INTO dead_letter (task_id, uri, payload, attempts, reason, dead_lettered_at)
+            SELECT task_id, uri, payload, attempts, ?, ? FROM queue WHERE lease_id = ?
+            "#,
+        )
+        .bind(reason)
+        .bind(Utc::now().timestamp_millis())
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if inserted.rows_affected() == 0 {
+            return Err(AppError::QueueLeaseNotFound(id.to_string()));
+        }
+
+        query("DELETE FROM queue WHERE lease_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+fn decode_record(row: &SqliteRow) -> Result<Record, AppError> {
+    Record::decode(row.get("payload"))
+}
+
+#[async_trait]
+impl Queue for SqliteQueue {
+    async fn put(&self, record: Record) -> Result<(), AppError> {
+        self.put_with_priority(record, 0).await
+    }
+
+    async fn put_with_priority(&self, record: Record, priority: i64) -> Result<(), AppError> {
+        self.insert(record, priority, Duration::ZERO).await
+    }
+
+    async fn put_delayed(
+        &self,
+        record: Record,
+        priority: i64,
+        delay: Duration,
+    ) -> Result<(), AppError> {
+        self.insert(record, priority, delay).await
+    }
+
+    async fn get(&self, wait: Duration) -> Result<Option<QueueMessage>, AppError> {
+        let deadline = Instant::now() + wait;
+
+        loop {
+            if let Some(message) = self.try_lease().await? {
+                return Ok(Some(message));
+            }
+
+            let now = Instant::now();
+
+            if now >= deadline {
+                return Ok(None);
+            }
+
+            sleep(POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    async fn ack(&self, id: &str) -> Result<(), AppError> {
+        let deleted = query("DELETE FROM queue WHERE lease_id = ?")
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+
+        match deleted.rows_affected() {
+            0 => Err(AppError::QueueLeaseNotFound(id.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    async fn nack(&self, id: &str, delay: Duration) -> Result<(), AppError> {
+        let attempts: Option<u32> = query("SELECT attempts FROM queue WHERE lease_id = ?")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .map(|row| row.get("attempts"));
+
+        match attempts {
+            None => Err(AppError::QueueLeaseNotFound(id.to_string())),
+            Some(a) if a >= self.max_attempts => {
+                self.dead_letter(id, "exceeded max attempts").await
+            }
+            Some(_) => {
+                let visible_at = Utc::now().timestamp_millis() + delay.as_millis() as i64;
+
+                query(
+                    r#"
+                    UPDATE queue SET lease_id = NULL, lease_until = NULL, visible_at = ?
+                    WHERE lease_id = ?
+                    "#,
+                )
+                .bind(visible_at)
+                .bind(id)
+                .execute(&self.db)
+                .await?;
+
+                Ok(())
+            }
+        }
+    }
+
+    async fn status(&self) -> Result<QueueStatus, AppError> {
+        let now = Utc::now().timestamp_millis();
+        let mut status = QueueStatus::default();
+
+        let queued = query(
+            r#"
+            SELECT
+                task_id,
+                COUNT(*) FILTER (WHERE lease_id IS NULL OR lease_until <= ?) AS depth,
+                COUNT(*) FILTER (WHERE lease_id IS NOT NULL AND lease_until > ?) AS in_flight,
+                MIN(enqueued_at) AS oldest
+            FROM queue
+            WHERE ? IS NULL OR task_id = ?
+            GROUP BY task_id
+            "#,
+        )
+        .bind(now)
+        .bind(now)
+        .bind(&self.task_id)
+        .bind(&self.task_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        for row in queued {
+            let depth = row.get::<i64, _>("depth") as u64;
+            let in_flight = row.get::<i64, _>("in_flight") as u64;
+            let task = status.task_mut(row.get("task_id"));
+
+            task.depth = depth;
+            task.in_flight = in_flight;
+            status.depth += depth;
+            status.in_flight += in_flight;
+
+            let oldest = row.get::<i64, _>("oldest");
+            status.observe_age(Duration::from_millis(
+                now.saturating_sub(oldest).max(0) as u64
+            ));
+        }
+
+        let dead = query(
+            r#"
+            SELECT task_id, COUNT(*) AS dead_lettered FROM dead_letter
+            WHERE ? IS NULL OR task_id = ?
+            GROUP BY task_id
+            "#,
+        )
+        .bind(&self.task_id)
+        .bind(&self.task_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        for row in dead {
+            let dead_lettered = row.get::<i64, _>("dead_lettered") as u64;
+
+            status.task_mut(row.get("task_id")).dead_lettered = dead_lettered;
+            status.dead_lettered += dead_lettered;
+        }
+
+        let rates = query(
+            r#"
+            SELECT kind, SUM(count) AS total FROM queue_rate
+            WHERE second > ? AND (? IS NULL OR task_id = ?)
+            GROUP BY kind
+            "#,
+        )
+        .bind(now / 1000 - RATE_WINDOW.as_secs() as i64)
+        .bind(&self.task_id)
+        .bind(&self.task_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        for row in rates {
+            let rate = row.get::<i64, _>("total") as f64 / RATE_WINDOW.as_secs_f64();
+
+            match row.get::<&str, _>("kind") {
+                "enqueue" => status.enqueue_rate = rate,
+                _ => status.dequeue_rate = rate,
+            }
+        }
+
+        Ok(status)
+    }
+
+    async fn purge(&self, task_id: &str) -> Result<(), AppError> {
+        let mut tx = self.db.begin().await?;
+
+        query("DELETE FROM queue WHERE task_id = ?")
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+
+        query("DELETE FROM dead_letter WHERE task_id = ?")
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+
+        query("DELETE FROM queue_rate WHERE task_id = ?")
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use crate::types::structs::record::HopType;
+
+    use super::*;
+
+    fn record(uri: &str) -> Record {
+        Record {
+            uri: uri.to_string(),
+            task_id: "test".to_string(),
+            metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        }
+    }
+
+    fn config(path: &str, visibility_timeout: u64, max_attempts: u32) -> SqliteQueueConfig {
+        SqliteQueueConfig {
+            path: path.to_string(),
+            visibility_timeout,
+            max_attempts,
+            encoding: RecordEncoding::default(),
+        }
+    }
+
+    fn temp_db() -> String {
+        temp_dir()
+            .join(format!("{}.db", Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_priority_order() {
+        let queue = SqliteQueue::new(config(&temp_db(), 30, 5), None)
+            .await
+            .unwrap();
+
+        queue.put(record("http://example.com/low")).await.unwrap();
+        queue
+            .put_with_priority(record("http://example.com/high"), 10)
+            .await
+            .unwrap();
+        queue
+            .put_with_priority(record("http://example.com/mid"), 5)
+            .await
+            .unwrap();
+
+        let mut uris = vec![];
+
+        while let Some(message) = queue.get(Duration::ZERO).await.unwrap() {
+            queue.ack(&message.id).await.unwrap();
+            uris.push(message.record.uri);
+        }
+
+        assert_eq!(
+            uris,
+            vec![
+                "http://example.com/high",
+                "http://example.com/mid",
+                "http://example.com/low"
+            ]
+        );
+        let status = queue.status().await.unwrap();
+
+        assert_eq!((status.depth, status.in_flight), (0, 0));
+        assert_eq!(status.enqueue_rate, 3.0 / 60.0);
+        assert_eq!(status.dequeue_rate, 3.0 / 60.0);
+        assert!(status.oldest_age.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_restart() {
+        let path = temp_db();
+        let queue = SqliteQueue::new(config(&path, 1, 5), None).await.unwrap();
+
+        queue.put(record("http://example.com/a")).await.unwrap();
+        queue.put(record("http://example.com/b")).await.unwrap();
+
+        let leased = queue.get(Duration::ZERO).await.unwrap().unwrap();
+
+        assert_eq!(leased.record.uri, "http://example.com/a");
+
+        drop(queue);
+
+        let queue = SqliteQueue::new(config(&path, 1, 5), None).await.unwrap();
+
+        let status = queue.status().await.unwrap();
+
+        assert_eq!((status.depth, status.in_flight), (1, 1));
+        assert_eq!(status.tasks["test"].depth, 1);
+
+        let b = queue.get(Duration::ZERO).await.unwrap().unwrap();
+        assert_eq!(b.record.uri, "http://example.com/b");
+        queue.ack(&b.id).await.unwrap();
+
+        // An expired lease is waiting to be delivered again, not in flight
+        sleep(Duration::from_millis(1100)).await;
+
+        let status = queue.status().await.unwrap();
+
+        assert_eq!((status.depth, status.in_flight), (1, 0));
+
+        let a = queue.get(Duration::from_secs(2)).await.unwrap().unwrap();
+        assert_eq!(a.record.uri, "http://example.com/a");
+        assert_eq!(a.attempts, 2);
+        assert!(queue.ack(&leased.id).await.is_err());
+        queue.ack(&a.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_delayed() {
+        let queue = SqliteQueue::new(config(&temp_db(), 30, 5), None)
+            .await
+            .unwrap();
+
+        queue
+            .put_delayed(
+                record("http://example.com/a"),
+                0,
+                Duration::from_millis(200),
+            )
+            .await
+            .unwrap();
+
+        assert!(queue.get(Duration::ZERO).await.unwrap().is_none());
+        assert_eq!(queue.status().await.unwrap().depth, 1);
+
+        let message = queue.get(Duration::from_secs(2)).await.unwrap().unwrap();
+
+        assert_eq!(message.record.uri, "http://example.com/a");
+        assert_eq!(message.attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_after_max_attempts() {
+        let queue = SqliteQueue::new(config(&temp_db(), 30, 2), None)
+            .await
+            .unwrap();
+
+        queue.put(record("http://example.com/a")).await.unwrap();
+
+        for _ in 0..2 {
+            let message = queue.get(Duration::ZERO).await.unwrap().unwrap();
+            queue.nack(&message.id, Duration::ZERO).await.unwrap();
+        }
+
+        assert!(queue.get(Duration::ZERO).await.unwrap().is_none());
+
+        let dead_letters = queue.dead_letters().await.unwrap();
+
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].record.uri, "http://example.com/a");
+        assert_eq!(dead_letters[0].attempts, 2);
+        assert_eq!(dead_letters[0].reason, "exceeded max attempts");
+
+        let status = queue.status().await.unwrap();
+
+        assert_eq!(status.dead_lettered, 1);
+        assert_eq!(status.tasks["test"].dead_lettered, 1);
+    }
+
+    #[tokio::test]
+    async fn test_task_scoped_status() {
+        let path = temp_db();
+        let a = SqliteQueue::new(config(&path, 30, 1), Some("a"))
+            .await
+            .unwrap();
+        let b = SqliteQueue::new(config(&path, 30, 1), Some("b"))
+            .await
+            .unwrap();
+
+        for (queue, task_id, count) in [(&a, "a", 1), (&b, "b", 2)] {
+            for i in 0..count {
+                let record = Record {
+                    task_id: task_id.to_string(),
+                    ..record(&format!("http://example.com/{i}"))
+                };
+
+                queue.put(record).await.unwrap();
+            }
+        }
+
+        let message = a.get(Duration::ZERO).await.unwrap().unwrap();
+        a.nack(&message.id, Duration::ZERO).await.unwrap();
+
+        // Neither sees the other's rates or dead letters
+        let status = a.status().await.unwrap();
+
+        assert_eq!(status.enqueue_rate, 1.0 / 60.0);
+        assert_eq!(status.dequeue_rate, 1.0 / 60.0);
+        assert_eq!(status.dead_lettered, 1);
+        assert_eq!(a.dead_letters().await.unwrap().len(), 1);
+
+        let status = b.status().await.unwrap();
+
+        assert_eq!(status.enqueue_rate, 2.0 / 60.0);
+        assert_eq!(status.dequeue_rate, 0.0);
+        assert_eq!(status.dead_lettered, 0);
+        assert!(b.dead_letters().await.unwrap().is_empty());
+    }
+}
@@ -20,34 +20,65 @@ impl RedisHashSet {
     }
 }
 
+// Every namespace is one redis set
+fn set_key(namespace: &str) -> String {
+    format!("aetherscope:seen:{}", namespace)
+}
+
 #[async_trait]
 impl CheckHashSet for RedisHashSet {
     async fn contains_entities(
         &self,
+        namespace: &str,
         entities: Vec<String>,
     ) -> Result<Vec<(String, bool)>, AppError> {
+        if entities.is_empty() {
+            return Ok(vec![]);
+        }
+
         let mut conn = self.conn.clone();
+        let key = set_key(namespace);
+        let mut pipe = pipe();
+
+        // SADD answers 0 for members that were already there, so checking and inserting
+        // is one round trip and can't race with another crawler.
+        for entity in &entities {
+            pipe.cmd("SADD").arg(&key).arg(entity);
+        }
+
+        let added: Vec<i32> = pipe.query_async(&mut conn).await.map_err(AppError::from)?;
+
+        Ok(entities
+            .into_iter()
+            .zip(added.into_iter().map(|a| a == 0))
+            .collect())
+    }
+
+    async fn add_entities(&self, namespace: &str, entities: Vec<String>) -> Result<(), AppError> {
+        if entities.is_empty() {
+            return Ok(());
+        }
 
-        let vals: Vec<Option<u8>> = cmd("MGET")
-            .arg(&entities)
+        let mut conn = self.conn.clone();
+        let _: i32 = cmd("SADD")
+            .arg(set_key(namespace))
+            .arg(entities)
             .query_async(&mut conn)
             .await
             .map_err(AppError::from)?;
 
-        let existed: Vec<bool> = vals.iter().map(|v| v.is_some()).collect();
-        let mut pipe = pipe();
-
-        for (k, did_exist) in entities.iter().zip(existed.iter()) {
-            if !did_exist {
-                pipe.cmd("SETNX").arg(k).arg(1u8);
-            }
-        }
+        Ok(())
+    }
 
-        if !pipe.is_empty() {
-            let _: Vec<i32> = pipe.query_async(&mut conn).await.map_err(AppError::from)?;
-        }
+    async fn purge(&self, namespace: &str) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let _: i32 = cmd("DEL")
+            .arg(set_key(namespace))
+            .query_async(&mut conn)
+            .await
+            .map_err(AppError::from)?;
 
-        Ok(entities.into_iter().zip(existed.into_iter()).collect())
+        Ok(())
     }
 }
 
@@ -67,7 +98,10 @@ mod tests {
 
         let hash_set = RedisHashSet::new(config).await.unwrap();
         let entities: Vec<String> = (0..100).map(|_| Uuid::new_v4().to_string()).collect();
-        let results = hash_set.contains_entities(entities.clone()).await.unwrap();
+        let results = hash_set
+            .contains_entities("test", entities.clone())
+            .await
+            .unwrap();
 
         assert!(results.iter().all(|(_, b)| !*b));
 
@@ -75,7 +109,7 @@ mod tests {
 
         some_true.extend(entities);
 
-        let results = hash_set.contains_entities(some_true).await.unwrap();
+        let results = hash_set.contains_entities("test", some_true).await.unwrap();
 
         let mut counts = HashMap::new();
         for (_, b) in &results {
@@ -22,9 +22,17 @@ impl SqliteHashSet {
     }
 
     pub async fn init_db(db: &Pool<Sqlite>) -> Result<(), AppError> {
-        sqlx::query("CREATE TABLE entity (name TEXT PRIMARY KEY)")
-            .execute(db)
-            .await?;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS seen (
+                namespace TEXT NOT NULL,
+                name      TEXT NOT NULL,
+                PRIMARY KEY (namespace, name)
+            )
+            "#,
+        )
+        .execute(db)
+        .await?;
 
         Ok(())
     }
@@ -34,31 +42,32 @@ impl SqliteHashSet {
 impl CheckHashSet for SqliteHashSet {
     async fn contains_entities(
         &self,
+        namespace: &str,
         entities: Vec<String>,
     ) -> Result<Vec<(String, bool)>, AppError> {
         if entities.is_empty() {
             return Ok(vec![]);
         }
 
-        let vals = std::iter::repeat("(?)")
-            .take(entities.len())
+        let vals = std::iter::repeat_n("(?)", entities.len())
             .collect::<Vec<_>>()
             .join(",");
 
         let select_sql = format!(
             r#"
         WITH input(name) AS (VALUES {vals})
-        SELECT e.name
-        FROM entity e
-        JOIN input i ON i.name = e.name;
+        SELECT s.name
+        FROM seen s
+        JOIN input i ON i.name = s.name
+        WHERE s.namespace = ?;
         "#
         );
 
         let insert_sql = format!(
             r#"
         WITH input(name) AS (VALUES {vals})
-        INSERT OR IGNORE INTO entity(name)
-        SELECT name FROM input;
+        INSERT OR IGNORE INTO seen(namespace, name)
+        SELECT ?, name FROM input;
         "#
         );
 
@@ -69,6 +78,8 @@ impl CheckHashSet for SqliteHashSet {
             sel = sel.bind(e);
         }
 
+        sel = sel.bind(namespace);
+
         let existing_rows = sel.fetch_all(&mut *tx).await?;
         let existing: HashSet<String> = existing_rows
             .into_iter()
@@ -80,6 +91,8 @@ impl CheckHashSet for SqliteHashSet {
             ins = ins.bind(e);
         }
 
+        ins = ins.bind(namespace);
+
         ins.execute(&mut *tx).await?;
         tx.commit().await?;
 
@@ -91,6 +104,43 @@ impl CheckHashSet for SqliteHashSet {
             })
             .collect())
     }
+
+    async fn add_entities(&self, namespace: &str, entities: Vec<String>) -> Result<(), AppError> {
+        if entities.is_empty() {
+            return Ok(());
+        }
+
+        let vals = std::iter::repeat_n("(?)", entities.len())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let insert_sql = format!(
+            r#"
+        WITH input(name) AS (VALUES {vals})
+        INSERT OR IGNORE INTO seen(namespace, name)
+        SELECT ?, name FROM input;
+        "#
+        );
+
+        let mut ins = query(&insert_sql);
+
+        for e in &entities {
+            ins = ins.bind(e);
+        }
+
+        ins.bind(namespace).execute(&self.db).await?;
+
+        Ok(())
+    }
+
+    async fn purge(&self, namespace: &str) -> Result<(), AppError> {
+        query("DELETE FROM seen WHERE namespace = ?")
+            .bind(namespace)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -109,7 +159,10 @@ mod tests {
 
         let hash_set = SqliteHashSet::new(config).await.unwrap();
         let entities: Vec<String> = (0..100).map(|_| Uuid::new_v4().to_string()).collect();
-        let results = hash_set.contains_entities(entities.clone()).await.unwrap();
+        let results = hash_set
+            .contains_entities("test", entities.clone())
+            .await
+            .unwrap();
 
         assert!(results.iter().all(|(_, b)| !*b));
 
@@ -117,7 +170,10 @@ mod tests {
 
         some_true.extend(entities);
 
-        let results = hash_set.contains_entities(some_true).await.unwrap();
+        let results = hash_set
+            .contains_entities("test", some_true.clone())
+            .await
+            .unwrap();
 
         let mut counts = HashMap::new();
         for (_, b) in &results {
@@ -126,5 +182,32 @@ mod tests {
 
         assert_eq!(counts.get(&true), Some(&100));
         assert_eq!(counts.get(&false), Some(&50));
+
+        // Other namespaces and purged ones start out empty
+        let results = hash_set
+            .contains_entities("other", some_true.clone())
+            .await
+            .unwrap();
+
+        assert!(results.iter().all(|(_, b)| !*b));
+
+        hash_set.purge("test").await.unwrap();
+        let results = hash_set
+            .contains_entities("test", some_true.clone())
+            .await
+            .unwrap();
+
+        assert!(results.iter().all(|(_, b)| !*b));
+
+        hash_set
+            .add_entities("added", some_true.clone())
+            .await
+            .unwrap();
+        let results = hash_set
+            .contains_entities("added", some_true)
+            .await
+            .unwrap();
+
+        assert!(results.iter().all(|(_, b)| *b));
     }
 }
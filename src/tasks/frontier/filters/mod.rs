@@ -1,3 +1,4 @@
 pub mod hash_sets;
 pub mod robots;
+pub mod scope;
 pub mod unique;
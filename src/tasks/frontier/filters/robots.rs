@@ -1,5 +1,7 @@
 use std::{collections::HashMap, time::Duration};
 
+use async_trait::async_trait;
+use bytes::Bytes;
 use reqwest::{Client, Proxy};
 use robotxt::Robots;
 use url::Url;
@@ -39,9 +41,10 @@ impl RobotsFilter {
     }
 }
 
+#[async_trait]
 impl FrontierFilter for RobotsFilter {
     async fn perform(
-        self,
+        &mut self,
         uris: Vec<String>,
         _origin: &str,
     ) -> Result<Vec<(String, bool)>, AppError> {
@@ -69,7 +72,14 @@ impl FrontierFilter for RobotsFilter {
             };
 
             let robots_url = get_robots_url(&robots_origin)?;
-            let contents = fetch_http_simple(self.client.clone(), &robots_url).await?;
+            // A missing or forbidden robots.txt places no restrictions on the crawler.
+            let contents = match fetch_http_simple(self.client.clone(), &robots_url).await {
+                Ok(contents) => contents,
+                Err(AppError::FetchError(status, _)) if (400..500).contains(&status) => {
+                    Bytes::new()
+                }
+                Err(e) => return Err(e),
+            };
             let robots = Robots::from_bytes(contents.as_ref(), &self.user_agent);
 
             for uri in bucket {
@@ -110,7 +120,7 @@ mod tests {
                 user_agent: Some(user_agent.to_string()),
             },
         };
-        let filter = RobotsFilter::new(config).unwrap();
+        let mut filter = RobotsFilter::new(config).unwrap();
         let robotstxt = format!(
             r#"User-agent: {}
 Disallow: /admin/
@@ -159,7 +169,7 @@ Disallow: /example/"#,
                 user_agent: Some(user_agent.to_string()),
             },
         };
-        let filter = RobotsFilter::new(config).unwrap();
+        let mut filter = RobotsFilter::new(config).unwrap();
 
         let server = MockServer::start();
         let mock = server.mock(|when, then| {
@@ -181,6 +191,33 @@ Disallow: /example/"#,
         );
     }
 
+    #[tokio::test]
+    async fn test_filter_missing_robots() {
+        let user_agent = "test-user-agent";
+        let config = RobotsFilterConfig {
+            http_config: BasicHttpFetcherConfig {
+                proxy_server: None,
+                timeout: 32,
+                user_agent: Some(user_agent.to_string()),
+            },
+        };
+        let mut filter = RobotsFilter::new(config).unwrap();
+
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .header("user-agent", user_agent)
+                .path("/robots.txt");
+            then.status(404);
+        });
+        let uri = format!("{}/admin/", server.base_url());
+        let filters = filter.perform(vec![uri.clone()], "").await.unwrap();
+
+        mock.assert();
+
+        assert_eq!(filters, vec![(uri, true)]);
+    }
+
     #[tokio::test]
     async fn test_filter_no_response() {
         let user_agent = "test-user-agent";
@@ -191,7 +228,7 @@ Disallow: /example/"#,
                 user_agent: Some(user_agent.to_string()),
             },
         };
-        let filter = RobotsFilter::new(config).unwrap();
+        let mut filter = RobotsFilter::new(config).unwrap();
 
         let err = filter
             .perform(vec!["http://127.0.0.1:9".to_string()], "")
@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use url::Url;
+
+use crate::types::{
+    configs::filters::scope_filter_config::ScopeFilterConfig, error::AppError,
+    traits::frontier_filter::FrontierFilter,
+};
+
+// Keeps a crawl on the hosts it was told to stay on
+pub struct ScopeFilter {
+    hosts: Vec<String>,
+    include_subdomains: bool,
+}
+
+impl ScopeFilter {
+    pub fn new(config: ScopeFilterConfig) -> Self {
+        Self {
+            hosts: config.hosts.iter().map(|h| h.to_lowercase()).collect(),
+            include_subdomains: config.include_subdomains,
+        }
+    }
+
+    fn in_scope(&self, uri: &str) -> bool {
+        let Some(host) = Url::parse(uri)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_lowercase))
+        else {
+            return false;
+        };
+
+        self.hosts.iter().any(|h| {
+            host == *h
+                || (self.include_subdomains
+                    && host
+                        .strip_suffix(h.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.')))
+        })
+    }
+}
+
+#[async_trait]
+impl FrontierFilter for ScopeFilter {
+    async fn perform(
+        &mut self,
+        uris: Vec<String>,
+        _origin: &str,
+    ) -> Result<Vec<(String, bool)>, AppError> {
+        Ok(uris
+            .into_iter()
+            .map(|uri| {
+                let in_scope = self.in_scope(&uri);
+                (uri, in_scope)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_filter_hosts() {
+        let mut filter = ScopeFilter::new(ScopeFilterConfig {
+            hosts: vec!["Example.com".to_string()],
+            include_subdomains: true,
+        });
+        let uris = vec![
+            "https://example.com/a".to_string(),
+            "https://docs.example.com/b".to_string(),
+            "https://notexample.com/c".to_string(),
+            "https://example.org/d".to_string(),
+        ];
+
+        let results = filter.perform(uris, "").await.unwrap();
+        let admitted: Vec<bool> = results.iter().map(|(_, a)| *a).collect();
+
+        assert_eq!(admitted, vec![true, true, false, false]);
+
+        filter.include_subdomains = false;
+
+        let results = filter
+            .perform(vec!["https://docs.example.com/b".to_string()], "")
+            .await
+            .unwrap();
+
+        assert!(!results[0].1);
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
 use fastbloom::BloomFilter;
 
 use crate::{
@@ -9,15 +12,20 @@ use crate::{
         error::AppError,
         traits::{check_hash_set::CheckHashSet, frontier_filter::FrontierFilter},
     },
+    utils::metrics::{HASH_SET_LOOKUPS, metrics},
 };
 
 pub struct UniqueFilter {
     bloom_filter: Option<BloomFilter>,
     hash_set: Option<Box<dyn CheckHashSet>>,
+    // Keeps this crawl's entries in the hash set apart from other crawls'
+    namespace: String,
+    // Backend of `hash_set`, for metrics
+    hash_set_name: &'static str,
 }
 
 impl UniqueFilter {
-    pub async fn new(config: UniqueFilterConfig) -> Result<Self, AppError> {
+    pub async fn new(config: UniqueFilterConfig, namespace: &str) -> Result<Self, AppError> {
         let bloom_filter = match config.bloom_filter.enable {
             true => Some(
                 BloomFilter::with_false_pos(config.bloom_filter.false_positive_rate)
@@ -26,11 +34,18 @@ impl UniqueFilter {
             false => None,
         };
 
+        let hash_set_name = match &config.hash_set {
+            HashSetConfig::Sqlite(_) => "sqlite",
+            HashSetConfig::Redis(_) => "redis",
+            HashSetConfig::Empty => "none",
+        };
         let hash_set = Self::get_hash_set(config.hash_set).await?;
 
         Ok(Self {
             bloom_filter,
             hash_set,
+            namespace: namespace.to_string(),
+            hash_set_name,
         })
     }
 
@@ -48,7 +63,18 @@ impl UniqueFilter {
         })
     }
 
-    pub fn check_bloom(mut filter: BloomFilter, entities: Vec<String>) -> Vec<(String, bool)> {
+    pub fn bloom_filter(&self) -> Option<&BloomFilter> {
+        self.bloom_filter.as_ref()
+    }
+
+    // Replaces the bloom filter with one saved by an earlier run
+    pub fn restore_bloom_filter(&mut self, bloom_filter: BloomFilter) {
+        if self.bloom_filter.is_some() {
+            self.bloom_filter = Some(bloom_filter);
+        }
+    }
+
+    pub fn check_bloom(filter: &mut BloomFilter, entities: Vec<String>) -> Vec<(String, bool)> {
         let mut results = vec![];
 
         for entity in entities {
@@ -68,30 +94,128 @@ impl UniqueFilter {
     }
 }
 
+#[async_trait]
 impl FrontierFilter for UniqueFilter {
     async fn perform(
-        mut self,
+        &mut self,
         uris: Vec<String>,
         _origin: &str,
     ) -> Result<Vec<(String, bool)>, AppError> {
-        Ok(match (&mut self.bloom_filter, &mut self.hash_set) {
-            (Some(bloom_filter), None) => uris
-                .iter()
-                .map(|u| (u.clone(), bloom_filter.contains(u)))
-                .collect(),
-            (None, Some(hash_set)) => hash_set.contains_entities(uris).await?,
+        let (set, seen) = match (&mut self.bloom_filter, &self.hash_set) {
+            (Some(bloom_filter), None) => ("bloom_filter", Self::check_bloom(bloom_filter, uris)),
+            (None, Some(hash_set)) => (
+                self.hash_set_name,
+                hash_set.contains_entities(&self.namespace, uris).await?,
+            ),
             (Some(bloom_filter), Some(hash_set)) => {
-                let (results, false_positives): (Vec<_>, Vec<_>) =
-                    uris.into_iter().partition(|u| bloom_filter.contains(u));
+                // A bloom filter miss is certain, so only its possible hits are looked up in
+                // the hash set. Misses are still recorded there since the hash set may
+                // outlive this process.
+                let checked = Self::check_bloom(bloom_filter, uris);
+                let (hits, misses): (Vec<_>, Vec<_>) =
+                    checked.iter().cloned().partition(|(_, hit)| *hit);
 
-                let mut results: Vec<(String, bool)> =
-                    results.into_iter().map(|u| (u, false)).collect();
+                hash_set
+                    .add_entities(
+                        &self.namespace,
+                        misses.into_iter().map(|(u, _)| u).collect(),
+                    )
+                    .await?;
 
-                results.extend(hash_set.contains_entities(false_positives).await?);
+                let confirmed: HashMap<String, bool> = hash_set
+                    .contains_entities(&self.namespace, hits.into_iter().map(|(u, _)| u).collect())
+                    .await?
+                    .into_iter()
+                    .collect();
 
-                results
+                (
+                    self.hash_set_name,
+                    checked
+                        .into_iter()
+                        .map(|(u, hit)| {
+                            let seen = hit && confirmed.get(&u).copied().unwrap_or(false);
+                            (u, seen)
+                        })
+                        .collect(),
+                )
             }
-            (None, None) => uris.iter().map(|u| (u.clone(), true)).collect(),
-        })
+            (None, None) => return Ok(uris.into_iter().map(|u| (u, true)).collect()),
+        };
+
+        let hits = seen.iter().filter(|(_, seen)| *seen).count();
+
+        for (result, count) in [("hit", hits), ("miss", seen.len() - hits)] {
+            metrics().increment(
+                &HASH_SET_LOOKUPS,
+                &[("set", set), ("result", result)],
+                count as f64,
+            );
+        }
+
+        Ok(seen.into_iter().map(|(u, seen)| (u, !seen)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::configs::filters::unique_filter_config::{
+        BloomFilterConfig, SqliteHashSetConfig,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bloom_filter_admits_once() {
+        let config = UniqueFilterConfig {
+            bloom_filter: BloomFilterConfig {
+                enable: true,
+                false_positive_rate: 0.001,
+                expected_size: 1000,
+            },
+            hash_set: HashSetConfig::Empty,
+        };
+        let mut filter = UniqueFilter::new(config, "test").await.unwrap();
+        let uris = vec![
+            "http://example.com/a".to_string(),
+            "http://example.com/b".to_string(),
+        ];
+
+        let results = filter.perform(uris.clone(), "").await.unwrap();
+        assert!(results.iter().all(|(_, admitted)| *admitted));
+
+        let results = filter.perform(uris, "").await.unwrap();
+        assert!(results.iter().all(|(_, admitted)| !*admitted));
+    }
+
+    #[tokio::test]
+    async fn test_bloom_filter_in_front_of_hash_set() {
+        let path = std::env::temp_dir().join(format!("{}.db", uuid::Uuid::new_v4()));
+        let hash_set = HashSetConfig::Sqlite(SqliteHashSetConfig {
+            path: format!("sqlite://{}?mode=rwc", path.display()),
+        });
+        let config = UniqueFilterConfig {
+            bloom_filter: BloomFilterConfig {
+                enable: true,
+                false_positive_rate: 0.001,
+                expected_size: 1000,
+            },
+            hash_set: hash_set.clone(),
+        };
+        let uris = vec![
+            "http://example.com/a".to_string(),
+            "http://example.com/b".to_string(),
+        ];
+
+        let mut filter = UniqueFilter::new(config.clone(), "test").await.unwrap();
+        let results = filter.perform(uris.clone(), "").await.unwrap();
+        assert!(results.iter().all(|(_, admitted)| *admitted));
+
+        let results = filter.perform(uris.clone(), "").await.unwrap();
+        assert!(results.iter().all(|(_, admitted)| !*admitted));
+
+        // Bloom filter misses were recorded in the hash set too
+        let stored = UniqueFilter::get_hash_set(hash_set).await.unwrap().unwrap();
+        let results = stored.contains_entities("test", uris).await.unwrap();
+        assert!(results.iter().all(|(_, seen)| *seen));
     }
 }
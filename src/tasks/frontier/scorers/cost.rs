@@ -1,5 +1,7 @@
 // Cost (estimate latency from previous requests, how many errors occurred on the host prior, limit how many per host, robots.txt limits)
 
+use async_trait::async_trait;
+
 use crate::types::{
     configs::scorers::cost_scorer_config::CostScorerConfig, error::AppError,
     traits::frontier_scorer::FrontierScorer,
@@ -13,6 +15,7 @@ impl CostScorer {
     }
 }
 
+#[async_trait]
 impl FrontierScorer for CostScorer {
     async fn score(self, uris: Vec<String>, origin: &str) -> Result<Vec<(String, i32)>, AppError> {
         unimplemented!()
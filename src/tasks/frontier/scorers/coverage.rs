@@ -1,5 +1,7 @@
 // Coverage (penalize websites with large url counts)
 
+use async_trait::async_trait;
+
 use crate::types::{
     configs::scorers::coverage_scorer_config::CoverageScorerConfig, error::AppError,
     traits::frontier_scorer::FrontierScorer,
@@ -13,6 +15,7 @@ impl CoverageScorer {
     }
 }
 
+#[async_trait]
 impl FrontierScorer for CoverageScorer {
     async fn score(self, uris: Vec<String>, origin: &str) -> Result<Vec<(String, i32)>, AppError> {
         unimplemented!()
@@ -1,5 +1,7 @@
 // Freshness (separate new from recrawl)
 
+use async_trait::async_trait;
+
 use crate::types::{
     configs::scorers::freshness_scorer_config::FreshnessScorerConfig, error::AppError,
     traits::frontier_scorer::FrontierScorer,
@@ -13,6 +15,7 @@ impl FreshnessScorer {
     }
 }
 
+#[async_trait]
 impl FrontierScorer for FreshnessScorer {
     async fn score(self, uris: Vec<String>, origin: &str) -> Result<Vec<(String, i32)>, AppError> {
         unimplemented!()
@@ -1,5 +1,7 @@
 // Importance (depth from initial seed, how many unique inlinks)
 
+use async_trait::async_trait;
+
 use crate::types::{
     configs::scorers::importance_scorer_config::ImportanceScorerConfig, error::AppError,
     traits::frontier_scorer::FrontierScorer,
@@ -13,6 +15,7 @@ impl ImportanceScorer {
     }
 }
 
+#[async_trait]
 impl FrontierScorer for ImportanceScorer {
     async fn score(self, uris: Vec<String>, origin: &str) -> Result<Vec<(String, i32)>, AppError> {
         unimplemented!()
@@ -1,5 +1,7 @@
 // Expected yield (reward novelty)
 
+use async_trait::async_trait;
+
 use crate::types::{
     configs::scorers::novelty_scorer_config::NoveltyScorerConfig, error::AppError,
     traits::frontier_scorer::FrontierScorer,
@@ -13,6 +15,7 @@ impl NoveltyScorer {
     }
 }
 
+#[async_trait]
 impl FrontierScorer for NoveltyScorer {
     async fn score(self, uris: Vec<String>, origin: &str) -> Result<Vec<(String, i32)>, AppError> {
         unimplemented!()
@@ -1,5 +1,7 @@
 // Spam (proximity to adversarial websites, content variance between pages such as templates or same titles, session ids, repeated path segments)
 
+use async_trait::async_trait;
+
 use crate::types::{
     configs::scorers::spam_scorer_config::SpamScorerConfig, error::AppError,
     traits::frontier_scorer::FrontierScorer,
@@ -13,6 +15,7 @@ impl SpamScorer {
     }
 }
 
+#[async_trait]
 impl FrontierScorer for SpamScorer {
     async fn score(self, uris: Vec<String>, origin: &str) -> Result<Vec<(String, i32)>, AppError> {
         unimplemented!()
@@ -5,7 +5,9 @@ use crate::{
         configs::tasks::headless_browser_config::HeadlessBrowserConfig,
         error::AppError,
         structs::{
-            metadata::http_response::{HttpRequest, HttpResponse},
+            metadata::http_response::{
+                FetchError, FetchErrorKind, FetchTiming, HttpRequest, HttpResponse,
+            },
             record::{Record, RecordMetadata},
         },
         traits::{object_store::ObjectStore, task::Task},
@@ -13,31 +15,40 @@ use crate::{
     utils::{
         dependencies::dependencies,
         fs::{TempDir, download_browser},
+        metrics::observe_fetch,
+        namespace::object_key,
         sync::TabPool,
     },
 };
 use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose};
-use chromiumoxide::{Browser, BrowserConfig};
-use chromiumoxide::{browser::HeadlessMode, cdp::browser_protocol::network};
+use chromiumoxide::{Browser, BrowserConfig, error::CdpError};
+use chromiumoxide::{
+    browser::HeadlessMode,
+    cdp::browser_protocol::{browser::CloseParams, network},
+};
 use chrono::{DateTime, Utc};
 use fastpool::bounded::{Object, Pool, PoolConfig};
 use futures::StreamExt;
 use tokio::{
     spawn,
+    sync::Mutex,
     task::JoinHandle,
     time::{Instant, sleep_until},
 };
-use uuid::Uuid;
+use tracing::debug;
 
 static PREFIXES: &[&str] = &["http://", "https://", "ftp://"];
 
 pub struct HeadlessBrowserFetcher<'a> {
-    _handle: JoinHandle<()>,
+    browser: Arc<Browser>,
+    // Drives the browser connection until the browser goes away
+    handler: Mutex<Option<JoinHandle<()>>>,
     pool: Arc<Pool<TabPool<'a>>>,
     config: &'a HeadlessBrowserConfig,
     object_store: Arc<dyn ObjectStore>,
-    data_directory: TempDir,
+    // Profile the browser writes to, removed once the browser has exited
+    data_directory: Mutex<Option<TempDir>>,
 }
 
 impl<'a> HeadlessBrowserFetcher<'a> {
@@ -78,7 +89,7 @@ impl<'a> HeadlessBrowserFetcher<'a> {
         let (browser, mut handler) = Browser::launch(browser_config.build()?).await?;
         let browser = Arc::new(browser);
 
-        let _handle = spawn(async move {
+        let handler = spawn(async move {
             while let Some(h) = handler.next().await {
                 if h.is_err() {
                     break;
@@ -97,17 +108,19 @@ impl<'a> HeadlessBrowserFetcher<'a> {
             .get_object_store(&config.object_store)?;
 
         Ok(Self {
-            _handle,
+            browser,
+            handler: Mutex::new(Some(handler)),
             pool,
             config,
             object_store,
-            data_directory,
+            data_directory: Mutex::new(Some(data_directory)),
         })
     }
 
     pub async fn fetch_http_response(
         page: Object<TabPool<'a>>,
         url: String,
+        task_id: &str,
         object_store: Arc<dyn ObjectStore>,
         idle_timeout: Duration,
         request_timestamp: DateTime<Utc>,
@@ -124,6 +137,7 @@ impl<'a> HeadlessBrowserFetcher<'a> {
             .await?;
         let mut fails = page.event_listener::<network::EventLoadingFailed>().await?;
 
+        let started = Instant::now();
         let mut nav = Box::pin(page.goto(url));
         let mut request_headers: Option<network::Headers> = None;
         let mut response_headers: Option<network::Headers> = None;
@@ -140,13 +154,30 @@ impl<'a> HeadlessBrowserFetcher<'a> {
         let mut nav_done = false;
 
         let mut response_timestamp: Option<DateTime<Utc>> = None;
+        let mut timing = FetchTiming::default();
 
         loop {
             tokio::select! {
-                _ = &mut nav, if !nav_done => {
+                result = &mut nav, if !nav_done => {
                     last_event = Instant::now();
                     nav_done = true;
 
+                    // Chrome reports a page that failed to load with its net error code,
+                    // anything else going wrong with the browser is returned
+                    match result {
+                        Err(CdpError::ChromeMessage(text)) if text.starts_with("net::") => {
+                            let mut response = HttpResponse::failed(
+                                FetchError::from_net_error(&text),
+                                request_timestamp,
+                            );
+
+                            response.timing = timing;
+                            return Ok(response);
+                        }
+                        Err(e) => return Err(e.into()),
+                        Ok(_) => {}
+                    }
+
                     if saw_request && saw_response && saw_body {
                         break;
                     }
@@ -166,29 +197,31 @@ impl<'a> HeadlessBrowserFetcher<'a> {
                     saw_response = true;
                     let url = e.response.url.clone();
 
+                    // Chrome shows an error page in place of one that failed to load, and
+                    // the navigation says why
                     if !PREFIXES.iter().any(|p| url.starts_with(p)) {
-                        return Ok(HttpResponse {
-                            status: None,
-                            timestamp: response_timestamp,
-                            request: HttpRequest {
-                                method: "GET".to_string(),
-                                request_headers: HashMap::new(),
-                                timestamp: request_timestamp,
+                        let error = match nav_done {
+                            false => match (&mut nav).await {
+                                Err(CdpError::ChromeMessage(text)) if text.starts_with("net::") => {
+                                    FetchError::from_net_error(&text)
+                                }
+                                _ => FetchError::new(FetchErrorKind::Other, "Request failed"),
                             },
-                            response_headers: HashMap::new(),
-                            key: None,
-                            error: Some("Request failed".to_string()),
-                            minhash: None,
-                        });
+                            true => FetchError::new(FetchErrorKind::Other, "Request failed"),
+                        };
+
+                        return Ok(HttpResponse::failed(error, request_timestamp));
                     }
 
                     last_event = Instant::now();
                     status = Some(e.response.status);
                     response_headers = Some(e.response.headers.clone());
                     response_request_id = Some(e.request_id.clone());
+                    timing.remote_ip = e.response.remote_ip_address.clone();
 
                     if response_timestamp.is_none() {
                         response_timestamp = Some(Utc::now());
+                        timing.time_to_headers_ms = Some(started.elapsed().as_millis() as u64);
                     }
 
                     if saw_request && saw_body {
@@ -200,6 +233,9 @@ impl<'a> HeadlessBrowserFetcher<'a> {
                     last_event = Instant::now();
 
                     if response_request_id.as_ref() == Some(&e.request_id) {
+                        timing.time_to_last_byte_ms = Some(started.elapsed().as_millis() as u64);
+                        timing.bytes = Some(e.encoded_data_length as u64);
+
                         let request_id = e.request_id.clone();
                         let resp = page
                             .execute(network::GetResponseBodyParams { request_id })
@@ -223,19 +259,14 @@ impl<'a> HeadlessBrowserFetcher<'a> {
                     last_event = Instant::now();
 
                     if response_request_id.as_ref() == Some(&e.request_id) {
-                        return Ok(HttpResponse {
-                            status: None,
-                            timestamp: response_timestamp,
-                            request: HttpRequest {
-                                method: "GET".to_string(),
-                                request_headers: HashMap::new(),
-                                timestamp: request_timestamp,
-                            },
-                            response_headers: HashMap::new(),
-                            key: None,
-                            error: Some(e.error_text.clone()),
-                            minhash: None,
-                        });
+                        let mut response = HttpResponse::failed(
+                            FetchError::from_net_error(&e.error_text),
+                            request_timestamp,
+                        );
+
+                        response.timestamp = response_timestamp;
+                        response.timing = timing;
+                        return Ok(response);
                     }
                 }
 
@@ -251,7 +282,7 @@ impl<'a> HeadlessBrowserFetcher<'a> {
         let mut minhash: Option<Vec<u64>> = None;
 
         if let Some(body) = body {
-            let storage_key = Uuid::new_v4().to_string();
+            let storage_key = object_key(task_id);
             let resp = object_store.put(&storage_key, &body).await?;
 
             minhash = Some(resp.minhash);
@@ -270,6 +301,11 @@ impl<'a> HeadlessBrowserFetcher<'a> {
             key,
             error: None,
             minhash: minhash,
+            unchanged: false,
+            redirects: vec![],
+            final_uri: None,
+            truncated: false,
+            timing,
         })
     }
 }
@@ -301,39 +337,68 @@ impl<'a> Task for HeadlessBrowserFetcher<'a> {
     async fn on_message(&self, message: Record) -> Result<Record, AppError> {
         let request_timestamp = Utc::now();
         let tab = self.pool.get().await?;
-        let response = match Self::fetch_http_response(
+        let started = Instant::now();
+        let result = Self::fetch_http_response(
             tab,
             message.uri.clone(),
+            &message.task_id,
             self.object_store.clone(),
             Duration::from_secs(self.config.timeout as u64),
             request_timestamp,
         )
-        .await
-        {
+        .await;
+        let elapsed = started.elapsed();
+
+        observe_fetch(
+            "headless_browser",
+            elapsed,
+            result.as_ref().ok().and_then(|r| r.status),
+        );
+
+        match &result {
+            Ok(r) => debug!(
+                status = r.status,
+                key = r.key.as_deref(),
+                elapsed_ms = elapsed.as_millis() as u64,
+                "fetched"
+            ),
+            Err(e) => debug!(error = %e, elapsed_ms = elapsed.as_millis() as u64, "fetch failed"),
+        }
+
+        let response = match result {
             Ok(resp) => resp,
-            Err(e) => HttpResponse {
-                status: None,
-                request: HttpRequest {
-                    method: "GET".to_string(),
-                    request_headers: HashMap::new(),
-                    timestamp: request_timestamp,
-                },
-                response_headers: HashMap::new(),
-                key: None,
-                error: Some(e.to_string()),
-                timestamp: None,
-                minhash: None,
-            },
+            // Failed loads are recorded by `fetch_http_response`, so what's left is trouble
+            // with the browser or the object store, which the pipeline retries if transient
+            Err(e) if e.is_retryable() => return Err(e),
+            Err(e) => HttpResponse::failed(e.fetch_error(), request_timestamp),
         };
         let mut metadata = message.metadata;
-        metadata.push(RecordMetadata::HttpResponse(response));
+        metadata.push(RecordMetadata::HttpResponse(Box::new(response)));
 
         Ok(Record {
-            uri: message.uri,
-            task_id: message.task_id,
             metadata: metadata,
+            ..message
         })
     }
+
+    fn recorded_failure(&self, record: &Record) -> Option<AppError> {
+        record.fetch_error().cloned().map(AppError::FetchFailed)
+    }
+
+    async fn close(&self) -> Result<(), AppError> {
+        let Some(handler) = self.handler.lock().await.take() else {
+            return Ok(());
+        };
+
+        self.browser.execute(CloseParams::default()).await?;
+        handler.await?;
+
+        if let Some(dir) = self.data_directory.lock().await.take() {
+            dir.close()?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -341,8 +406,12 @@ mod tests {
     use std::env::temp_dir;
 
     use httpmock::{Method::GET, MockServer};
+    use uuid::Uuid;
 
-    use crate::{services::object_store::fs::FileSystemObjectStore, utils::web::get_user_agent};
+    use crate::{
+        services::object_store::fs::FileSystemObjectStore, types::structs::record::HopType,
+        utils::web::get_user_agent,
+    };
 
     use super::*;
 
@@ -381,6 +450,13 @@ mod tests {
             uri: format!("{}/test", server.base_url()),
             task_id: task_id,
             metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
         };
 
         let response = fetcher.on_message(record).await.unwrap();
@@ -448,6 +524,13 @@ mod tests {
             uri: server.base_url(),
             task_id: task_id,
             metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
         };
 
         let response = fetcher.on_message(record).await.unwrap();
@@ -505,6 +588,13 @@ mod tests {
             uri: "http://127.0.0.1:9".to_string(),
             task_id: task_id,
             metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
         };
 
         let response = fetcher.on_message(record).await.unwrap();
@@ -514,6 +604,10 @@ mod tests {
             _ => panic!("headless browser did not create a response object"),
         };
 
-        assert_eq!(http_response.error, Some("Request failed".to_string()))
+        let error = http_response.error.clone().unwrap();
+
+        assert_eq!(error.kind, FetchErrorKind::ConnectionRefused);
+        assert_eq!(error.message, "net::ERR_CONNECTION_REFUSED");
+        assert!(fetcher.recorded_failure(&response).unwrap().is_retryable());
     }
 }
@@ -1,34 +1,61 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    future::ready,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
 use crate::{
     types::{
-        configs::tasks::http_fetcher_config::HttpFetcherConfig,
+        configs::tasks::{content_config::OversizeAction, http_fetcher_config::HttpFetcherConfig},
         error::AppError,
         structs::{
-            metadata::http_response::{HttpRequest, HttpResponse},
+            metadata::http_response::{
+                FetchError, FetchErrorKind, FetchTiming, HttpRequest, HttpResponse, RedirectHop,
+                Validators,
+            },
             record::{Record, RecordMetadata},
         },
         traits::{object_store::ObjectStore, task::Task},
     },
-    utils::{dependencies::dependencies, web::get_user_agent},
+    utils::{
+        dependencies::dependencies,
+        dns::SystemResolver,
+        metrics::observe_fetch,
+        namespace::object_key,
+        politeness::{HostLease, Politeness},
+        web::{extract_site, get_user_agent, parse_retry_after},
+    },
 };
 use async_trait::async_trait;
+use bytes::Bytes;
 use chrono::{DateTime, Utc};
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use futures_util::TryStreamExt;
-use reqwest::{Client, Proxy};
-use uuid::Uuid;
+use reqwest::{
+    Client, Proxy, Response, StatusCode,
+    header::{CONTENT_TYPE, IF_MODIFIED_SINCE, IF_NONE_MATCH, LOCATION, RETRY_AFTER},
+    redirect::Policy,
+};
+use tracing::debug;
+use url::Url;
 pub struct HttpFetcher<'a> {
     config: &'a HttpFetcherConfig,
     client: Client,
     object_store: Arc<dyn ObjectStore>,
+    politeness: Option<Politeness>,
 }
 
 impl<'a> HttpFetcher<'a> {
     pub async fn new(config: &'a HttpFetcherConfig) -> Result<Self, AppError> {
         let mut builder = Client::builder()
             .timeout(Duration::from_secs(config.timeout as u64))
-            .user_agent(get_user_agent(config.user_agent.clone()));
+            .user_agent(get_user_agent(config.user_agent.clone()))
+            .redirect(Policy::none())
+            .dns_resolver(Arc::new(SystemResolver));
 
         if let Some(proxy_server) = &config.proxy_server {
             builder = builder.proxy(Proxy::all(proxy_server)?);
@@ -40,92 +67,427 @@ impl<'a> HttpFetcher<'a> {
             .lock()
             .await
             .get_object_store(&config.object_store)?;
+        let politeness = match &config.politeness {
+            Some(c) => Some(
+                Politeness::new(c, client.clone(), get_user_agent(config.user_agent.clone()))
+                    .await?,
+            ),
+            None => None,
+        };
 
         Ok(Self {
             config,
             client,
             object_store,
+            politeness,
         })
     }
 
     pub async fn fetch_http_response(
         &self,
         uri: &str,
+        task_id: &str,
+        validators: Option<&Validators>,
         request_timestamp: DateTime<Utc>,
     ) -> Result<HttpResponse, AppError> {
-        let req = self.client.get(uri).build()?;
-        let (req_headers, method) = {
-            let headers: HashMap<String, String> = req
-                .headers()
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-                .collect();
+        let mut lease = None;
+        let result = self
+            .fetch_leased(uri, task_id, validators, request_timestamp, &mut lease)
+            .await;
 
-            let method: String = req.method().as_str().to_string();
+        if let (Some(politeness), Some(lease)) = (&self.politeness, lease) {
+            match &result {
+                Err(e @ AppError::Throttled { .. }) => {
+                    politeness.back_off(lease, e.retry_after()).await?
+                }
+                // Without a `Retry-After` the 503 is the response, but the host is still
+                // struggling
+                Ok(r) if r.status == Some(503) => politeness.back_off(lease, None).await?,
+                _ => politeness.release(lease).await?,
+            }
+        }
+
+        result
+    }
 
-            (headers, method)
+    // Holds in `lease` the host of the hop being fetched, so it's still there to give back
+    // whichever way the fetch ends
+    async fn fetch_leased(
+        &self,
+        uri: &str,
+        task_id: &str,
+        validators: Option<&Validators>,
+        request_timestamp: DateTime<Utc>,
+        lease: &mut Option<HostLease>,
+    ) -> Result<HttpResponse, AppError> {
+        let mut current = Url::parse(uri)?;
+        let mut redirects = vec![];
+        let mut blocked = None;
+        let mut request = None;
+        let mut head_bytes = 0;
+        let started = Instant::now();
+
+        // Redirects are followed here rather than by reqwest so every hop is recorded
+        let resp = loop {
+            // A redirect can lead to another host, which has to be waited for all the same
+            if let Some(politeness) = &self.politeness {
+                if let Some(previous) = lease.take() {
+                    politeness.release(previous).await?;
+                }
+
+                *lease = Some(politeness.acquire(task_id, current.as_str()).await?);
+            }
+
+            let mut builder = self.client.get(current.as_str());
+
+            // Validators belong to the url they were stored for, not to where it redirects
+            if let Some(validators) = validators.filter(|_| redirects.is_empty()) {
+                if let Some(etag) = &validators.etag {
+                    builder = builder.header(IF_NONE_MATCH, etag);
+                }
+
+                if let Some(last_modified) = &validators.last_modified {
+                    builder = builder.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+
+            let req = builder.build()?;
+
+            // The request reported is the one made for the record's own url
+            request.get_or_insert_with(|| HttpRequest {
+                method: req.method().as_str().to_string(),
+                request_headers: req
+                    .headers()
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+                    .collect(),
+                timestamp: request_timestamp,
+            });
+
+            let resp = self.client.execute(req).await?;
+
+            head_bytes += response_head_len(&resp);
+
+            let Some(target) = redirect_target(&current, &resp) else {
+                break resp;
+            };
+
+            redirects.push(RedirectHop {
+                uri: current.to_string(),
+                status: resp.status().as_u16() as i64,
+                location: target.to_string(),
+            });
+
+            if let Err(reason) = self.may_follow(&current, &target, redirects.len()) {
+                debug!(from = %current, to = %target, reason = %reason.message, "not following redirect");
+                blocked = Some(reason);
+                break resp;
+            }
+
+            debug!(from = %current, to = %target, "following redirect");
+            current = target;
         };
-        let resp = self.client.execute(req).await?;
+
+        let request = request.expect("at least one request is made");
         let response_timestamp = Utc::now();
+        let mut timing = FetchTiming {
+            time_to_headers_ms: Some(started.elapsed().as_millis() as u64),
+            bytes: Some(head_bytes),
+            remote_ip: resp.remote_addr().map(|addr| addr.ip().to_string()),
+            ..Default::default()
+        };
         let status = resp.status().as_u16();
+        let retry_after = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| parse_retry_after(v, response_timestamp));
+
+        // Left for the pipeline to retry once the server is ready again
+        if status == 429 || (status == 503 && retry_after.is_some()) {
+            return Err(AppError::Throttled {
+                status,
+                uri: current.to_string(),
+                retry_after,
+            });
+        }
         let response_headers: HashMap<String, String> = resp
             .headers()
             .iter()
             .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
             .collect();
 
-        let stream = resp.bytes_stream().map_err(AppError::from).boxed();
-        let key = Uuid::new_v4().to_string();
-        let put_resp = self.object_store.put_stream(&key, stream).await?;
+        // The body didn't change, so point at the one stored last time
+        if let Some(validators) = validators
+            && redirects.is_empty()
+            && resp.status() == StatusCode::NOT_MODIFIED
+        {
+            timing.time_to_last_byte_ms = Some(started.elapsed().as_millis() as u64);
 
-        Ok(HttpResponse {
-            request: HttpRequest {
-                method: method.to_string(),
-                request_headers: req_headers,
-                timestamp: request_timestamp,
-            },
+            return Ok(HttpResponse {
+                request,
+                response_headers,
+                status: Some(status as i64),
+                key: validators.key.clone(),
+                error: None,
+                timestamp: Some(response_timestamp),
+                minhash: validators.minhash.clone(),
+                unchanged: true,
+                redirects,
+                final_uri: Some(current.to_string()),
+                truncated: false,
+                timing,
+            });
+        }
+
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let mut response = HttpResponse {
+            request,
             response_headers,
             status: Some(status as i64),
-            key: Some(key),
-            error: None,
+            key: None,
+            error: blocked,
             timestamp: Some(response_timestamp),
-            minhash: Some(put_resp.minhash),
-        })
+            minhash: None,
+            unchanged: false,
+            redirects,
+            final_uri: Some(current.to_string()),
+            truncated: false,
+            timing,
+        };
+
+        // Checked before any of the body is read, so unwanted types cost nothing to skip
+        if !self.allows_type(content_type.as_deref()) {
+            response.error = Some(FetchError::new(
+                FetchErrorKind::BlockedByPolicy,
+                format!(
+                    "content type {} is not allowed",
+                    content_type.as_deref().unwrap_or("(none)")
+                ),
+            ));
+            response.timing.time_to_last_byte_ms = Some(started.elapsed().as_millis() as u64);
+            return Ok(response);
+        }
+
+        let content = &self.config.content;
+        let abort = content.oversize == OversizeAction::Abort;
+
+        if let Some(limit) = content.max_body_bytes
+            && abort
+            && resp.content_length().is_some_and(|len| len > limit)
+        {
+            response.error = Some(oversize_error(limit));
+            response.timing.time_to_last_byte_ms = Some(started.elapsed().as_millis() as u64);
+            return Ok(response);
+        }
+
+        let truncated = Arc::new(AtomicBool::new(false));
+        let received = Arc::new(AtomicU64::new(0));
+        let counter = received.clone();
+        let stream = resp
+            .bytes_stream()
+            .inspect_ok(move |chunk| {
+                counter.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            })
+            .map_err(AppError::from);
+        let stream = match content.max_body_bytes {
+            Some(limit) => limit_body(stream, limit, truncated.clone()).boxed(),
+            None => stream.boxed(),
+        };
+        let key = object_key(task_id);
+        let put_resp = self.object_store.put_stream(&key, stream).await?;
+
+        response.timing.time_to_last_byte_ms = Some(started.elapsed().as_millis() as u64);
+        response.timing.bytes = Some(head_bytes + received.load(Ordering::Relaxed));
+
+        // Servers don't always send a length, so an oversized body may only show itself
+        // once it has been partly stored
+        if let Some(limit) = content.max_body_bytes
+            && abort
+            && truncated.load(Ordering::Relaxed)
+        {
+            self.object_store.delete(&key).await?;
+            response.error = Some(oversize_error(limit));
+            return Ok(response);
+        }
+
+        response.key = Some(key);
+        response.minhash = Some(put_resp.minhash);
+        response.truncated = truncated.load(Ordering::Relaxed);
+
+        Ok(response)
+    }
+
+    fn allows_type(&self, content_type: Option<&str>) -> bool {
+        let content = &self.config.content;
+        let Some(media_type) = content_type
+            .and_then(|t| t.split(';').next())
+            .map(|t| t.trim().to_ascii_lowercase())
+        else {
+            return content.allow_types.is_empty();
+        };
+        let matches = |pattern: &String| {
+            let pattern = pattern.to_ascii_lowercase();
+
+            match pattern.strip_suffix("/*") {
+                Some("*") => true,
+                Some(kind) => media_type.split('/').next() == Some(kind),
+                None => pattern == media_type,
+            }
+        };
+
+        (content.allow_types.is_empty() || content.allow_types.iter().any(matches))
+            && !content.deny_types.iter().any(matches)
+    }
+
+    fn may_follow(&self, from: &Url, to: &Url, hops: usize) -> Result<(), FetchError> {
+        let policy = &self.config.redirects;
+
+        if hops > policy.max_hops {
+            return Err(FetchError::new(
+                FetchErrorKind::TooManyRedirects,
+                format!("too many redirects, more than {}", policy.max_hops),
+            ));
+        }
+
+        if policy.same_site
+            && extract_site(from).ok().as_deref() != extract_site(to).ok().as_deref()
+        {
+            return Err(FetchError::new(
+                FetchErrorKind::BlockedByPolicy,
+                "redirect leaves the site",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn oversize_error(limit: u64) -> FetchError {
+    FetchError::new(
+        FetchErrorKind::BodyTooLarge,
+        format!("body larger than {} bytes", limit),
+    )
+}
+
+// Ends the body after `limit` bytes, flagging `truncated` if there was more
+fn limit_body(
+    stream: impl Stream<Item = Result<Bytes, AppError>>,
+    limit: u64,
+    truncated: Arc<AtomicBool>,
+) -> impl Stream<Item = Result<Bytes, AppError>> {
+    stream.scan(limit, move |remaining, chunk| {
+        let chunk = match chunk {
+            Ok(chunk) if *remaining == 0 && !chunk.is_empty() => {
+                truncated.store(true, Ordering::Relaxed);
+                return ready(None);
+            }
+            Ok(chunk) if chunk.len() as u64 > *remaining => {
+                truncated.store(true, Ordering::Relaxed);
+                let head = chunk.slice(..*remaining as usize);
+                *remaining = 0;
+                Ok(head)
+            }
+            Ok(chunk) => {
+                *remaining -= chunk.len() as u64;
+                Ok(chunk)
+            }
+            Err(e) => Err(e),
+        };
+
+        ready(Some(chunk))
+    })
+}
+
+// Size of the status line and headers of a response as HTTP/1.1 sends them. Bodies
+// arrive as sent, reqwest isn't built to decompress them.
+fn response_head_len(resp: &Response) -> u64 {
+    let status_line = format!("{:?} {}\r\n", resp.version(), resp.status());
+    let headers: usize = resp
+        .headers()
+        .iter()
+        .map(|(k, v)| k.as_str().len() + v.len() + 4)
+        .sum();
+
+    (status_line.len() + headers + 2) as u64
+}
+
+// Where a redirect response points, if it is one with a usable `Location`
+fn redirect_target(current: &Url, resp: &Response) -> Option<Url> {
+    if !resp.status().is_redirection() {
+        return None;
     }
+
+    let location = resp.headers().get(LOCATION)?.to_str().ok()?;
+    let target = current.join(location).ok()?;
+
+    matches!(target.scheme(), "http" | "https").then_some(target)
 }
 
 #[async_trait]
 impl<'a> Task for HttpFetcher<'a> {
     async fn on_message(&self, message: Record) -> Result<Record, AppError> {
         let request_timestamp = Utc::now();
-        let response = match self
-            .fetch_http_response(&message.uri, request_timestamp)
-            .await
-        {
+        let started = Instant::now();
+        // Only a caller that passes the record's previous response in its metadata gets a
+        // conditional request; earlier fetches aren't looked up anywhere
+        let validators = match self.config.revalidate {
+            true => message.metadata.iter().rev().find_map(|m| match m {
+                RecordMetadata::HttpResponse(r) => r.validators(),
+                _ => None,
+            }),
+            false => None,
+        };
+        let result = self
+            .fetch_http_response(
+                &message.uri,
+                &message.task_id,
+                validators.as_ref(),
+                request_timestamp,
+            )
+            .await;
+        let elapsed = started.elapsed();
+
+        observe_fetch("http", elapsed, result.as_ref().ok().and_then(|r| r.status));
+
+        match &result {
+            Ok(r) => debug!(
+                status = r.status,
+                key = r.key.as_deref(),
+                unchanged = r.unchanged,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "fetched"
+            ),
+            Err(e) => debug!(error = %e, elapsed_ms = elapsed.as_millis() as u64, "fetch failed"),
+        }
+
+        let response = match result {
             Ok(r) => r,
-            Err(e) => HttpResponse {
-                request: HttpRequest {
-                    method: "GET".to_string(),
-                    request_headers: HashMap::new(),
-                    timestamp: request_timestamp,
-                },
-                status: None,
-                response_headers: HashMap::new(),
-                key: None,
-                error: Some(e.to_string()),
-                timestamp: None,
-                minhash: None,
-            },
+            // Network failures are recorded, and retried by the pipeline if transient
+            Err(e @ (AppError::ReqwestError(_) | AppError::UrlParseError(_))) => {
+                HttpResponse::failed(e.fetch_error(), request_timestamp)
+            }
+            // Anything else transient, like a throttled host or a failing object store,
+            // goes back to the pipeline to be retried
+            Err(e) if e.is_retryable() => return Err(e),
+            Err(e) => HttpResponse::failed(e.fetch_error(), request_timestamp),
         };
         let mut metadata = message.metadata;
-        metadata.push(RecordMetadata::HttpResponse(response));
+        metadata.push(RecordMetadata::HttpResponse(Box::new(response)));
 
         Ok(Record {
-            uri: message.uri,
-            task_id: message.task_id,
             metadata: metadata,
+            ..message
         })
     }
+
+    fn recorded_failure(&self, record: &Record) -> Option<AppError> {
+        record.fetch_error().cloned().map(AppError::FetchFailed)
+    }
 }
 
 #[cfg(test)]
@@ -133,8 +495,20 @@ mod tests {
     use std::env::temp_dir;
 
     use httpmock::{Method::GET, MockServer};
+    use uuid::Uuid;
 
-    use crate::{services::object_store::fs::FileSystemObjectStore, utils::web::get_user_agent};
+    use crate::{
+        services::object_store::fs::FileSystemObjectStore,
+        types::{
+            configs::tasks::{
+                content_config::{ContentConfig, OversizeAction},
+                politeness_config::PolitenessConfig,
+                redirect_config::RedirectConfig,
+            },
+            structs::record::HopType,
+        },
+        utils::web::get_user_agent,
+    };
 
     use super::*;
 
@@ -157,6 +531,10 @@ mod tests {
             proxy_server: None,
             object_store: store_name.to_string(),
             timeout: 30,
+            revalidate: true,
+            redirects: RedirectConfig::default(),
+            content: ContentConfig::default(),
+            politeness: None,
         };
 
         let fetcher = HttpFetcher::new(&config).await.unwrap();
@@ -172,6 +550,13 @@ mod tests {
             uri: format!("{}/test", server.base_url()),
             task_id: task_id,
             metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
         };
 
         let response = fetcher.on_message(record).await.unwrap();
@@ -189,8 +574,18 @@ mod tests {
         assert!(http_response.response_headers.len() > 1);
         assert!(http_response.request.request_headers.len() == 0);
 
+        let timing = &http_response.timing;
+
+        assert!(timing.time_to_headers_ms.is_some());
+        assert!(timing.time_to_headers_ms <= timing.time_to_last_byte_ms);
+        // The status line and headers were read too
+        assert!(timing.bytes > Some(test_response.len() as u64));
+        assert_eq!(timing.remote_ip.as_deref(), Some("127.0.0.1"));
+
         let key = http_response.key.clone().unwrap();
 
+        assert!(key.starts_with(&format!("{}/", response.task_id)));
+
         let object_store = dependencies()
             .lock()
             .await
@@ -222,6 +617,10 @@ mod tests {
             proxy_server: None,
             object_store: store_name.to_string(),
             timeout: 30,
+            revalidate: true,
+            redirects: RedirectConfig::default(),
+            content: ContentConfig::default(),
+            politeness: None,
         };
 
         let fetcher = HttpFetcher::new(&config).await.unwrap();
@@ -238,6 +637,13 @@ mod tests {
             uri: server.base_url(),
             task_id: task_id,
             metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
         };
 
         let response = fetcher.on_message(record).await.unwrap();
@@ -269,6 +675,505 @@ mod tests {
         assert_eq!(response_string, test_response)
     }
 
+    #[tokio::test]
+    async fn test_revalidation() {
+        let path = temp_dir().join(Uuid::new_v4().to_string());
+        let store = FileSystemObjectStore::new(path.clone()).await.unwrap();
+        let store_name = "test-revalidation-object-store";
+        let task_id = Uuid::new_v4().to_string();
+
+        dependencies()
+            .lock()
+            .await
+            .set_object_store(store_name, Arc::new(store))
+            .unwrap();
+
+        let config = HttpFetcherConfig {
+            user_agent: None,
+            proxy_server: None,
+            object_store: store_name.to_string(),
+            timeout: 30,
+            revalidate: true,
+            redirects: RedirectConfig::default(),
+            content: ContentConfig::default(),
+            politeness: None,
+        };
+
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let server = MockServer::start();
+        let conditional = server.mock(|when, then| {
+            when.method(GET)
+                .path("/page")
+                .header("if-none-match", "\"v1\"")
+                .header("if-modified-since", "Wed, 01 Jan 2025 00:00:00 GMT");
+            then.status(304).header("etag", "\"v1\"");
+        });
+        let fresh = server.mock(|when, then| {
+            when.method(GET)
+                .path("/page")
+                .header_missing("if-none-match");
+            then.status(200)
+                .header("etag", "\"v1\"")
+                .header("last-modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+                .body("unchanging");
+        });
+
+        let record = Record {
+            uri: server.url("/page"),
+            task_id: task_id.clone(),
+            metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        };
+
+        let first = fetcher.on_message(record).await.unwrap();
+        let second = fetcher.on_message(first).await.unwrap();
+
+        fresh.assert();
+        conditional.assert();
+
+        let [
+            RecordMetadata::HttpResponse(first),
+            RecordMetadata::HttpResponse(second),
+        ] = second.metadata.as_slice()
+        else {
+            panic!("fetcher did not create two response objects");
+        };
+
+        assert!(!first.unchanged);
+        assert!(second.unchanged);
+        assert_eq!(second.status, Some(304));
+        assert_eq!(second.key, first.key);
+        assert_eq!(second.minhash, first.minhash);
+        assert!(second.timing.time_to_last_byte_ms.is_some());
+
+        // Only the first fetch stored a body
+        let stored = std::fs::read_dir(path.join(&task_id)).unwrap().count();
+
+        assert_eq!(stored, 1);
+
+        let config = HttpFetcherConfig {
+            revalidate: false,
+            ..config
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let response = fetcher
+            .fetch_http_response(&server.url("/page"), &task_id, None, Utc::now())
+            .await
+            .unwrap();
+
+        assert!(!response.unchanged);
+        assert_eq!(response.status, Some(200));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_chain() {
+        let path = temp_dir().join(Uuid::new_v4().to_string());
+        let store = FileSystemObjectStore::new(path).await.unwrap();
+        let store_name = "test-redirect-object-store";
+        let task_id = Uuid::new_v4().to_string();
+
+        dependencies()
+            .lock()
+            .await
+            .set_object_store(store_name, Arc::new(store))
+            .unwrap();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/a");
+            then.status(301).header("location", "/dir/b");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/dir/b");
+            then.status(302).header("location", "c");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/dir/c");
+            then.status(200).body("landed");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/away");
+            then.status(302).header(
+                "location",
+                format!("http://localhost:{}/dir/c", server.port()),
+            );
+        });
+
+        let config = HttpFetcherConfig {
+            user_agent: None,
+            proxy_server: None,
+            object_store: store_name.to_string(),
+            timeout: 30,
+            revalidate: true,
+            redirects: RedirectConfig::default(),
+            content: ContentConfig::default(),
+            politeness: None,
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let response = fetcher
+            .fetch_http_response(&server.url("/a"), &task_id, None, Utc::now())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, Some(200));
+        assert_eq!(response.error, None);
+        assert_eq!(response.final_uri, Some(server.url("/dir/c")));
+        assert_eq!(
+            response.redirects,
+            vec![
+                RedirectHop {
+                    uri: server.url("/a"),
+                    status: 301,
+                    location: server.url("/dir/b"),
+                },
+                RedirectHop {
+                    uri: server.url("/dir/b"),
+                    status: 302,
+                    location: server.url("/dir/c"),
+                },
+            ]
+        );
+
+        // Past the hop limit the last redirect is the response
+        let config = HttpFetcherConfig {
+            redirects: RedirectConfig {
+                max_hops: 1,
+                same_site: false,
+            },
+            ..config
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let response = fetcher
+            .fetch_http_response(&server.url("/a"), &task_id, None, Utc::now())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, Some(302));
+        assert_eq!(response.redirects.len(), 2);
+        assert_eq!(response.final_uri, Some(server.url("/dir/b")));
+        assert_eq!(
+            response.unfollowed_redirect(),
+            Some(server.url("/dir/c").as_str())
+        );
+        assert_eq!(
+            response.error.unwrap().kind,
+            FetchErrorKind::TooManyRedirects
+        );
+
+        let config = HttpFetcherConfig {
+            redirects: RedirectConfig {
+                max_hops: 10,
+                same_site: true,
+            },
+            ..config
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let response = fetcher
+            .fetch_http_response(&server.url("/away"), &task_id, None, Utc::now())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, Some(302));
+        assert_eq!(response.final_uri, Some(server.url("/away")));
+        assert_eq!(
+            response.error.unwrap().kind,
+            FetchErrorKind::BlockedByPolicy
+        );
+    }
+
+    #[tokio::test]
+    async fn test_content_limits() {
+        let path = temp_dir().join(Uuid::new_v4().to_string());
+        let store = FileSystemObjectStore::new(path).await.unwrap();
+        let store_name = "test-content-object-store";
+        let task_id = Uuid::new_v4().to_string();
+
+        dependencies()
+            .lock()
+            .await
+            .set_object_store(store_name, Arc::new(store))
+            .unwrap();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/page");
+            then.status(200)
+                .header("content-type", "text/html; charset=utf-8")
+                .body("0123456789abcdef");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/disk.iso");
+            then.status(200)
+                .header("content-type", "application/x-iso9660-image")
+                .body("iso");
+        });
+
+        let config = HttpFetcherConfig {
+            user_agent: None,
+            proxy_server: None,
+            object_store: store_name.to_string(),
+            timeout: 30,
+            revalidate: true,
+            redirects: RedirectConfig::default(),
+            content: ContentConfig {
+                max_body_bytes: Some(10),
+                ..ContentConfig::default()
+            },
+            politeness: None,
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let response = fetcher
+            .fetch_http_response(&server.url("/page"), &task_id, None, Utc::now())
+            .await
+            .unwrap();
+        let stored = fetcher
+            .object_store
+            .get(&response.key.unwrap())
+            .await
+            .unwrap();
+
+        assert!(response.truncated);
+        assert_eq!(stored, b"0123456789");
+
+        let config = HttpFetcherConfig {
+            content: ContentConfig {
+                max_body_bytes: Some(10),
+                oversize: OversizeAction::Abort,
+                ..ContentConfig::default()
+            },
+            ..config
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let response = fetcher
+            .fetch_http_response(&server.url("/page"), &task_id, None, Utc::now())
+            .await
+            .unwrap();
+
+        assert_eq!(response.key, None);
+        assert!(!response.truncated);
+        assert_eq!(
+            response.error,
+            Some(FetchError::new(
+                FetchErrorKind::BodyTooLarge,
+                "body larger than 10 bytes"
+            ))
+        );
+
+        let config = HttpFetcherConfig {
+            content: ContentConfig {
+                allow_types: vec!["text/*".to_string(), "application/*".to_string()],
+                deny_types: vec!["application/x-iso9660-image".to_string()],
+                ..ContentConfig::default()
+            },
+            ..config
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let page = fetcher
+            .fetch_http_response(&server.url("/page"), &task_id, None, Utc::now())
+            .await
+            .unwrap();
+        let iso = fetcher
+            .fetch_http_response(&server.url("/disk.iso"), &task_id, None, Utc::now())
+            .await
+            .unwrap();
+
+        assert!(page.key.is_some());
+        assert!(!page.truncated);
+        assert_eq!(iso.key, None);
+        assert_eq!(
+            iso.error,
+            Some(FetchError::new(
+                FetchErrorKind::BlockedByPolicy,
+                "content type application/x-iso9660-image is not allowed"
+            ))
+        );
+        assert!(iso.timing.time_to_last_byte_ms.is_some());
+        assert!(iso.timing.bytes > Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_politeness() {
+        let path = temp_dir().join(Uuid::new_v4().to_string());
+        let store = FileSystemObjectStore::new(path).await.unwrap();
+        let store_name = "test-politeness-object-store";
+        let task_id = Uuid::new_v4().to_string();
+
+        dependencies()
+            .lock()
+            .await
+            .set_object_store(store_name, Arc::new(store))
+            .unwrap();
+
+        let server = MockServer::start();
+        let robots = server.mock(|when, then| {
+            when.method(GET).path("/robots.txt");
+            then.status(200).body("User-agent: *\nCrawl-delay: 1\n");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path_matches(r"^/page/");
+            then.status(200).body("page");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/hop");
+            then.status(302).header("location", "/page/e");
+        });
+
+        let config = HttpFetcherConfig {
+            user_agent: None,
+            proxy_server: None,
+            object_store: store_name.to_string(),
+            timeout: 30,
+            revalidate: true,
+            redirects: RedirectConfig::default(),
+            content: ContentConfig::default(),
+            politeness: Some(PolitenessConfig {
+                min_delay_ms: 200,
+                ..PolitenessConfig::default()
+            }),
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let record = |path: &str| Record {
+            uri: server.url(path),
+            task_id: task_id.clone(),
+            metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        };
+
+        let started = Instant::now();
+        let (a, b) = tokio::join!(
+            fetcher.on_message(record("/page/a")),
+            fetcher.on_message(record("/page/b"))
+        );
+
+        a.unwrap();
+        b.unwrap();
+
+        // The robots.txt crawl delay is longer than the minimum, and is only read once
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        robots.assert();
+
+        // Without a crawl delay the minimum applies
+        let config = HttpFetcherConfig {
+            politeness: Some(PolitenessConfig {
+                min_delay_ms: 200,
+                crawl_delay: false,
+                ..PolitenessConfig::default()
+            }),
+            ..config
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let started = Instant::now();
+
+        fetcher.on_message(record("/page/c")).await.unwrap();
+        fetcher.on_message(record("/page/d")).await.unwrap();
+
+        let elapsed = started.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(200));
+        assert!(elapsed < Duration::from_secs(1));
+
+        // Each hop of a redirect waits its turn for the host it goes to
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let started = Instant::now();
+
+        fetcher.on_message(record("/hop")).await.unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_throttled() {
+        let path = temp_dir().join(Uuid::new_v4().to_string());
+        let store = FileSystemObjectStore::new(path).await.unwrap();
+        let store_name = "test-throttled-object-store";
+        let task_id = Uuid::new_v4().to_string();
+
+        dependencies()
+            .lock()
+            .await
+            .set_object_store(store_name, Arc::new(store))
+            .unwrap();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/limited");
+            then.status(429).header("retry-after", "1");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/down");
+            then.status(503);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/ok");
+            then.status(200).body("ok");
+        });
+
+        let config = HttpFetcherConfig {
+            user_agent: None,
+            proxy_server: None,
+            object_store: store_name.to_string(),
+            timeout: 30,
+            revalidate: true,
+            redirects: RedirectConfig::default(),
+            content: ContentConfig::default(),
+            politeness: Some(PolitenessConfig {
+                min_delay_ms: 0,
+                crawl_delay: false,
+                throttle_backoff_ms: 100,
+                ..PolitenessConfig::default()
+            }),
+        };
+        let fetcher = HttpFetcher::new(&config).await.unwrap();
+        let record = |path: &str| Record {
+            uri: server.url(path),
+            task_id: task_id.clone(),
+            metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        };
+
+        let error = fetcher.on_message(record("/limited")).await.unwrap_err();
+
+        assert!(error.is_retryable());
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(1)));
+
+        // The host is left alone until its Retry-After has passed
+        let started = Instant::now();
+
+        fetcher.on_message(record("/ok")).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(900));
+
+        // A 503 without Retry-After is an ordinary response, but still backs off the host
+        let response = fetcher.on_message(record("/down")).await.unwrap();
+
+        assert!(matches!(
+            response.metadata.first(),
+            Some(RecordMetadata::HttpResponse(r)) if r.status == Some(503)
+        ));
+
+        let started = Instant::now();
+
+        fetcher.on_message(record("/ok")).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
     #[tokio::test]
     async fn test_request_error() {
         let path = temp_dir().join(Uuid::new_v4().to_string());
@@ -287,6 +1192,10 @@ mod tests {
             proxy_server: None,
             object_store: store_name.to_string(),
             timeout: 30,
+            revalidate: true,
+            redirects: RedirectConfig::default(),
+            content: ContentConfig::default(),
+            politeness: None,
         };
 
         let fetcher = HttpFetcher::new(&config).await.unwrap();
@@ -294,21 +1203,49 @@ mod tests {
             uri: "http://127.0.0.1:9".to_string(),
             task_id: task_id,
             metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
         };
 
         let response = fetcher.on_message(record).await.unwrap();
 
         let http_response: &HttpResponse = match response.metadata.first() {
             Some(RecordMetadata::HttpResponse(r)) => r,
-            _ => panic!("headless browser did not create a response object"),
+            _ => panic!("http fetcher did not create a response object"),
         };
+        let error = http_response.error.clone().unwrap();
 
-        assert!(
-            http_response
-                .error
-                .clone()
-                .unwrap()
-                .contains("error sending request")
-        );
+        assert!(error.message.contains("error sending request"));
+        assert_eq!(error.kind, FetchErrorKind::ConnectionRefused);
+        assert!(fetcher.recorded_failure(&response).unwrap().is_retryable());
+
+        // A name that doesn't resolve or a handshake that fails will fail again
+        let server = MockServer::start();
+
+        for (uri, kind) in [
+            (
+                "http://aetherscope.invalid/".to_string(),
+                FetchErrorKind::Dns,
+            ),
+            (
+                format!("https://{}/", server.address()),
+                FetchErrorKind::Tls,
+            ),
+        ] {
+            let record = Record {
+                uri,
+                metadata: vec![],
+                ..response.clone()
+            };
+            let failed = fetcher.on_message(record).await.unwrap();
+
+            assert_eq!(failed.fetch_error().unwrap().kind, kind);
+            assert!(!fetcher.recorded_failure(&failed).unwrap().is_retryable());
+        }
     }
 }
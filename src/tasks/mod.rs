@@ -1,5 +1,6 @@
 pub mod frontier;
 pub mod headless_browser_fetcher;
 pub mod http_fetcher;
+pub mod record_filter;
 pub mod signal_extractor;
 pub mod url_extractor;
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use url::Url;
+
+use crate::{
+    types::{
+        configs::tasks::record_filter_config::RecordFilterConfig, error::AppError,
+        structs::record::Record, traits::task::Task,
+    },
+    utils::web::extract_host,
+};
+
+// Drops records that fall outside the configured limits. Kept records pass through
+// unchanged.
+pub struct RecordFilter<'a> {
+    config: &'a RecordFilterConfig,
+}
+
+impl<'a> RecordFilter<'a> {
+    pub async fn new(config: &'a RecordFilterConfig) -> Result<Self, AppError> {
+        Ok(Self { config })
+    }
+
+    fn keep(&self, record: &Record) -> Result<bool, AppError> {
+        if self.config.max_depth.is_some_and(|max| record.depth > max) {
+            return Ok(false);
+        }
+
+        if let (true, Some(parent)) = (self.config.same_host, &record.parent_uri) {
+            let host = extract_host(&Url::parse(&record.uri)?)?;
+
+            return Ok(host == extract_host(&Url::parse(parent)?)?);
+        }
+
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl<'a> Task for RecordFilter<'a> {
+    async fn on_message(&self, message: Record) -> Result<Record, AppError> {
+        Ok(message)
+    }
+
+    async fn fan_out(&self, message: Record) -> Result<Vec<Record>, AppError> {
+        match self.keep(&message)? {
+            true => Ok(vec![message]),
+            false => Ok(vec![]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::structs::record::HopType;
+
+    use super::*;
+
+    fn record(uri: &str, parent_uri: Option<&str>, depth: u32) -> Record {
+        Record {
+            uri: uri.to_string(),
+            task_id: "test".to_string(),
+            metadata: vec![],
+            parent_uri: parent_uri.map(str::to_string),
+            depth,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drops_records() {
+        let config = RecordFilterConfig {
+            max_depth: Some(2),
+            same_host: true,
+        };
+        let filter = RecordFilter::new(&config).await.unwrap();
+
+        let cases = [
+            (record("http://a.com/", None, 0), 1),
+            (record("http://a.com/x", Some("http://a.com/"), 2), 1),
+            (record("http://a.com/y", Some("http://a.com/x"), 3), 0),
+            (record("http://b.com/", Some("http://a.com/"), 1), 0),
+        ];
+
+        for (record, kept) in cases {
+            assert_eq!(filter.fan_out(record).await.unwrap().len(), kept);
+        }
+    }
+}
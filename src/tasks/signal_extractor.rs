@@ -1,19 +1,16 @@
 use async_trait::async_trait;
 
 use crate::types::{
-    configs::tasks::signal_extractor_config::SignalExtractorConfig,
-    error::AppError,
-    signals::url_state::UrlState,
-    structs::record::Record,
-    traits::{signal::Signal, task::Task},
+    configs::tasks::signal_extractor_config::SignalExtractorConfig, error::AppError,
+    structs::record::Record, traits::task::Task,
 };
 
 pub struct SignalExtractor<'a> {
-    config: &'a SignalExtractorConfig<'a>,
+    config: &'a SignalExtractorConfig,
 }
 
 impl<'a> SignalExtractor<'a> {
-    pub async fn new(config: &'a SignalExtractorConfig<'a>) -> Result<Self, AppError> {
+    pub async fn new(config: &'a SignalExtractorConfig) -> Result<Self, AppError> {
         Ok(Self { config })
     }
 }
@@ -21,6 +18,11 @@ impl<'a> SignalExtractor<'a> {
 #[async_trait]
 impl<'a> Task for SignalExtractor<'a> {
     async fn on_message(&self, message: Record) -> Result<Record, AppError> {
+        // Nothing to extract, so the record passes through untouched
+        if self.config.signals.is_empty() {
+            return Ok(message);
+        }
+
         // TODO start here next, create a trait for signals so that it can extract
         // them and output the values for writing.
         unimplemented!()
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use tracing::debug;
 
 use crate::{
     types::{
@@ -8,11 +9,15 @@ use crate::{
         error::AppError,
         structs::{
             metadata::uris::Uris,
-            record::{Record, RecordMetadata},
+            record::{HopType, Record, RecordMetadata},
         },
         traits::{object_store::ObjectStore, task::Task},
     },
-    utils::{dependencies::dependencies, fsm::url_fsm::UriExtractorFSM},
+    utils::{
+        dependencies::dependencies,
+        fsm::url_fsm::UriExtractorFSM,
+        metrics::{EXTRACTED_URLS, metrics},
+    },
 };
 
 pub struct UrlExtractor<'a> {
@@ -47,9 +52,16 @@ impl<'a> Task for UrlExtractor<'a> {
 
             if let Some(key) = http_response.key {
                 let buf = self.object_store.get_stream(&key).await?;
-                let fsm = UriExtractorFSM::new(buf, message.uri.clone())?;
+                // Relative links resolve against where the body came from after redirects
+                let base = http_response
+                    .final_uri
+                    .unwrap_or_else(|| message.uri.clone());
+                let fsm = UriExtractorFSM::new(buf, base)?;
                 let uris = fsm.perform().await?;
 
+                metrics().observe(&EXTRACTED_URLS, &[], uris.len() as f64);
+                debug!(urls = uris.len(), key = %key, "extracted urls");
+
                 metadata.push(RecordMetadata::Uris(Uris { uris }));
             }
 
@@ -57,11 +69,35 @@ impl<'a> Task for UrlExtractor<'a> {
         }
 
         Ok(Record {
-            uri: message.uri,
-            task_id: message.task_id,
             metadata: metadata,
+            ..message
         })
     }
+
+    async fn fan_out(&self, message: Record) -> Result<Vec<Record>, AppError> {
+        let mut record = self.on_message(message).await?;
+
+        if !self.config.children {
+            return Ok(vec![record]);
+        }
+
+        let mut uris = vec![];
+
+        record.metadata.retain(|m| match m {
+            RecordMetadata::Uris(u) => {
+                uris.extend(u.uris.iter().cloned());
+                false
+            }
+            _ => true,
+        });
+
+        let children: Vec<Record> = uris
+            .into_iter()
+            .map(|u| record.child(u, HopType::Link))
+            .collect();
+
+        Ok([vec![record], children].concat())
+    }
 }
 
 #[cfg(test)]
@@ -107,6 +143,11 @@ mod tests {
             error: None,
             timestamp: None,
             minhash: None,
+            unchanged: false,
+            redirects: vec![],
+            final_uri: None,
+            truncated: false,
+            timing: Default::default(),
         };
 
         dependencies()
@@ -117,12 +158,20 @@ mod tests {
 
         let config = &UrlExtractorConfig {
             object_store: store_name.to_string(),
+            children: false,
         };
         let extractor = UrlExtractor::new(config).await.unwrap();
         let record = Record {
             uri: "http://example.com".to_string(),
             task_id: task_id,
-            metadata: vec![RecordMetadata::HttpResponse(response)],
+            metadata: vec![RecordMetadata::HttpResponse(Box::new(response))],
+            parent_uri: None,
+            depth: 0,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
         };
 
         let response = extractor.on_message(record).await.unwrap();
@@ -138,4 +187,72 @@ mod tests {
             assert_eq!(uris.uris.len(), 3);
         }
     }
+
+    #[tokio::test]
+    async fn test_children() {
+        let path = temp_dir().join(Uuid::new_v4().to_string());
+        let store = FileSystemObjectStore::new(path).await.unwrap();
+        let store_name = "test-children-object-store";
+        let key = Uuid::new_v4().to_string();
+
+        store
+            .put(&key, r#"<a href="/a">a</a> <a href="/b">b</a>"#.as_bytes())
+            .await
+            .unwrap();
+
+        dependencies()
+            .lock()
+            .await
+            .set_object_store(store_name, Arc::new(store))
+            .unwrap();
+
+        let config = &UrlExtractorConfig {
+            object_store: store_name.to_string(),
+            children: true,
+        };
+        let extractor = UrlExtractor::new(config).await.unwrap();
+        let record = Record {
+            uri: "http://example.com/".to_string(),
+            task_id: "test".to_string(),
+            metadata: vec![RecordMetadata::HttpResponse(Box::new(HttpResponse {
+                status: Some(200),
+                request: HttpRequest {
+                    method: "GET".to_string(),
+                    request_headers: HashMap::new(),
+                    timestamp: Utc::now(),
+                },
+                response_headers: HashMap::new(),
+                key: Some(key),
+                error: None,
+                timestamp: None,
+                minhash: None,
+                unchanged: false,
+                redirects: vec![],
+                final_uri: None,
+                truncated: false,
+                timing: Default::default(),
+            }))],
+            parent_uri: None,
+            depth: 3,
+            max_depth: None,
+            seed_uri: None,
+            hop: HopType::Seed,
+            discovered_at: None,
+            deferrals: 0,
+        };
+
+        let records = extractor.fan_out(record).await.unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].metadata.len(), 1);
+
+        for child in &records[1..] {
+            assert_eq!(child.parent_uri.as_deref(), Some("http://example.com/"));
+            assert_eq!(child.depth, 4);
+            assert_eq!(child.task_id, "test");
+            assert_eq!(child.seed_uri.as_deref(), Some("http://example.com/"));
+            assert_eq!(child.hop, HopType::Link);
+            assert!(child.discovered_at.is_some());
+        }
+    }
 }
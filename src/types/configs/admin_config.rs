@@ -0,0 +1,26 @@
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    // Address the admin API is served on by `aetherscope serve`
+    pub listen: SocketAddr,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([127, 0, 0, 1], 9899)),
+        }
+    }
+}
+
+impl Validate for AdminConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
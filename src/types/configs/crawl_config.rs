@@ -0,0 +1,490 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, de::Error};
+use serde_json::{Map, Value};
+
+use crate::{
+    types::{
+        configs::{
+            admin_config::AdminConfig,
+            filters::{
+                robots_filter_config::RobotsFilterConfig, scope_filter_config::ScopeFilterConfig,
+                unique_filter_config::UniqueFilterConfig,
+            },
+            job_store_config::JobStoreConfig,
+            logging_config::LoggingConfig,
+            metrics_config::MetricsConfig,
+            queues::queue_config::QueueConfig,
+            tasks::{
+                headless_browser_config::HeadlessBrowserConfig,
+                http_fetcher_config::HttpFetcherConfig, record_filter_config::RecordFilterConfig,
+                retry_config::RetryConfig, url_extractor_config::UrlExtractorConfig,
+            },
+        },
+        error::AppError,
+        traits::validate::Validate,
+    },
+    utils::{config::load_config, namespace::validate_task_id},
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrawlConfig {
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub object_stores: Vec<ObjectStoreConfig>,
+    #[serde(default)]
+    pub queue: QueueConfig,
+    // Where records go once a task gives up on them; they are dropped when unset
+    #[serde(default)]
+    pub dead_letter: Option<DeadLetterConfig>,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub admin: AdminConfig,
+    #[serde(default)]
+    pub jobs: JobStoreConfig,
+    // Serves `/metrics` while crawling when set
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
+    pub tasks: Vec<StageConfig>,
+    #[serde(default)]
+    pub filters: FrontierFiltersConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetConfig {
+    pub max_pages: Option<usize>,
+    pub max_depth: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ObjectStoreConfig {
+    FileSystem { name: String, path: PathBuf },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DeadLetterConfig {
+    File { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    // Seconds in-flight records get to finish after a shutdown signal before the rest
+    // are requeued
+    pub drain_timeout: u64,
+    // File the frontier and bloom filter are saved to when interrupted, and resumed from
+    pub checkpoint: Option<PathBuf>,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_timeout: 30,
+            checkpoint: None,
+        }
+    }
+}
+
+// A task together with how many workers run it and how many records may wait in front
+// of them. A full buffer blocks the stage before it.
+#[derive(Debug, Clone)]
+pub struct StageConfig {
+    pub task: TaskConfig,
+    pub workers: usize,
+    pub buffer: usize,
+    pub retry: RetryConfig,
+}
+
+// The keys of a `[[tasks]]` table that configure the stage rather than its task
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StageFields {
+    #[serde(default = "default_workers")]
+    workers: usize,
+    #[serde(default = "default_buffer")]
+    buffer: usize,
+    #[serde(default)]
+    retry: RetryConfig,
+}
+
+const STAGE_FIELDS: [&str; 3] = ["workers", "buffer", "retry"];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskConfig {
+    HttpFetcher(HttpFetcherConfig),
+    HeadlessBrowser(HeadlessBrowserConfig),
+    UrlExtractor(UrlExtractorConfig),
+    RecordFilter(RecordFilterConfig),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrontierFiltersConfig {
+    pub scope: Option<ScopeFilterConfig>,
+    pub robots: Option<RobotsFilterConfig>,
+    pub unique: Option<UniqueFilterConfig>,
+}
+
+pub fn default_workers() -> usize {
+    1
+}
+
+pub fn default_buffer() -> usize {
+    16
+}
+
+impl CrawlConfig {
+    pub async fn from_file(path: &Path) -> Result<Self, AppError> {
+        load_config(path).await
+    }
+}
+
+// Stage and task keys share one table. They're split by hand rather than with
+// `#[serde(flatten)]`, which would stop the task's own `deny_unknown_fields` from
+// rejecting misspelled keys.
+impl<'de> Deserialize<'de> for StageConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut table = Map::<String, Value>::deserialize(deserializer)?;
+        let stage: Map<String, Value> = STAGE_FIELDS
+            .iter()
+            .filter_map(|key| table.remove_entry(*key))
+            .collect();
+
+        let stage = StageFields::deserialize(Value::Object(stage)).map_err(D::Error::custom)?;
+        let task = TaskConfig::deserialize(Value::Object(table)).map_err(D::Error::custom)?;
+
+        Ok(Self {
+            task,
+            workers: stage.workers,
+            buffer: stage.buffer,
+            retry: stage.retry,
+        })
+    }
+}
+
+impl ObjectStoreConfig {
+    pub fn name(&self) -> &str {
+        match self {
+            ObjectStoreConfig::FileSystem { name, .. } => name,
+        }
+    }
+}
+
+impl TaskConfig {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskConfig::HttpFetcher(_) => "http_fetcher",
+            TaskConfig::HeadlessBrowser(_) => "headless_browser",
+            TaskConfig::UrlExtractor(_) => "url_extractor",
+            TaskConfig::RecordFilter(_) => "record_filter",
+        }
+    }
+
+    pub fn object_store(&self) -> Option<&str> {
+        match self {
+            TaskConfig::HttpFetcher(c) => Some(&c.object_store),
+            TaskConfig::HeadlessBrowser(c) => Some(&c.object_store),
+            TaskConfig::UrlExtractor(c) => Some(&c.object_store),
+            TaskConfig::RecordFilter(_) => None,
+        }
+    }
+}
+
+impl Validate for CrawlConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if let Some(task_id) = &self.task_id {
+            validate_task_id(task_id)?;
+        }
+
+        self.budget.validate().map_err(|e| e.in_field("budget"))?;
+
+        let mut names = HashSet::new();
+
+        for (i, store) in self.object_stores.iter().enumerate() {
+            if !names.insert(store.name()) {
+                return Err(AppError::invalid_config(
+                    &format!("object_stores[{}].name", i),
+                    &format!("duplicate object store `{}`", store.name()),
+                ));
+            }
+        }
+
+        self.queue.validate().map_err(|e| e.in_field("queue"))?;
+        self.shutdown
+            .validate()
+            .map_err(|e| e.in_field("shutdown"))?;
+        self.logging.validate().map_err(|e| e.in_field("logging"))?;
+        self.admin.validate().map_err(|e| e.in_field("admin"))?;
+        self.jobs.validate().map_err(|e| e.in_field("jobs"))?;
+        validate_section(&self.metrics, "metrics")?;
+
+        if self.tasks.is_empty() {
+            return Err(AppError::invalid_config("tasks", "must not be empty"));
+        }
+
+        for (i, task) in self.tasks.iter().enumerate() {
+            let field = format!("tasks[{}]", i);
+
+            task.validate().map_err(|e| e.in_field(&field))?;
+
+            if let Some(store) = task.task.object_store()
+                && !names.contains(store)
+            {
+                return Err(AppError::invalid_config(
+                    &format!("{}.object_store", field),
+                    &format!("unknown object store `{}`", store),
+                ));
+            }
+        }
+
+        self.filters.validate().map_err(|e| e.in_field("filters"))
+    }
+}
+
+impl Validate for ShutdownConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        match &self.checkpoint {
+            Some(path) if path.as_os_str().is_empty() => {
+                Err(AppError::invalid_config("checkpoint", "must not be empty"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Validate for BudgetConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        match self.max_pages {
+            Some(0) => Err(AppError::invalid_config(
+                "max_pages",
+                "must be greater than 0",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Validate for StageConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.workers == 0 {
+            return Err(AppError::invalid_config(
+                "workers",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.buffer == 0 {
+            return Err(AppError::invalid_config("buffer", "must be greater than 0"));
+        }
+
+        self.retry.validate().map_err(|e| e.in_field("retry"))?;
+        self.task.validate()
+    }
+}
+
+impl Validate for TaskConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        match self {
+            TaskConfig::HttpFetcher(c) => c.validate(),
+            TaskConfig::HeadlessBrowser(c) => c.validate(),
+            TaskConfig::UrlExtractor(c) => c.validate(),
+            TaskConfig::RecordFilter(c) => c.validate(),
+        }
+    }
+}
+
+impl Validate for FrontierFiltersConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        validate_section(&self.scope, "scope")?;
+        validate_section(&self.robots, "robots")?;
+        validate_section(&self.unique, "unique")
+    }
+}
+
+fn validate_section<T: Validate>(section: &Option<T>, field: &str) -> Result<(), AppError> {
+    match section {
+        Some(c) => c.validate().map_err(|e| e.in_field(field)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use tokio::fs::write;
+    use uuid::Uuid;
+
+    use crate::types::configs::{
+        filters::unique_filter_config::HashSetConfig, logging_config::LogFormat,
+    };
+
+    use super::*;
+
+    const TOML_CONFIG: &str = r#"
+        task_id = "test"
+
+        [budget]
+        max_pages = 10
+
+        [[object_stores]]
+        type = "file_system"
+        name = "default"
+        path = "/tmp/aetherscope"
+
+        [queue]
+        type = "sqlite"
+        path = "/tmp/aetherscope/queue.db"
+
+        [shutdown]
+        checkpoint = "/tmp/aetherscope/checkpoint.json"
+
+        [logging]
+        format = "json"
+        filter = "info,aetherscope::crawler=debug"
+
+        [metrics]
+
+        [[tasks]]
+        type = "http_fetcher"
+        object_store = "default"
+        workers = 8
+
+        [tasks.retry]
+        max_attempts = 5
+
+        [[tasks]]
+        type = "url_extractor"
+        object_store = "default"
+
+        [filters.robots]
+
+        [filters.unique.hash_set]
+        type = "sqlite"
+        path = ":memory:"
+    "#;
+
+    async fn load(contents: &str, extension: &str) -> Result<CrawlConfig, AppError> {
+        let path = temp_dir().join(format!("{}.{}", Uuid::new_v4(), extension));
+        write(&path, contents).await.unwrap();
+
+        CrawlConfig::from_file(&path).await
+    }
+
+    #[tokio::test]
+    async fn test_load_toml_with_defaults() {
+        let config = load(TOML_CONFIG, "toml").await.unwrap();
+
+        assert_eq!(config.task_id.as_deref(), Some("test"));
+        assert_eq!(config.budget.max_pages, Some(10));
+        assert_eq!(config.tasks.len(), 2);
+        assert!(matches!(&config.queue, QueueConfig::Sqlite(c) if c.max_attempts == 5));
+        assert_eq!(config.shutdown.drain_timeout, 30);
+        assert!(config.shutdown.checkpoint.is_some());
+        assert_eq!(config.metrics.unwrap().listen.port(), 9898);
+        assert_eq!(config.logging.format, LogFormat::Json);
+
+        let TaskConfig::HttpFetcher(fetcher) = &config.tasks[0].task else {
+            panic!("expected an http fetcher");
+        };
+
+        assert_eq!(fetcher.timeout, 30);
+        assert_eq!(config.tasks[0].workers, 8);
+        assert_eq!(config.tasks[1].workers, 1);
+        assert_eq!(config.tasks[1].buffer, 16);
+        assert_eq!(config.tasks[0].retry.max_attempts, 5);
+        assert_eq!(config.tasks[1].retry.max_attempts, 3);
+        assert_eq!(config.filters.robots.unwrap().http_config.timeout, 30);
+
+        let unique = config.filters.unique.unwrap();
+
+        assert!(unique.bloom_filter.enable);
+        assert!(matches!(unique.hash_set, HashSetConfig::Sqlite(c) if c.path == ":memory:"));
+    }
+
+    #[tokio::test]
+    async fn test_load_json() {
+        let contents = r#"{
+            "object_stores": [{"type": "file_system", "name": "default", "path": "/tmp/aetherscope"}],
+            "tasks": [{"type": "headless_browser", "object_store": "default", "timeout": 5}],
+            "filters": {"unique": {"hash_set": {"type": "redis", "uri": "redis://localhost:6379"}}}
+        }"#;
+        let config = load(contents, "json").await.unwrap();
+
+        assert!(matches!(&config.tasks[0].task, TaskConfig::HeadlessBrowser(c) if c.timeout == 5));
+        assert!(matches!(&config.queue, QueueConfig::Memory(c) if c.capacity.is_none()));
+        assert!(matches!(
+            config.filters.unique.unwrap().hash_set,
+            HashSetConfig::Redis(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_validation_names_field() {
+        let contents = TOML_CONFIG.replace(
+            "type = \"url_extractor\"\n        object_store = \"default\"",
+            "type = \"url_extractor\"\n        object_store = \"missing\"",
+        );
+        let err = load(&contents, "toml").await.unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "invalid config field `tasks[1].object_store`: unknown object store `missing`"
+        );
+
+        let contents = format!(
+            "{}\n[filters.unique.bloom_filter]\nfalse_positive_rate = 2.0",
+            TOML_CONFIG
+        );
+        let err = load(&contents, "toml").await.unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "invalid config field `filters.unique.bloom_filter.false_positive_rate`: must be between 0 and 1"
+        );
+
+        let contents = TOML_CONFIG.replace("aetherscope::crawler=debug", "aetherscope=loud");
+        let err = load(&contents, "toml").await.unwrap_err();
+
+        assert!(
+            err.to_string()
+                .starts_with("invalid config field `logging.filter`")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_field_rejected() {
+        let contents = TOML_CONFIG.replace("max_pages = 10", "max_pagez = 10");
+        let err = load(&contents, "toml").await.unwrap_err();
+
+        assert!(err.to_string().contains("unknown field `max_pagez`"));
+
+        let contents = TOML_CONFIG.replace("workers = 8", "workerz = 8");
+        let err = load(&contents, "toml").await.unwrap_err();
+
+        assert!(err.to_string().contains("unknown field `workerz`"));
+
+        let contents = TOML_CONFIG.replace("max_attempts = 5", "max_attemptz = 5");
+        let err = load(&contents, "toml").await.unwrap_err();
+
+        assert!(err.to_string().contains("unknown field `max_attemptz`"));
+
+        let contents = format!("{}\n[scorers.cost]", TOML_CONFIG);
+        let err = load(&contents, "toml").await.unwrap_err();
+
+        assert!(err.to_string().contains("unknown field `scorers`"));
+    }
+}
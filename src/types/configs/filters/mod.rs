@@ -1,2 +1,3 @@
 pub mod robots_filter_config;
+pub mod scope_filter_config;
 pub mod unique_filter_config;
@@ -1,5 +1,20 @@
-use crate::types::configs::tasks::http_fetcher_config::BasicHttpFetcherConfig;
+use serde::Deserialize;
 
+use crate::types::{
+    configs::tasks::http_fetcher_config::BasicHttpFetcherConfig, error::AppError,
+    traits::validate::Validate,
+};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RobotsFilterConfig {
     pub http_config: BasicHttpFetcherConfig,
 }
+
+impl Validate for RobotsFilterConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        self.http_config
+            .validate()
+            .map_err(|e| e.in_field("http_config"))
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScopeFilterConfig {
+    // Hosts the crawl may visit
+    pub hosts: Vec<String>,
+    // Whether subdomains of those hosts are in scope too
+    #[serde(default = "default_include_subdomains")]
+    pub include_subdomains: bool,
+}
+
+pub fn default_include_subdomains() -> bool {
+    true
+}
+
+impl Validate for ScopeFilterConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.hosts.is_empty() {
+            return Err(AppError::invalid_config("hosts", "must not be empty"));
+        }
+
+        match self.hosts.iter().any(|h| h.is_empty()) {
+            true => Err(AppError::invalid_config(
+                "hosts",
+                "must not contain empty hosts",
+            )),
+            false => Ok(()),
+        }
+    }
+}
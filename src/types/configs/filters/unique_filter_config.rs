@@ -1,33 +1,97 @@
-use crate::{
-    tasks::frontier::filters::hash_sets::{
-        redis_hash_set::RedisHashSet, sqlite_hash_set::SqliteHashSet,
-    },
-    types::{error::AppError, traits::check_hash_set::CheckHashSet},
-};
-
-#[derive(Clone)]
+use serde::Deserialize;
+use url::Url;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct SqliteHashSetConfig {
     pub path: String,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RedisHashSetConfig {
     pub uri: String,
 }
 
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
 pub enum HashSetConfig {
     Sqlite(SqliteHashSetConfig),
     Redis(RedisHashSetConfig),
+    #[default]
     Empty,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BloomFilterConfig {
     pub enable: bool,
     pub false_positive_rate: f64,
     pub expected_size: usize,
 }
 
+impl Default for BloomFilterConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            false_positive_rate: 0.001,
+            expected_size: 1_000_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct UniqueFilterConfig {
     pub bloom_filter: BloomFilterConfig,
     pub hash_set: HashSetConfig,
 }
+
+impl Validate for HashSetConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        match self {
+            HashSetConfig::Sqlite(c) if c.path.is_empty() => {
+                Err(AppError::invalid_config("path", "must not be empty"))
+            }
+            HashSetConfig::Redis(c) if Url::parse(&c.uri).is_err() => {
+                Err(AppError::invalid_config("uri", "must be a valid url"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Validate for BloomFilterConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if !self.enable {
+            return Ok(());
+        }
+
+        if !(self.false_positive_rate > 0.0 && self.false_positive_rate < 1.0) {
+            return Err(AppError::invalid_config(
+                "false_positive_rate",
+                "must be between 0 and 1",
+            ));
+        }
+
+        if self.expected_size == 0 {
+            return Err(AppError::invalid_config(
+                "expected_size",
+                "must be greater than 0",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for UniqueFilterConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        self.bloom_filter
+            .validate()
+            .map_err(|e| e.in_field("bloom_filter"))?;
+        self.hash_set.validate().map_err(|e| e.in_field("hash_set"))
+    }
+}
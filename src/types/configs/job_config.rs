@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::{
+    configs::{
+        crawl_config::{BudgetConfig, CrawlConfig, TaskConfig},
+        filters::scope_filter_config::ScopeFilterConfig,
+    },
+    error::AppError,
+    traits::validate::Validate,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetcherKind {
+    HttpFetcher,
+    HeadlessBrowser,
+}
+
+// What one crawl job changes about the shared crawl config. Unset fields keep the
+// shared value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobConfig {
+    pub scope: Option<ScopeFilterConfig>,
+    pub budget: Option<BudgetConfig>,
+    // Sent by every fetcher and the robots filter
+    pub user_agent: Option<String>,
+    // Keeps only the fetch stages of this kind
+    pub fetcher: Option<FetcherKind>,
+}
+
+impl JobConfig {
+    // The config a job's crawl runs with
+    pub fn apply(&self, base: &CrawlConfig, task_id: &str) -> Result<CrawlConfig, AppError> {
+        let mut config = base.clone();
+
+        config.task_id = Some(task_id.to_string());
+
+        if let Some(scope) = &self.scope {
+            config.filters.scope = Some(scope.clone());
+        }
+
+        if let Some(budget) = &self.budget {
+            config.budget = budget.clone();
+        }
+
+        if let Some(fetcher) = self.fetcher {
+            config.tasks.retain(|stage| match &stage.task {
+                TaskConfig::HttpFetcher(_) => fetcher == FetcherKind::HttpFetcher,
+                TaskConfig::HeadlessBrowser(_) => fetcher == FetcherKind::HeadlessBrowser,
+                _ => true,
+            });
+
+            if !config.tasks.iter().any(|stage| is_fetcher(&stage.task)) {
+                return Err(AppError::invalid_config(
+                    "fetcher",
+                    "no task of this kind is configured",
+                ));
+            }
+        }
+
+        if let Some(user_agent) = &self.user_agent {
+            for stage in &mut config.tasks {
+                match &mut stage.task {
+                    TaskConfig::HttpFetcher(c) => c.user_agent = Some(user_agent.clone()),
+                    TaskConfig::HeadlessBrowser(c) => c.user_agent = Some(user_agent.clone()),
+                    _ => {}
+                }
+            }
+
+            if let Some(robots) = &mut config.filters.robots {
+                robots.http_config.user_agent = Some(user_agent.clone());
+            }
+        }
+
+        config.validate()?;
+
+        Ok(config)
+    }
+}
+
+fn is_fetcher(task: &TaskConfig) -> bool {
+    matches!(
+        task,
+        TaskConfig::HttpFetcher(_) | TaskConfig::HeadlessBrowser(_)
+    )
+}
+
+impl Validate for JobConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if let Some(scope) = &self.scope {
+            scope.validate().map_err(|e| e.in_field("scope"))?;
+        }
+
+        if let Some(budget) = &self.budget {
+            budget.validate().map_err(|e| e.in_field("budget"))?;
+        }
+
+        match self.user_agent.as_deref() {
+            Some("") => Err(AppError::invalid_config("user_agent", "must not be empty")),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::configs::{
+        crawl_config::StageConfig,
+        tasks::{headless_browser_config::HeadlessBrowserConfig, retry_config::RetryConfig},
+    };
+
+    use super::*;
+
+    fn base() -> CrawlConfig {
+        toml::from_str(
+            r#"
+            [[object_stores]]
+            type = "file_system"
+            name = "default"
+            path = "/tmp/aetherscope"
+
+            [budget]
+            max_pages = 100
+
+            [[tasks]]
+            type = "http_fetcher"
+            object_store = "default"
+
+            [[tasks]]
+            type = "url_extractor"
+            object_store = "default"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let job = JobConfig {
+            scope: None,
+            budget: Some(BudgetConfig {
+                max_pages: Some(5),
+                max_depth: Some(2),
+            }),
+            user_agent: Some("job-agent".to_string()),
+            fetcher: None,
+        };
+        let config = job.apply(&base(), "job").unwrap();
+
+        assert_eq!(config.task_id.as_deref(), Some("job"));
+        assert_eq!(config.budget.max_pages, Some(5));
+
+        let TaskConfig::HttpFetcher(fetcher) = &config.tasks[0].task else {
+            panic!("expected the http fetcher first");
+        };
+
+        assert_eq!(fetcher.user_agent.as_deref(), Some("job-agent"));
+    }
+
+    #[test]
+    fn test_choose_fetcher() {
+        let mut base = base();
+
+        base.tasks.insert(
+            1,
+            StageConfig {
+                task: TaskConfig::HeadlessBrowser(HeadlessBrowserConfig {
+                    proxy_server: None,
+                    browser_path: None,
+                    object_store: "default".to_string(),
+                    timeout: 30,
+                    user_agent: None,
+                }),
+                workers: 1,
+                buffer: 1,
+                retry: RetryConfig::default(),
+            },
+        );
+
+        let job = JobConfig {
+            fetcher: Some(FetcherKind::HeadlessBrowser),
+            ..JobConfig::default()
+        };
+        let config = job.apply(&base, "job").unwrap();
+        let names: Vec<&str> = config.tasks.iter().map(|s| s.task.name()).collect();
+
+        assert_eq!(names, vec!["headless_browser", "url_extractor"]);
+
+        base.tasks.remove(1);
+
+        let err = job.apply(&base, "job").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "invalid config field `fetcher`: no task of this kind is configured"
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+// Where crawl jobs are kept
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum JobStoreConfig {
+    // Lost when the process exits
+    #[default]
+    Memory,
+    Sqlite {
+        path: String,
+    },
+}
+
+impl Validate for JobStoreConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        match self {
+            JobStoreConfig::Sqlite { path } if path.is_empty() => {
+                Err(AppError::invalid_config("path", "must not be empty"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
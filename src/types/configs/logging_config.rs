@@ -0,0 +1,41 @@
+use serde::Deserialize;
+use tracing_subscriber::EnvFilter;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    // One aligned line per event, followed by the fields of its spans
+    #[default]
+    Human,
+    // One JSON object per line, with the fields of every enclosing span
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    // `RUST_LOG` style directives, e.g. `info,aetherscope::crawler=debug`. `RUST_LOG`
+    // takes precedence when set.
+    pub filter: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Human,
+            filter: "info".to_string(),
+        }
+    }
+}
+
+impl Validate for LoggingConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        match EnvFilter::try_new(&self.filter) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(AppError::invalid_config("filter", &e.to_string())),
+        }
+    }
+}
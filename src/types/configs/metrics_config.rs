@@ -0,0 +1,26 @@
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    // Address `/metrics` is served on
+    pub listen: SocketAddr,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([127, 0, 0, 1], 9898)),
+        }
+    }
+}
+
+impl Validate for MetricsConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
@@ -1,3 +1,10 @@
+pub mod admin_config;
+pub mod crawl_config;
 pub mod filters;
+pub mod job_config;
+pub mod job_store_config;
+pub mod logging_config;
+pub mod metrics_config;
+pub mod queues;
 pub mod scorers;
 pub mod tasks;
@@ -0,0 +1,44 @@
+use serde::Deserialize;
+
+use crate::types::{
+    configs::queues::queue_config::DEFAULT_VISIBILITY_TIMEOUT, error::AppError,
+    traits::validate::Validate,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MemoryQueueConfig {
+    // Messages, pending and leased, the queue holds before `put` waits for room. A crawl
+    // is the only consumer of its memory queue, so it leaves out what doesn't fit instead.
+    pub capacity: Option<usize>,
+    pub visibility_timeout: u64,
+}
+
+impl Default for MemoryQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            visibility_timeout: DEFAULT_VISIBILITY_TIMEOUT,
+        }
+    }
+}
+
+impl Validate for MemoryQueueConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.capacity == Some(0) {
+            return Err(AppError::invalid_config(
+                "capacity",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.visibility_timeout == 0 {
+            return Err(AppError::invalid_config(
+                "visibility_timeout",
+                "must be greater than 0",
+            ));
+        }
+
+        Ok(())
+    }
+}
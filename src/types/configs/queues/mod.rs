@@ -0,0 +1,4 @@
+pub mod memory_queue_config;
+pub mod queue_config;
+pub mod redis_queue_config;
+pub mod sqlite_queue_config;
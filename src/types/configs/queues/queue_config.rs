@@ -0,0 +1,38 @@
+use serde::Deserialize;
+
+use crate::types::{
+    configs::queues::{
+        memory_queue_config::MemoryQueueConfig, redis_queue_config::RedisQueueConfig,
+        sqlite_queue_config::SqliteQueueConfig,
+    },
+    error::AppError,
+    traits::validate::Validate,
+};
+
+// Defaults every queue shares
+pub const DEFAULT_VISIBILITY_TIMEOUT: u64 = 300;
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueueConfig {
+    Memory(MemoryQueueConfig),
+    Sqlite(SqliteQueueConfig),
+    Redis(RedisQueueConfig),
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        QueueConfig::Memory(MemoryQueueConfig::default())
+    }
+}
+
+impl Validate for QueueConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        match self {
+            QueueConfig::Memory(c) => c.validate(),
+            QueueConfig::Sqlite(c) => c.validate(),
+            QueueConfig::Redis(c) => c.validate(),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use serde::Deserialize;
+use url::Url;
+
+use crate::types::{
+    configs::queues::queue_config::{DEFAULT_MAX_ATTEMPTS, DEFAULT_VISIBILITY_TIMEOUT},
+    error::AppError,
+    structs::record::RecordEncoding,
+    traits::validate::Validate,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedisQueueConfig {
+    pub uri: String,
+    pub stream: String,
+    pub group: String,
+    // Defaults to a random name, so every process joins the group as its own consumer
+    pub consumer: Option<String>,
+    pub visibility_timeout: u64,
+    pub max_attempts: u32,
+    // How new messages are written; either encoding can always be read
+    pub encoding: RecordEncoding,
+}
+
+impl Default for RedisQueueConfig {
+    fn default() -> Self {
+        Self {
+            uri: String::new(),
+            stream: "aetherscope:queue".to_string(),
+            group: "aetherscope".to_string(),
+            consumer: None,
+            visibility_timeout: DEFAULT_VISIBILITY_TIMEOUT,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            encoding: RecordEncoding::default(),
+        }
+    }
+}
+
+impl Validate for RedisQueueConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if Url::parse(&self.uri).is_err() {
+            return Err(AppError::invalid_config("uri", "must be a valid url"));
+        }
+
+        if self.stream.is_empty() {
+            return Err(AppError::invalid_config("stream", "must not be empty"));
+        }
+
+        if self.group.is_empty() {
+            return Err(AppError::invalid_config("group", "must not be empty"));
+        }
+
+        if self.consumer.as_deref().is_some_and(str::is_empty) {
+            return Err(AppError::invalid_config("consumer", "must not be empty"));
+        }
+
+        if self.visibility_timeout == 0 {
+            return Err(AppError::invalid_config(
+                "visibility_timeout",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.max_attempts == 0 {
+            return Err(AppError::invalid_config(
+                "max_attempts",
+                "must be greater than 0",
+            ));
+        }
+
+        Ok(())
+    }
+}
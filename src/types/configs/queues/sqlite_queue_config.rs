@@ -0,0 +1,53 @@
+use serde::Deserialize;
+
+use crate::types::{
+    configs::queues::queue_config::{DEFAULT_MAX_ATTEMPTS, DEFAULT_VISIBILITY_TIMEOUT},
+    error::AppError,
+    structs::record::RecordEncoding,
+    traits::validate::Validate,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SqliteQueueConfig {
+    pub path: String,
+    pub visibility_timeout: u64,
+    pub max_attempts: u32,
+    // How new messages are written; either encoding can always be read
+    pub encoding: RecordEncoding,
+}
+
+impl Default for SqliteQueueConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            visibility_timeout: DEFAULT_VISIBILITY_TIMEOUT,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            encoding: RecordEncoding::default(),
+        }
+    }
+}
+
+impl Validate for SqliteQueueConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.path.is_empty() {
+            return Err(AppError::invalid_config("path", "must not be empty"));
+        }
+
+        if self.visibility_timeout == 0 {
+            return Err(AppError::invalid_config(
+                "visibility_timeout",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.max_attempts == 0 {
+            return Err(AppError::invalid_config(
+                "max_attempts",
+                "must be greater than 0",
+            ));
+        }
+
+        Ok(())
+    }
+}
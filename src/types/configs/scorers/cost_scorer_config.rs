@@ -1 +1,13 @@
-pub struct CostScorerConfig;
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CostScorerConfig {}
+
+impl Validate for CostScorerConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
@@ -1 +1,13 @@
-pub struct CoverageScorerConfig;
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoverageScorerConfig {}
+
+impl Validate for CoverageScorerConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
@@ -1 +1,13 @@
-pub struct FreshnessScorerConfig;
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FreshnessScorerConfig {}
+
+impl Validate for FreshnessScorerConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
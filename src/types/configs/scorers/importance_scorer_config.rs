@@ -1 +1,13 @@
-pub struct ImportanceScorerConfig;
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportanceScorerConfig {}
+
+impl Validate for ImportanceScorerConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
@@ -1 +1,13 @@
-pub struct NoveltyScorerConfig;
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NoveltyScorerConfig {}
+
+impl Validate for NoveltyScorerConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizeAction {
+    // Store the first `max_body_bytes` and mark the response truncated
+    #[default]
+    Truncate,
+    // Store nothing and record the response as an error
+    Abort,
+}
+
+// Which responses a fetcher stores, and how much of them. Media types are matched
+// without parameters and may end in `/*`, like `text/*`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContentConfig {
+    // Unlimited when unset
+    pub max_body_bytes: Option<u64>,
+    pub oversize: OversizeAction,
+    // Every type is allowed when empty. Responses without a `Content-Type` only pass an
+    // empty allowlist.
+    pub allow_types: Vec<String>,
+    pub deny_types: Vec<String>,
+}
+
+impl Validate for ContentConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.max_body_bytes == Some(0) {
+            return Err(AppError::invalid_config(
+                "max_body_bytes",
+                "must be greater than 0",
+            ));
+        }
+
+        for (field, types) in [
+            ("allow_types", &self.allow_types),
+            ("deny_types", &self.deny_types),
+        ] {
+            if types.iter().any(|t| t.split_once('/').is_none()) {
+                return Err(AppError::invalid_config(
+                    field,
+                    "must be media types like text/html or text/*",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,19 @@
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct FrontierManagerConfig {
-    enable_unique_filter: bool,
-    enable_unique_domain_rank: bool,
-    enable_pagerank_rank: bool,
-    enable_robots_filter: bool,
+    pub enable_unique_filter: bool,
+    pub enable_unique_domain_rank: bool,
+    pub enable_pagerank_rank: bool,
+    pub enable_robots_filter: bool,
     // TODO rate limiter?
 }
+
+impl Validate for FrontierManagerConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
@@ -1,7 +1,28 @@
+use serde::Deserialize;
+
+use crate::types::{
+    configs::tasks::http_fetcher_config::{
+        default_timeout, validate_object_store, validate_proxy_server, validate_timeout,
+    },
+    error::AppError,
+    traits::validate::Validate,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct HeadlessBrowserConfig {
     pub proxy_server: Option<String>,
     pub browser_path: Option<String>,
     pub object_store: String,
+    #[serde(default = "default_timeout")]
     pub timeout: i32,
     pub user_agent: Option<String>,
 }
+
+impl Validate for HeadlessBrowserConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        validate_proxy_server(&self.proxy_server)?;
+        validate_object_store(&self.object_store)?;
+        validate_timeout(self.timeout)
+    }
+}
@@ -1,12 +1,114 @@
+use serde::Deserialize;
+use url::Url;
+
+use crate::types::{
+    configs::tasks::{
+        content_config::ContentConfig, politeness_config::PolitenessConfig,
+        redirect_config::RedirectConfig,
+    },
+    error::AppError,
+    traits::validate::Validate,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct HttpFetcherConfig {
     pub proxy_server: Option<String>,
     pub object_store: String,
+    #[serde(default = "default_timeout")]
     pub timeout: i32,
     pub user_agent: Option<String>,
+    // Make requests conditional on the ETag / Last-Modified of the previous response a
+    // record carries. Nothing looks up earlier fetches, so only records passed in with
+    // that response's metadata are revalidated.
+    #[serde(default = "default_revalidate")]
+    pub revalidate: bool,
+    #[serde(default)]
+    pub redirects: RedirectConfig,
+    #[serde(default)]
+    pub content: ContentConfig,
+    // Fetches go as fast as records arrive when unset
+    #[serde(default)]
+    pub politeness: Option<PolitenessConfig>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct BasicHttpFetcherConfig {
     pub proxy_server: Option<String>,
+    #[serde(default = "default_timeout")]
     pub timeout: i32,
     pub user_agent: Option<String>,
 }
+
+impl Default for BasicHttpFetcherConfig {
+    fn default() -> Self {
+        Self {
+            proxy_server: None,
+            timeout: default_timeout(),
+            user_agent: None,
+        }
+    }
+}
+
+pub fn default_timeout() -> i32 {
+    30
+}
+
+pub fn default_revalidate() -> bool {
+    true
+}
+
+pub fn validate_timeout(timeout: i32) -> Result<(), AppError> {
+    match timeout > 0 {
+        true => Ok(()),
+        false => Err(AppError::invalid_config(
+            "timeout",
+            "must be greater than 0",
+        )),
+    }
+}
+
+pub fn validate_proxy_server(proxy_server: &Option<String>) -> Result<(), AppError> {
+    match proxy_server.as_deref().map(Url::parse) {
+        Some(Err(_)) => Err(AppError::invalid_config(
+            "proxy_server",
+            "must be a valid url",
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_object_store(object_store: &str) -> Result<(), AppError> {
+    match object_store.is_empty() {
+        true => Err(AppError::invalid_config(
+            "object_store",
+            "must not be empty",
+        )),
+        false => Ok(()),
+    }
+}
+
+impl Validate for HttpFetcherConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        validate_proxy_server(&self.proxy_server)?;
+        validate_object_store(&self.object_store)?;
+        self.redirects.validate()?;
+        self.content.validate()?;
+
+        if let Some(politeness) = &self.politeness {
+            politeness
+                .validate()
+                .map_err(|e| e.in_field("politeness"))?;
+        }
+
+        validate_timeout(self.timeout)
+    }
+}
+
+impl Validate for BasicHttpFetcherConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        validate_proxy_server(&self.proxy_server)?;
+        validate_timeout(self.timeout)
+    }
+}
@@ -1,5 +1,10 @@
+pub mod content_config;
 pub mod frontier_manager_config;
 pub mod headless_browser_config;
 pub mod http_fetcher_config;
+pub mod politeness_config;
+pub mod record_filter_config;
+pub mod redirect_config;
+pub mod retry_config;
 pub mod signal_extractor_config;
 pub mod url_extractor_config;
@@ -0,0 +1,77 @@
+use serde::Deserialize;
+use url::Url;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedisHostGateConfig {
+    pub uri: String,
+}
+
+// Where host leases are kept. Workers in other processes only wait for each other when
+// they share a redis gate.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum HostGateConfig {
+    #[default]
+    Memory,
+    Redis(RedisHostGateConfig),
+}
+
+// One fetch per host at a time, and at least the larger of `min_delay_ms` and the host's
+// robots.txt `Crawl-delay` between the end of one fetch and the start of the next
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolitenessConfig {
+    pub min_delay_ms: u64,
+    pub crawl_delay: bool,
+    // Caps `Crawl-delay`, so one robots.txt can't stall a crawl
+    pub max_crawl_delay_ms: u64,
+    // How long a host stays leased to a worker that never releases it
+    pub lease_ms: u64,
+    // A host that throttles is left alone for this long, doubling each time it throttles
+    // again up to `max_throttle_backoff_ms`. Its `Retry-After` wins when longer.
+    pub throttle_backoff_ms: u64,
+    pub max_throttle_backoff_ms: u64,
+    pub gate: HostGateConfig,
+}
+
+impl Default for PolitenessConfig {
+    fn default() -> Self {
+        Self {
+            min_delay_ms: 1_000,
+            crawl_delay: true,
+            max_crawl_delay_ms: 60_000,
+            lease_ms: 60_000,
+            throttle_backoff_ms: 5_000,
+            max_throttle_backoff_ms: 600_000,
+            gate: HostGateConfig::default(),
+        }
+    }
+}
+
+impl Validate for PolitenessConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.lease_ms == 0 {
+            return Err(AppError::invalid_config(
+                "lease_ms",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.max_throttle_backoff_ms < self.throttle_backoff_ms {
+            return Err(AppError::invalid_config(
+                "max_throttle_backoff_ms",
+                "must not be less than throttle_backoff_ms",
+            ));
+        }
+
+        match &self.gate {
+            HostGateConfig::Redis(c) if Url::parse(&c.uri).is_err() => {
+                Err(AppError::invalid_config("uri", "must be a valid url").in_field("gate"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordFilterConfig {
+    // Drop records more than this many hops from their seed
+    pub max_depth: Option<u32>,
+    // Drop child records on a different host than their parent
+    pub same_host: bool,
+}
+
+impl Validate for RecordFilterConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+// Which redirects a fetcher follows itself. A redirect it won't follow is recorded as the
+// response instead, with the hops taken so far.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedirectConfig {
+    // Redirects followed per fetch, 0 to follow none
+    pub max_hops: usize,
+    // Only follow redirects that stay on the same eTLD+1
+    pub same_site: bool,
+}
+
+impl Default for RedirectConfig {
+    fn default() -> Self {
+        Self {
+            max_hops: 10,
+            same_site: false,
+        }
+    }
+}
+
+impl Validate for RedirectConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.max_hops > 50 {
+            return Err(AppError::invalid_config("max_hops", "must be at most 50"));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+// How often a task is retried on a record after a retryable error, and how long to wait
+// between attempts. The wait grows by `multiplier` per attempt up to `max_backoff_ms`,
+// and up to `jitter` of it is taken off at random so retries don't line up. A record a
+// server throttles goes back to the queue instead, until its `Retry-After` has passed, or
+// at most `max_retry_after_ms`, and the backoff when it sets none.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub multiplier: f64,
+    pub jitter: f64,
+    pub max_retry_after_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_retry_after_ms: 300_000,
+        }
+    }
+}
+
+impl Validate for RetryConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.max_attempts == 0 {
+            return Err(AppError::invalid_config(
+                "max_attempts",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.max_backoff_ms < self.initial_backoff_ms {
+            return Err(AppError::invalid_config(
+                "max_backoff_ms",
+                "must not be less than initial_backoff_ms",
+            ));
+        }
+
+        if self.multiplier < 1.0 {
+            return Err(AppError::invalid_config("multiplier", "must be at least 1"));
+        }
+
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(AppError::invalid_config(
+                "jitter",
+                "must be between 0 and 1",
+            ));
+        }
+
+        Ok(())
+    }
+}
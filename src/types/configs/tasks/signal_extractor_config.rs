@@ -1,3 +1,21 @@
-pub struct SignalExtractorConfig<'a> {
-    pub signals: Vec<&'a str>,
+use serde::Deserialize;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignalExtractorConfig {
+    pub signals: Vec<String>,
+}
+
+impl Validate for SignalExtractorConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        match self.signals.iter().position(|s| s.is_empty()) {
+            Some(i) => Err(AppError::invalid_config(
+                &format!("signals[{}]", i),
+                "must not be empty",
+            )),
+            None => Ok(()),
+        }
+    }
 }
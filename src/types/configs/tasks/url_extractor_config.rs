@@ -1,3 +1,21 @@
+use serde::Deserialize;
+
+use crate::types::{
+    configs::tasks::http_fetcher_config::validate_object_store, error::AppError,
+    traits::validate::Validate,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct UrlExtractorConfig {
     pub object_store: String,
+    // Emit discovered links as child records instead of a `Uris` list on the page
+    #[serde(default)]
+    pub children: bool,
+}
+
+impl Validate for UrlExtractorConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        validate_object_store(&self.object_store)
+    }
 }
@@ -1,5 +1,19 @@
+use std::{
+    error::Error as _,
+    io::{self, ErrorKind},
+    time::Duration,
+};
+
 use thiserror::Error;
 
+use crate::{
+    types::structs::{
+        crawl_job::JobState,
+        metadata::http_response::{FetchError, FetchErrorKind},
+    },
+    utils::dns::DnsError,
+};
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("{0}")]
@@ -32,14 +46,127 @@ pub enum AppError {
     SqlxError(#[from] sqlx::Error),
     #[error(transparent)]
     RedisError(#[from] redis::RedisError),
+    // Boxed so it doesn't make every error as large as it is
+    #[error(transparent)]
+    CdrsTokioError(Box<cdrs_tokio::error::Error>),
+    #[error(transparent)]
+    TomlError(#[from] toml::de::Error),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
     #[error(transparent)]
-    CdrsTokioError(#[from] cdrs_tokio::error::Error),
+    JoinError(#[from] tokio::task::JoinError),
+    #[error(transparent)]
+    MessagePackEncodeError(#[from] rmp_serde::encode::Error),
+    #[error(transparent)]
+    MessagePackDecodeError(#[from] rmp_serde::decode::Error),
+    #[error("record envelope version {version} is newer than supported version {supported}")]
+    UnsupportedRecordVersion { version: u16, supported: u16 },
+    #[error("checkpoint version {version} is newer than supported version {supported}")]
+    UnsupportedCheckpointVersion { version: u16, supported: u16 },
+    #[error("malformed record: {0}")]
+    MalformedRecord(String),
+    #[error("no leased queue message with id {0}")]
+    QueueLeaseNotFound(String),
+    #[error("memory queue is full, can't queue {0}")]
+    QueueFull(String),
+    #[error("invalid config field `{field}`: {message}")]
+    InvalidConfig { field: String, message: String },
+    #[error("no crawl job with task id {0}")]
+    UnknownJob(String),
+    #[error("crawl job {task_id} can't go from {from} to {to}")]
+    InvalidJobTransition {
+        task_id: String,
+        from: JobState,
+        to: JobState,
+    },
+    #[error("crawl job {0} is still active")]
+    ActiveJob(String),
+    #[error("HTTP {status} from {uri}, throttled")]
+    Throttled {
+        status: u16,
+        uri: String,
+        // When the server asked to be tried again, if it said
+        retry_after: Option<Duration>,
+    },
     #[error("HTTP {method} {status}: {message}")]
     Http {
         status: i64,
         method: String,
         message: String,
     },
+    // A failed fetch a task recorded on its record instead of returning
+    #[error("{}", .0.message)]
+    FetchFailed(FetchError),
+}
+
+impl AppError {
+    pub fn invalid_config(field: &str, message: &str) -> Self {
+        AppError::InvalidConfig {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    // Whether running the same task on the same record again might succeed. Network
+    // trouble and overloaded servers are worth retrying; malformed input never is.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AppError::IOError(_)
+            | AppError::CdpError(_)
+            | AppError::HeadlessBrowserFetcherError(_)
+            | AppError::SqlxError(_)
+            | AppError::CdrsTokioError(_) => true,
+            AppError::ReqwestError(e) => reqwest_error_kind(e).is_transient(),
+            AppError::FetchFailed(e) => e.kind.is_transient(),
+            AppError::RedisError(e) => {
+                e.is_io_error()
+                    || e.is_timeout()
+                    || e.is_connection_dropped()
+                    || e.is_connection_refusal()
+            }
+            AppError::FetchError(status, _) => *status == 429 || *status >= 500,
+            AppError::Http { status, .. } => *status == 429 || *status >= 500,
+            AppError::Throttled { .. } => true,
+            _ => false,
+        }
+    }
+
+    // What kind of failure this is when it ends a fetch
+    pub fn fetch_error(&self) -> FetchError {
+        let kind = match self {
+            AppError::ReqwestError(e) => reqwest_error_kind(e),
+            AppError::FetchFailed(e) => return e.clone(),
+            AppError::UrlParseError(_) => FetchErrorKind::InvalidUrl,
+            AppError::IOError(e) if e.kind() == ErrorKind::TimedOut => FetchErrorKind::Timeout,
+            _ => FetchErrorKind::Other,
+        };
+
+        FetchError::new(kind, self.to_string())
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AppError::Throttled { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    // Prefixes the field of a config error with the path of its parent.
+    pub fn in_field(self, parent: &str) -> Self {
+        match self {
+            AppError::InvalidConfig { field, message } => AppError::InvalidConfig {
+                field: format!("{}.{}", parent, field),
+                message,
+            },
+            e => e,
+        }
+    }
+}
+
+impl From<cdrs_tokio::error::Error> for AppError {
+    fn from(e: cdrs_tokio::error::Error) -> Self {
+        AppError::CdrsTokioError(Box::new(e))
+    }
 }
 
 impl From<String> for AppError {
@@ -53,3 +180,60 @@ impl From<&str> for AppError {
         AppError::Generic(s.to_owned())
     }
 }
+
+// reqwest only tells connection failures apart by the errors they wrap, so the chain is
+// walked for the resolver's, socket's and TLS backends' own error types
+fn reqwest_error_kind(e: &reqwest::Error) -> FetchErrorKind {
+    if e.is_timeout() {
+        return FetchErrorKind::Timeout;
+    }
+
+    if e.is_redirect() {
+        return FetchErrorKind::TooManyRedirects;
+    }
+
+    if e.is_builder() {
+        return FetchErrorKind::InvalidUrl;
+    }
+
+    if e.is_body() || e.is_decode() {
+        return FetchErrorKind::BodyRead;
+    }
+
+    let mut source = e.source();
+
+    while let Some(cause) = source {
+        if cause.is::<DnsError>() {
+            return FetchErrorKind::Dns;
+        }
+
+        if is_tls_error(cause) {
+            return FetchErrorKind::Tls;
+        }
+
+        if let Some(io) = cause.downcast_ref::<io::Error>() {
+            // A TLS failure can surface as the io error of the stream it happened on
+            if io.get_ref().is_some_and(|inner| is_tls_error(inner)) {
+                return FetchErrorKind::Tls;
+            }
+
+            match io.kind() {
+                ErrorKind::ConnectionRefused => return FetchErrorKind::ConnectionRefused,
+                ErrorKind::TimedOut => return FetchErrorKind::Timeout,
+                _ => {}
+            }
+        }
+
+        source = cause.source();
+    }
+
+    match e.is_connect() {
+        true => FetchErrorKind::Connection,
+        false => FetchErrorKind::Other,
+    }
+}
+
+// Errors of either TLS backend reqwest may be built with
+fn is_tls_error(e: &(dyn std::error::Error + 'static)) -> bool {
+    e.is::<native_tls::Error>() || e.is::<rustls::Error>()
+}
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use cdrs_tokio::{query::QueryValues, query_values};
 use chrono::{DateTime, Utc};
 
@@ -13,6 +14,8 @@ use crate::types::{
 // Used to bootstrap importance before sufficient crawl data exists.
 #[derive(Debug, Clone, PartialEq)]
 pub struct DomainAuthorityPrior {
+    // Crawl the row belongs to
+    pub task_id: String,
     // Hash of eTLD+1
     pub domain_key: Vec<u8>,
     // Authority prior score
@@ -21,27 +24,38 @@ pub struct DomainAuthorityPrior {
     pub updated_ts: DateTime<Utc>,
 }
 
+#[async_trait]
 impl Signal for DomainAuthorityPrior {
+    const TABLE: &'static str = "domain_authority_prior";
+    const PARTITION_KEY: &'static str = "domain_key";
+
     const CREATE_TABLE_QUERY: &'static str = r#"
         CREATE TABLE IF NOT EXISTS domain_authority_prior (
-            domain_key  blob PRIMARY KEY,
+            task_id     text,
+            domain_key  blob,
             authority   double,
-            updated_ts  timestamp
+            updated_ts  timestamp,
+            PRIMARY KEY ((task_id, domain_key))
         )
     "#;
 
     const UPSERT_QUERY: &'static str = r#"
         INSERT INTO domain_authority_prior (
-            domain_key, authority, updated_ts
-        ) VALUES (?, ?, ?)
+            task_id, domain_key, authority, updated_ts
+        ) VALUES (?, ?, ?, ?)
     "#;
 
     async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
         unimplemented!()
     }
 
+    fn partition(&self) -> (&str, &[u8]) {
+        (&self.task_id, &self.domain_key)
+    }
+
     fn bind_values(&self) -> QueryValues {
         query_values!(
+            self.task_id.clone(),
             self.domain_key.clone(),
             self.authority,
             self.updated_ts.naive_utc()
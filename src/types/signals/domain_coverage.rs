@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use cdrs_tokio::{query::QueryValues, query_values};
 use chrono::{DateTime, Utc};
 
@@ -14,6 +15,8 @@ use crate::types::{
 // for crawl balancing and saturation detection.
 #[derive(Debug, Clone, PartialEq)]
 pub struct DomainCoverage {
+    // Crawl the row belongs to
+    pub task_id: String,
     // Hash of eTLD+1
     pub domain_key: Vec<u8>,
     // HyperLogLog sketch of discovered URLs
@@ -24,28 +27,39 @@ pub struct DomainCoverage {
     pub last_update_ts: DateTime<Utc>,
 }
 
+#[async_trait]
 impl Signal for DomainCoverage {
+    const TABLE: &'static str = "domain_coverage";
+    const PARTITION_KEY: &'static str = "domain_key";
+
     const CREATE_TABLE_QUERY: &'static str = r#"
         CREATE TABLE IF NOT EXISTS domain_coverage (
-            domain_key      blob PRIMARY KEY,
+            task_id         text,
+            domain_key      blob,
             hll_discovered  blob,
             hll_fetched     blob,
-            last_update_ts  timestamp
+            last_update_ts  timestamp,
+            PRIMARY KEY ((task_id, domain_key))
         )
     "#;
 
     const UPSERT_QUERY: &'static str = r#"
         INSERT INTO domain_coverage (
-            domain_key, hll_discovered, hll_fetched, last_update_ts
-        ) VALUES (?, ?, ?, ?)
+            task_id, domain_key, hll_discovered, hll_fetched, last_update_ts
+        ) VALUES (?, ?, ?, ?, ?)
     "#;
 
     async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
         unimplemented!()
     }
 
+    fn partition(&self) -> (&str, &[u8]) {
+        (&self.task_id, &self.domain_key)
+    }
+
     fn bind_values(&self) -> QueryValues {
         query_values!(
+            self.task_id.clone(),
             self.domain_key.clone(),
             self.hll_discovered.clone(),
             self.hll_fetched.clone(),
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use cdrs_tokio::{query::QueryValues, query_values};
 use chrono::{DateTime, Utc};
 
@@ -14,6 +15,8 @@ use crate::types::{
 // only one worker fetches a host at a time.
 #[derive(Debug, Clone, PartialEq)]
 pub struct HostGate {
+    // Crawl the row belongs to
+    pub task_id: String,
     // Hash of the host (scheme+host+port)
     pub host_key: Vec<u8>,
     // Earliest time at which this host may be fetched again
@@ -24,28 +27,39 @@ pub struct HostGate {
     pub lease_owner: String,
 }
 
+#[async_trait]
 impl Signal for HostGate {
+    const TABLE: &'static str = "host_gate";
+    const PARTITION_KEY: &'static str = "host_key";
+
     const CREATE_TABLE_QUERY: &'static str = r#"
         CREATE TABLE IF NOT EXISTS host_gate (
-            host_key         blob PRIMARY KEY,
+            task_id          text,
+            host_key         blob,
             next_allowed_ts  timestamp,
             lease_until_ts   timestamp,
-            lease_owner      text
+            lease_owner      text,
+            PRIMARY KEY ((task_id, host_key))
         )
     "#;
 
     const UPSERT_QUERY: &'static str = r#"
         INSERT INTO host_gate (
-            host_key, next_allowed_ts, lease_until_ts, lease_owner
-        ) VALUES (?, ?, ?, ?)
+            task_id, host_key, next_allowed_ts, lease_until_ts, lease_owner
+        ) VALUES (?, ?, ?, ?, ?)
     "#;
 
     async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
         unimplemented!()
     }
 
+    fn partition(&self) -> (&str, &[u8]) {
+        (&self.task_id, &self.host_key)
+    }
+
     fn bind_values(&self) -> QueryValues {
         query_values!(
+            self.task_id.clone(),
             self.host_key.clone(),
             self.next_allowed_ts.naive_utc(),
             self.lease_until_ts.naive_utc(),
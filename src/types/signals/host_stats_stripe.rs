@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use cdrs_tokio::{query::QueryValues, query_values};
 use chrono::{DateTime, Utc};
 
@@ -14,6 +15,8 @@ use crate::types::{
 // hot partitions; stripes are merged at read time.
 #[derive(Debug, Clone, PartialEq)]
 pub struct HostStatsStripe {
+    // Crawl the row belongs to
+    pub task_id: String,
     // Hash of the host (scheme+host+port)
     pub host_key: Vec<u8>,
     // Stripe index for avoiding hot partitions
@@ -44,9 +47,14 @@ pub struct HostStatsStripe {
     pub redirect_ema: f64,
 }
 
+#[async_trait]
 impl Signal for HostStatsStripe {
+    const TABLE: &'static str = "host_stats_stripe";
+    const PARTITION_KEY: &'static str = "host_key";
+
     const CREATE_TABLE_QUERY: &'static str = r#"
         CREATE TABLE IF NOT EXISTS host_stats_stripe (
+            task_id           text,
             host_key          blob,
             stripe            tinyint,
             last_update_ts    timestamp,
@@ -61,28 +69,33 @@ impl Signal for HostStatsStripe {
             dup_outlink_ema   double,
             novel_outlink_ema double,
             redirect_ema      double,
-            PRIMARY KEY ((host_key), stripe)
+            PRIMARY KEY ((task_id, host_key), stripe)
         )
     "#;
 
     const UPSERT_QUERY: &'static str = r#"
         INSERT INTO host_stats_stripe (
-            host_key, stripe,
+            task_id, host_key, stripe,
             last_update_ts,
             latency_ms_ema, bytes_ema,
             http2xx_ema, http3xx_ema, http4xx_ema, http5xx_ema, http429_ema,
             timeout_ema,
             dup_outlink_ema, novel_outlink_ema,
             redirect_ema
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
     "#;
 
     async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
         unimplemented!()
     }
 
+    fn partition(&self) -> (&str, &[u8]) {
+        (&self.task_id, &self.host_key)
+    }
+
     fn bind_values(&self) -> QueryValues {
         query_values!(
+            self.task_id.clone(),
             self.host_key.clone(),
             self.stripe,
             self.last_update_ts.naive_utc(),
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use cdrs_tokio::{query::QueryValues, query_values};
 use chrono::{DateTime, Utc};
 
@@ -14,6 +15,8 @@ use crate::types::{
 // keyed by target and kind.
 #[derive(Debug, Clone, PartialEq)]
 pub struct InlinkAgg {
+    // Crawl the row belongs to
+    pub task_id: String,
     // Hash of target entity (url_key OR host_key OR domain_key)
     pub target_key: Vec<u8>,
     // Kind of target (0=url,1=host,2=domain)
@@ -26,32 +29,42 @@ pub struct InlinkAgg {
     pub last_update_ts: DateTime<Utc>,
 }
 
+#[async_trait]
 impl Signal for InlinkAgg {
+    const TABLE: &'static str = "inlink_agg";
+    const PARTITION_KEY: &'static str = "target_key";
+
     const CREATE_TABLE_QUERY: &'static str = r#"
         CREATE TABLE IF NOT EXISTS inlink_agg (
+            task_id        text,
             target_key     blob,
             kind           tinyint,
             inlinks_ema    double,
             w_inlinks_ema  double,
             last_update_ts timestamp,
-            PRIMARY KEY ((target_key), kind)
+            PRIMARY KEY ((task_id, target_key), kind)
         )
     "#;
 
     const UPSERT_QUERY: &'static str = r#"
         INSERT INTO inlink_agg (
-            target_key, kind,
+            task_id, target_key, kind,
             inlinks_ema, w_inlinks_ema,
             last_update_ts
-        ) VALUES (?, ?, ?, ?, ?)
+        ) VALUES (?, ?, ?, ?, ?, ?)
     "#;
 
     async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
         unimplemented!()
     }
 
+    fn partition(&self) -> (&str, &[u8]) {
+        (&self.task_id, &self.target_key)
+    }
+
     fn bind_values(&self) -> QueryValues {
         query_values!(
+            self.task_id.clone(),
             self.target_key.clone(),
             self.kind,
             self.inlinks_ema,
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use cdrs_tokio::{query::QueryValues, query_values};
 use chrono::{DateTime, Utc};
 
@@ -14,6 +15,8 @@ use crate::types::{
 // and adjust crawl priority accordingly.
 #[derive(Debug, Clone, PartialEq)]
 pub struct PrefixStats {
+    // Crawl the row belongs to
+    pub task_id: String,
     // Hash of the host (scheme+host+port)
     pub host_key: Vec<u8>,
     // Hash of the prefix/template id
@@ -30,9 +33,14 @@ pub struct PrefixStats {
     pub variance_ema: f64,
 }
 
+#[async_trait]
 impl Signal for PrefixStats {
+    const TABLE: &'static str = "prefix_stats";
+    const PARTITION_KEY: &'static str = "host_key";
+
     const CREATE_TABLE_QUERY: &'static str = r#"
         CREATE TABLE IF NOT EXISTS prefix_stats (
+            task_id         text,
             host_key        blob,
             prefix_key      blob,
             last_update_ts  timestamp,
@@ -40,24 +48,29 @@ impl Signal for PrefixStats {
             novelty_ema     double,
             near_dup_ema    double,
             variance_ema    double,
-            PRIMARY KEY ((host_key), prefix_key)
+            PRIMARY KEY ((task_id, host_key), prefix_key)
         )
     "#;
 
     const UPSERT_QUERY: &'static str = r#"
         INSERT INTO prefix_stats (
-            host_key, prefix_key,
+            task_id, host_key, prefix_key,
             last_update_ts,
             dup_page_ema, novelty_ema, near_dup_ema, variance_ema
-        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
     "#;
 
     async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
         unimplemented!()
     }
 
+    fn partition(&self) -> (&str, &[u8]) {
+        (&self.task_id, &self.host_key)
+    }
+
     fn bind_values(&self) -> QueryValues {
         query_values!(
+            self.task_id.clone(),
             self.host_key.clone(),
             self.prefix_key.clone(),
             self.last_update_ts.naive_utc(),
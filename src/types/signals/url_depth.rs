@@ -1,7 +1,9 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use cdrs_tokio::{query::QueryValues, query_values};
 use chrono::{DateTime, Utc};
+use xxhrs::XXH3_128;
 
 use crate::types::{
     error::AppError,
@@ -13,6 +15,8 @@ use crate::types::{
 // Records how far a URL is from initial seeds and when it was first seen.
 #[derive(Debug, Clone, PartialEq)]
 pub struct UrlDepth {
+    // Crawl the row belongs to
+    pub task_id: String,
     // Hash of the URL
     pub url_key: Vec<u8>,
     // Depth from seed
@@ -21,27 +25,44 @@ pub struct UrlDepth {
     pub discovered_ts: DateTime<Utc>,
 }
 
+#[async_trait]
 impl Signal for UrlDepth {
+    const TABLE: &'static str = "url_depth";
+    const PARTITION_KEY: &'static str = "url_key";
+
     const CREATE_TABLE_QUERY: &'static str = r#"
         CREATE TABLE IF NOT EXISTS url_depth (
-            url_key        blob PRIMARY KEY,
+            task_id        text,
+            url_key        blob,
             depth          int,
-            discovered_ts  timestamp
+            discovered_ts  timestamp,
+            PRIMARY KEY ((task_id, url_key))
         )
     "#;
 
     const UPSERT_QUERY: &'static str = r#"
         INSERT INTO url_depth (
-            url_key, depth, discovered_ts
-        ) VALUES (?, ?, ?)
+            task_id, url_key, depth, discovered_ts
+        ) VALUES (?, ?, ?, ?)
     "#;
 
-    async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
-        unimplemented!()
+    async fn from_record(_session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
+        Ok(vec![Self {
+            url_key: XXH3_128::hash(record.uri.as_bytes()).to_be_bytes().to_vec(),
+            depth: i32::try_from(record.depth).unwrap_or(i32::MAX),
+            // Records queued before lineage existed have no discovery time
+            discovered_ts: record.discovered_at.unwrap_or_else(Utc::now),
+            task_id: record.task_id,
+        }])
+    }
+
+    fn partition(&self) -> (&str, &[u8]) {
+        (&self.task_id, &self.url_key)
     }
 
     fn bind_values(&self) -> QueryValues {
         query_values!(
+            self.task_id.clone(),
             self.url_key.clone(),
             self.depth,
             self.discovered_ts.naive_utc()
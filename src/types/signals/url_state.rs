@@ -1,5 +1,6 @@
 use std::{str::FromStr, sync::Arc};
 
+use async_trait::async_trait;
 use cdrs_tokio::types::IntoRustByName;
 use cdrs_tokio::{query::QueryValues, query_values};
 use chrono::{DateTime, Utc};
@@ -20,6 +21,8 @@ use crate::{
 // freshness decisions, and spam suppression.
 #[derive(Debug, Clone, PartialEq)]
 pub struct UrlState {
+    // Crawl the row belongs to
+    pub task_id: String,
     // Hash of the URL
     pub url_key: Vec<u8>,
     // Hash of the host (scheme+host+port)
@@ -65,6 +68,7 @@ impl UrlState {
 
     pub async fn get_latest(
         session: Arc<DbSession>,
+        task_id: String,
         url_key: Vec<u8>,
         host_key: Vec<u8>,
         site_key: Vec<u8>,
@@ -82,18 +86,19 @@ impl UrlState {
                 latency_ms_ema,
                 bytes_ema
             FROM url_state
-            WHERE url_key = ?
+            WHERE task_id = ? AND url_key = ?
         "#;
 
         let prepared = session.prepare(Q).await?;
         let result = session
-            .exec_with_values(&prepared, query_values!(url_key.clone()))
+            .exec_with_values(&prepared, query_values!(task_id.clone(), url_key.clone()))
             .await?;
 
         let row = match result.response_body()?.into_rows() {
             Some(mut rows) if !rows.is_empty() => rows.remove(0),
             _ => {
                 return Ok(UrlState {
+                    task_id,
                     url_key,
                     host_key,
                     site_key,
@@ -134,6 +139,7 @@ impl UrlState {
         let bytes_ema: Option<f64> = row.get_by_name("bytes_ema")?;
 
         Ok(Self {
+            task_id,
             url_key,
             host_key,
             site_key,
@@ -151,10 +157,15 @@ impl UrlState {
     }
 }
 
+#[async_trait]
 impl Signal for UrlState {
+    const TABLE: &'static str = "url_state";
+    const PARTITION_KEY: &'static str = "url_key";
+
     const CREATE_TABLE_QUERY: &'static str = r#"
         CREATE TABLE IF NOT EXISTS url_state (
-            url_key         blob PRIMARY KEY,
+            task_id         text,
+            url_key         blob,
             host_key        blob,
             site_key        blob,
             last_fetch_ts   timestamp,
@@ -166,12 +177,14 @@ impl Signal for UrlState {
             soft404_ema     double,
             thin_ema        double,
             latency_ms_ema  double,
-            bytes_ema       double
+            bytes_ema       double,
+            PRIMARY KEY ((task_id, url_key))
         )
     "#;
 
     const UPSERT_QUERY: &'static str = r#"
         INSERT INTO url_state (
+            task_id,
             url_key,
             host_key,
             site_key,
@@ -185,7 +198,7 @@ impl Signal for UrlState {
             thin_ema,
             latency_ms_ema,
             bytes_ema
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
     "#;
 
     async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError> {
@@ -196,7 +209,8 @@ impl Signal for UrlState {
         let url_key = XXH3_128::hash(record.uri.as_bytes()).to_be_bytes().to_vec();
         let host_key = XXH3_128::hash(host.as_bytes()).to_be_bytes().to_vec();
         let site_key = XXH3_128::hash(site.as_bytes()).to_be_bytes().to_vec();
-        let latest = Self::get_latest(session, url_key, host_key, site_key).await?;
+        let latest =
+            Self::get_latest(session, record.task_id.clone(), url_key, host_key, site_key).await?;
 
         for m in record.metadata {
             let RecordMetadata::HttpResponse(resp) = m else {
@@ -230,8 +244,13 @@ impl Signal for UrlState {
         unimplemented!();
     }
 
+    fn partition(&self) -> (&str, &[u8]) {
+        (&self.task_id, &self.url_key)
+    }
+
     fn bind_values(&self) -> QueryValues {
         query_values!(
+            self.task_id.clone(),
             self.url_key.clone(),
             self.host_key.clone(),
             self.site_key.clone(),
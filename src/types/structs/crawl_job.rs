@@ -0,0 +1,160 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{configs::job_config::JobConfig, error::AppError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Created,
+    Running,
+    Paused,
+    Finished,
+    Failed,
+}
+
+impl JobState {
+    pub fn can_become(self, next: JobState) -> bool {
+        use JobState::*;
+
+        matches!(
+            (self, next),
+            (Created, Running | Failed)
+                | (Running, Paused | Finished | Failed)
+                | (Paused, Running | Finished | Failed)
+                // Seeding a job that already ran crawls it again
+                | (Finished | Failed, Running)
+        )
+    }
+}
+
+impl fmt::Display for JobState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            JobState::Created => "created",
+            JobState::Running => "running",
+            JobState::Paused => "paused",
+            JobState::Finished => "finished",
+            JobState::Failed => "failed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// Totals over every run of a job
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobCounters {
+    pub runs: u64,
+    pub pages: u64,
+    pub failures: u64,
+    pub dead_lettered: u64,
+    pub discovered: u64,
+    pub admitted: u64,
+}
+
+// A crawl under one task id, with the overrides it runs with and where it is in its
+// lifecycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlJob {
+    pub task_id: String,
+    pub config: JobConfig,
+    pub state: JobState,
+    pub counters: JobCounters,
+    // Why the last run failed
+    pub error: Option<String>,
+    // Whether the last run was cancelled before its frontier ran dry
+    pub interrupted: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl CrawlJob {
+    pub fn new(task_id: &str, config: JobConfig) -> Self {
+        let now = Utc::now();
+
+        Self {
+            task_id: task_id.to_string(),
+            config,
+            state: JobState::Created,
+            counters: JobCounters::default(),
+            error: None,
+            interrupted: false,
+            created_at: now,
+            updated_at: now,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    pub fn transition(&mut self, next: JobState) -> Result<(), AppError> {
+        if !self.state.can_become(next) {
+            return Err(AppError::InvalidJobTransition {
+                task_id: self.task_id.clone(),
+                from: self.state,
+                to: next,
+            });
+        }
+
+        let now = Utc::now();
+
+        match next {
+            JobState::Running if self.state != JobState::Paused => {
+                self.started_at = Some(now);
+                self.finished_at = None;
+                self.error = None;
+                self.interrupted = false;
+                self.counters.runs += 1;
+            }
+            JobState::Finished | JobState::Failed => self.finished_at = Some(now),
+            _ => {}
+        }
+
+        self.state = next;
+        self.updated_at = now;
+
+        Ok(())
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, JobState::Running | JobState::Paused)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifecycle() {
+        let mut job = CrawlJob::new("job", JobConfig::default());
+
+        assert!(job.transition(JobState::Paused).is_err());
+
+        job.transition(JobState::Running).unwrap();
+        job.transition(JobState::Paused).unwrap();
+        job.transition(JobState::Running).unwrap();
+
+        assert_eq!(job.counters.runs, 1);
+
+        job.transition(JobState::Finished).unwrap();
+
+        assert!(job.finished_at.is_some());
+
+        let err = job.transition(JobState::Paused).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "crawl job job can't go from finished to paused"
+        );
+
+        job.transition(JobState::Running).unwrap();
+
+        assert_eq!(job.counters.runs, 2);
+        assert!(job.finished_at.is_none());
+    }
+}
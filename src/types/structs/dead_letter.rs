@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::structs::record::Record;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub record: Record,
+    // Number of deliveries made before giving up
+    pub attempts: u32,
+    pub reason: String,
+    // Every failed attempt, oldest first. Empty when the queue gave up on the message
+    // without a task reporting an error.
+    #[serde(default)]
+    pub errors: Vec<AttemptError>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptError {
+    pub task: String,
+    pub attempt: u32,
+    pub error: String,
+    pub retryable: bool,
+    pub timestamp: DateTime<Utc>,
+}
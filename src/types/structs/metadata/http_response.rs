@@ -1,21 +1,216 @@
 use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpRequest {
     pub method: String,
     pub request_headers: HashMap<String, String>,
     pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpResponse {
     pub status: Option<i64>,
     pub request: HttpRequest,
     pub response_headers: HashMap<String, String>,
     pub key: Option<String>,
-    pub error: Option<String>,
+    #[serde(default, deserialize_with = "fetch_error_or_message")]
+    pub error: Option<FetchError>,
     pub timestamp: Option<DateTime<Utc>>,
     pub minhash: Option<Vec<u64>>,
+    // The server answered a conditional request with 304, so `key` and `minhash` are
+    // carried over from the previous fetch instead of a new body being stored
+    #[serde(default)]
+    pub unchanged: bool,
+    // Redirects followed to get here, in order
+    #[serde(default)]
+    pub redirects: Vec<RedirectHop>,
+    // Url the response came from, which differs from the record's after a redirect
+    #[serde(default)]
+    pub final_uri: Option<String>,
+    // Only the first part of the body was stored, the rest was over the size limit
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default)]
+    pub timing: FetchTiming,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchErrorKind {
+    Dns,
+    ConnectionRefused,
+    // Any other failure to connect, like a reset or an unreachable network
+    Connection,
+    Tls,
+    Timeout,
+    BodyRead,
+    TooManyRedirects,
+    // The fetcher's own policy turned the response down, like a content type filter
+    BlockedByPolicy,
+    BodyTooLarge,
+    InvalidUrl,
+    Other,
+}
+
+impl FetchErrorKind {
+    // Failures that fetching again later might get past. A name that doesn't resolve or a
+    // certificate that doesn't verify will fail the same way next time.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            FetchErrorKind::ConnectionRefused
+                | FetchErrorKind::Connection
+                | FetchErrorKind::Timeout
+                | FetchErrorKind::BodyRead
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FetchError {
+    pub kind: FetchErrorKind,
+    pub message: String,
+}
+
+impl FetchError {
+    pub fn new(kind: FetchErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    // Classifies the `net::ERR_*` codes chromium reports for failed loads
+    pub fn from_net_error(text: &str) -> Self {
+        let code = text.trim_start_matches("net::");
+        let kind = match code {
+            "ERR_NAME_NOT_RESOLVED" | "ERR_NAME_RESOLUTION_FAILED" => FetchErrorKind::Dns,
+            "ERR_CONNECTION_REFUSED" => FetchErrorKind::ConnectionRefused,
+            "ERR_TIMED_OUT" | "ERR_CONNECTION_TIMED_OUT" => FetchErrorKind::Timeout,
+            "ERR_TOO_MANY_REDIRECTS" => FetchErrorKind::TooManyRedirects,
+            "ERR_INVALID_URL" => FetchErrorKind::InvalidUrl,
+            c if c.starts_with("ERR_CERT_") || c.starts_with("ERR_SSL_") => FetchErrorKind::Tls,
+            c if c.starts_with("ERR_BLOCKED_") => FetchErrorKind::BlockedByPolicy,
+            c if c.starts_with("ERR_CONNECTION_")
+                || c.starts_with("ERR_ADDRESS_")
+                || c.starts_with("ERR_NETWORK_")
+                || c == "ERR_INTERNET_DISCONNECTED" =>
+            {
+                FetchErrorKind::Connection
+            }
+            "ERR_CONTENT_LENGTH_MISMATCH" | "ERR_INCOMPLETE_CHUNKED_ENCODING" => {
+                FetchErrorKind::BodyRead
+            }
+            _ => FetchErrorKind::Other,
+        };
+
+        Self::new(kind, text)
+    }
+}
+
+// Records from before version 8 carry the error as a bare message
+fn fetch_error_or_message<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<FetchError>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Message(String),
+        Error(FetchError),
+    }
+
+    Ok(
+        Option::<Repr>::deserialize(deserializer)?.map(|repr| match repr {
+            Repr::Message(message) => FetchError::new(FetchErrorKind::Other, message),
+            Repr::Error(error) => error,
+        }),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedirectHop {
+    pub uri: String,
+    pub status: i64,
+    pub location: String,
+}
+
+// Measured while fetching, so latency and cost estimates don't have to work from the
+// request and response timestamps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FetchTiming {
+    // Both counted from when the first request went out, so redirects are included
+    pub time_to_headers_ms: Option<u64>,
+    pub time_to_last_byte_ms: Option<u64>,
+    // Bytes read off the wire for the response, before anything is cut for a size limit
+    pub bytes: Option<u64>,
+    pub remote_ip: Option<String>,
+}
+
+// What a previous fetch of a url left behind that lets the next one be conditional
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub key: Option<String>,
+    pub minhash: Option<Vec<u64>>,
+}
+
+impl HttpResponse {
+    // A fetch that ended before any response came back
+    pub fn failed(error: FetchError, request_timestamp: DateTime<Utc>) -> Self {
+        HttpResponse {
+            status: None,
+            request: HttpRequest {
+                method: "GET".to_string(),
+                request_headers: HashMap::new(),
+                timestamp: request_timestamp,
+            },
+            response_headers: HashMap::new(),
+            key: None,
+            error: Some(error),
+            timestamp: None,
+            minhash: None,
+            unchanged: false,
+            redirects: vec![],
+            final_uri: None,
+            truncated: false,
+            timing: FetchTiming::default(),
+        }
+    }
+
+    // Where the last redirect pointed if the fetch stopped there instead of following it
+    pub fn unfollowed_redirect(&self) -> Option<&str> {
+        self.redirects
+            .last()
+            .map(|hop| hop.location.as_str())
+            .filter(|location| self.final_uri.as_deref() != Some(*location))
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.response_headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    // Only successful responses whose body was stored are worth revalidating, a 304
+    // without a body to fall back on would leave the record empty
+    pub fn validators(&self) -> Option<Validators> {
+        let validators = Validators {
+            etag: self.header("etag").map(str::to_string),
+            last_modified: self.header("last-modified").map(str::to_string),
+            key: self.key.clone(),
+            minhash: self.minhash.clone(),
+        };
+
+        match self.status {
+            Some(200..=299) | Some(304) if validators.key.is_some() => (validators.etag.is_some()
+                || validators.last_modified.is_some())
+            .then_some(validators),
+            _ => None,
+        }
+    }
 }
@@ -1,3 +1,7 @@
+pub mod crawl_job;
+pub mod dead_letter;
 pub mod metadata;
+pub mod queue_message;
 pub mod queue_status;
 pub mod record;
+pub mod seed;
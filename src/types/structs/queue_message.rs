@@ -0,0 +1,12 @@
+use crate::types::structs::record::Record;
+
+#[derive(Clone)]
+pub struct QueueMessage {
+    // Identifies this delivery when acking or nacking it
+    pub id: String,
+    pub record: Record,
+    // Number of times the message has been delivered, including this one
+    pub attempts: u32,
+    // What it was queued with; always 0 from queues that deliver in arrival order
+    pub priority: i64,
+}
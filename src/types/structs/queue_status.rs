@@ -1 +1,92 @@
-pub struct QueueStatus {}
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use serde::{Serialize, Serializer};
+
+// Span that enqueue and dequeue rates are averaged over
+pub const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct QueueStatus {
+    // Messages waiting to be delivered
+    pub depth: u64,
+    // Messages leased to a consumer and not yet acked
+    pub in_flight: u64,
+    // Messages that ran out of attempts
+    pub dead_lettered: u64,
+    // Time since the oldest waiting or leased message was first enqueued
+    #[serde(serialize_with = "as_secs")]
+    pub oldest_age: Option<Duration>,
+    // Messages per second over the last `RATE_WINDOW`
+    pub enqueue_rate: f64,
+    pub dequeue_rate: f64,
+    pub tasks: BTreeMap<String, TaskQueueStatus>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TaskQueueStatus {
+    pub depth: u64,
+    pub in_flight: u64,
+    pub dead_lettered: u64,
+}
+
+impl QueueStatus {
+    pub fn task_mut(&mut self, task_id: &str) -> &mut TaskQueueStatus {
+        self.tasks.entry(task_id.to_string()).or_default()
+    }
+
+    // Adds the counts of a queue covering other tasks
+    pub fn merge(&mut self, other: QueueStatus) {
+        self.depth += other.depth;
+        self.in_flight += other.in_flight;
+        self.dead_lettered += other.dead_lettered;
+        self.enqueue_rate += other.enqueue_rate;
+        self.dequeue_rate += other.dequeue_rate;
+
+        if let Some(age) = other.oldest_age {
+            self.observe_age(age);
+        }
+
+        for (task_id, status) in other.tasks {
+            let task = self.task_mut(&task_id);
+
+            task.depth += status.depth;
+            task.in_flight += status.in_flight;
+            task.dead_lettered += status.dead_lettered;
+        }
+    }
+
+    // Folds the age of another message into `oldest_age`
+    pub fn observe_age(&mut self, age: Duration) {
+        self.oldest_age = Some(self.oldest_age.map_or(age, |a| a.max(age)));
+    }
+}
+
+fn as_secs<S: Serializer>(age: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+    age.map(|a| a.as_secs_f64()).serialize(serializer)
+}
+
+impl fmt::Display for QueueStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "depth: {}", self.depth)?;
+        writeln!(f, "in flight: {}", self.in_flight)?;
+        writeln!(f, "dead lettered: {}", self.dead_lettered)?;
+
+        match self.oldest_age {
+            Some(age) => writeln!(f, "oldest message age: {}s", age.as_secs())?,
+            None => writeln!(f, "oldest message age: -")?,
+        }
+
+        writeln!(f, "enqueue rate: {:.2}/s", self.enqueue_rate)?;
+        write!(f, "dequeue rate: {:.2}/s", self.dequeue_rate)?;
+
+        for (task_id, status) in &self.tasks {
+            write!(
+                f,
+                "\ntask {}: depth {}, in flight {}, dead lettered {}",
+                task_id, status.depth, status.in_flight, status.dead_lettered
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,14 +1,367 @@
-use crate::types::structs::metadata::{http_response::HttpResponse, uris::Uris};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-#[derive(Clone)]
+use crate::types::{
+    error::AppError,
+    structs::metadata::{
+        http_response::{FetchError, HttpResponse},
+        uris::Uris,
+    },
+};
+
+// Wire format version written by this build. Bump it whenever `Record` or anything it
+// contains changes shape, and teach `Record::decode` how to read the previous version.
+pub const RECORD_VERSION: u16 = 10;
+
+// First byte of a binary envelope, chosen so it can never start a JSON document
+const BINARY_MAGIC: u8 = 0xae;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Record {
     pub uri: String,
     pub task_id: String,
     pub metadata: Vec<RecordMetadata>,
+    // Record whose links this one was spawned from, none for seeds
+    #[serde(default)]
+    pub parent_uri: Option<String>,
+    // Hops from the seed
+    #[serde(default)]
+    pub depth: u32,
+    // Depth limit of the seed this record descends from, on top of the crawl's budget
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    // Seed this record descends from, none for records queued before lineage existed
+    #[serde(default)]
+    pub seed_uri: Option<String>,
+    // How this record was reached from its parent
+    #[serde(default)]
+    pub hop: HopType,
+    // When the link to this record was found
+    #[serde(default)]
+    pub discovered_at: Option<DateTime<Utc>>,
+    // Times a throttling host sent this record back to the queue
+    #[serde(default)]
+    pub deferrals: u32,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HopType {
+    // Submitted directly rather than discovered
+    #[default]
+    Seed,
+    // An anchor or bare url in the parent's body
+    Link,
+    // The parent answered with a redirect to this uri
+    Redirect,
+    // Listed in a sitemap
+    Sitemap,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RecordMetadata {
-    HttpResponse(HttpResponse),
+    // Boxed to keep records of only uris small
+    HttpResponse(Box<HttpResponse>),
     Uris(Uris),
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordEncoding {
+    // `{"version": 1, "record": {...}}`, for debugging
+    Json,
+    // Magic byte, big endian u16 version, then the record as MessagePack
+    #[default]
+    Binary,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u16,
+    record: &'a Record,
+}
+
+impl Record {
+    // Why the fetch whose response was appended last failed, if it did
+    pub fn fetch_error(&self) -> Option<&FetchError> {
+        match self.metadata.last()? {
+            RecordMetadata::HttpResponse(r) => r.error.as_ref(),
+            _ => None,
+        }
+    }
+
+    // A new record for a uri reached from this one, one hop further from the seed
+    pub fn child(&self, uri: String, hop: HopType) -> Self {
+        Self {
+            uri,
+            task_id: self.task_id.clone(),
+            metadata: vec![],
+            parent_uri: Some(self.uri.clone()),
+            depth: self.depth + 1,
+            max_depth: self.max_depth,
+            seed_uri: Some(self.seed_uri.clone().unwrap_or_else(|| self.uri.clone())),
+            hop,
+            discovered_at: Some(Utc::now()),
+            deferrals: 0,
+        }
+    }
+
+    pub fn encode(&self, encoding: RecordEncoding) -> Result<Vec<u8>, AppError> {
+        match encoding {
+            RecordEncoding::Json => Ok(serde_json::to_vec(&Envelope {
+                version: RECORD_VERSION,
+                record: self,
+            })?),
+            RecordEncoding::Binary => {
+                let mut bytes = vec![BINARY_MAGIC];
+
+                bytes.extend(RECORD_VERSION.to_be_bytes());
+                bytes.extend(rmp_serde::to_vec(self)?);
+
+                Ok(bytes)
+            }
+        }
+    }
+
+    // Reads either encoding. Envelopes from a newer version are rejected rather than
+    // guessed at; older ones are upgraded.
+    pub fn decode(bytes: &[u8]) -> Result<Self, AppError> {
+        match bytes {
+            [BINARY_MAGIC, hi, lo, payload @ ..] => {
+                let version = check_version(u16::from_be_bytes([*hi, *lo]))?;
+
+                match version {
+                    // Versions 2 to 10 only appended defaulted fields to version 1, apart
+                    // from 8 turning response errors from messages into `FetchError`s,
+                    // which reads either
+                    1..=10 => Ok(rmp_serde::from_slice(payload)?),
+                    v => Err(AppError::MalformedRecord(format!(
+                        "no binary encoding for version {}",
+                        v
+                    ))),
+                }
+            }
+            [BINARY_MAGIC, ..] => Err(AppError::MalformedRecord(
+                "truncated binary envelope".to_string(),
+            )),
+            _ => decode_json(serde_json::from_slice(bytes)?),
+        }
+    }
+}
+
+fn decode_json(value: Value) -> Result<Record, AppError> {
+    let (version, record) = match value {
+        Value::Object(mut envelope) if envelope.contains_key("version") => {
+            let version = envelope
+                .get("version")
+                .and_then(Value::as_u64)
+                .and_then(|v| u16::try_from(v).ok())
+                .ok_or_else(|| AppError::MalformedRecord("invalid version".to_string()))?;
+            let record = envelope
+                .remove("record")
+                .ok_or_else(|| AppError::MalformedRecord("missing record".to_string()))?;
+
+            (version, record)
+        }
+        // Records queued before envelopes existed are bare objects
+        record => (0, record),
+    };
+
+    check_version(version)?;
+
+    // Every version so far only added defaulted fields or, like 8, reads the old shape of
+    // what it changed, so there is nothing to upgrade
+    Ok(serde_json::from_value(record)?)
+}
+
+fn check_version(version: u16) -> Result<u16, AppError> {
+    match version {
+        v if v > RECORD_VERSION => Err(AppError::UnsupportedRecordVersion {
+            version: v,
+            supported: RECORD_VERSION,
+        }),
+        v => Ok(v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::types::structs::metadata::http_response::{
+        FetchError, FetchErrorKind, FetchTiming, HttpRequest,
+    };
+
+    use super::*;
+
+    fn record() -> Record {
+        Record {
+            uri: "http://example.com/".to_string(),
+            task_id: "test".to_string(),
+            metadata: vec![
+                RecordMetadata::HttpResponse(Box::new(HttpResponse {
+                    status: Some(200),
+                    request: HttpRequest {
+                        method: "GET".to_string(),
+                        request_headers: HashMap::from([(
+                            "user-agent".to_string(),
+                            "aetherscope".to_string(),
+                        )]),
+                        timestamp: Utc::now(),
+                    },
+                    response_headers: HashMap::new(),
+                    key: Some("key".to_string()),
+                    error: Some(FetchError::new(FetchErrorKind::Timeout, "timed out")),
+                    timestamp: Some(Utc::now()),
+                    minhash: Some(vec![1, 2, 3]),
+                    unchanged: false,
+                    redirects: vec![],
+                    final_uri: None,
+                    truncated: false,
+                    timing: FetchTiming {
+                        time_to_headers_ms: Some(120),
+                        time_to_last_byte_ms: Some(340),
+                        bytes: Some(2048),
+                        remote_ip: Some("192.0.2.1".to_string()),
+                    },
+                })),
+                RecordMetadata::Uris(Uris {
+                    uris: vec!["http://example.com/a".to_string()],
+                }),
+            ],
+            parent_uri: Some("http://example.com/parent".to_string()),
+            depth: 2,
+            max_depth: None,
+            seed_uri: Some("http://example.com/seed".to_string()),
+            hop: HopType::Link,
+            discovered_at: Some(Utc::now()),
+            deferrals: 0,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let record = record();
+
+        for encoding in [RecordEncoding::Json, RecordEncoding::Binary] {
+            let decoded = Record::decode(&record.encode(encoding).unwrap()).unwrap();
+
+            assert_eq!(decoded.uri, record.uri);
+            assert_eq!(decoded.parent_uri, record.parent_uri);
+            assert_eq!(decoded.depth, 2);
+            assert_eq!(decoded.seed_uri, record.seed_uri);
+            assert_eq!(decoded.hop, HopType::Link);
+            assert_eq!(decoded.discovered_at, record.discovered_at);
+            assert!(matches!(
+                &decoded.metadata[0],
+                RecordMetadata::HttpResponse(r) if r.minhash == Some(vec![1, 2, 3])
+                    && r.error.as_ref().map(|e| e.kind) == Some(FetchErrorKind::Timeout)
+                    && r.timing.bytes == Some(2048)
+            ));
+            assert!(matches!(&decoded.metadata[1], RecordMetadata::Uris(u) if u.uris.len() == 1));
+        }
+
+        let json = record.encode(RecordEncoding::Json).unwrap();
+        let binary = record.encode(RecordEncoding::Binary).unwrap();
+
+        assert!(binary.len() < json.len());
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let mut binary = record().encode(RecordEncoding::Binary).unwrap();
+        binary[1..3].copy_from_slice(&(RECORD_VERSION + 1).to_be_bytes());
+
+        assert!(matches!(
+            Record::decode(&binary).unwrap_err(),
+            AppError::UnsupportedRecordVersion { version, .. } if version == RECORD_VERSION + 1
+        ));
+
+        let json = format!(r#"{{"version": {}, "record": {{}}}}"#, RECORD_VERSION + 1);
+
+        assert!(matches!(
+            Record::decode(json.as_bytes()).unwrap_err(),
+            AppError::UnsupportedRecordVersion { .. }
+        ));
+    }
+
+    #[test]
+    fn test_upgrades_bare_json() {
+        let bare = serde_json::to_vec(&record()).unwrap();
+
+        assert_eq!(Record::decode(&bare).unwrap().task_id, "test");
+    }
+
+    #[test]
+    fn test_upgrades_version_1() {
+        #[derive(Serialize)]
+        struct RecordV1 {
+            uri: String,
+            task_id: String,
+            metadata: Vec<RecordMetadata>,
+        }
+
+        let mut binary = vec![BINARY_MAGIC];
+        binary.extend(1u16.to_be_bytes());
+        binary.extend(
+            rmp_serde::to_vec(&RecordV1 {
+                uri: "http://example.com/".to_string(),
+                task_id: "test".to_string(),
+                metadata: vec![],
+            })
+            .unwrap(),
+        );
+
+        let decoded = Record::decode(&binary).unwrap();
+
+        assert_eq!(decoded.uri, "http://example.com/");
+        assert_eq!(decoded.parent_uri, None);
+        assert_eq!(decoded.depth, 0);
+        assert_eq!(decoded.hop, HopType::Seed);
+        assert_eq!(decoded.discovered_at, None);
+    }
+
+    #[test]
+    fn test_upgrades_error_message() {
+        let mut value = serde_json::to_value(record()).unwrap();
+        value["metadata"][0]["HttpResponse"]["error"] = Value::from("boom");
+
+        let json = serde_json::to_vec(&serde_json::json!({"version": 7, "record": value})).unwrap();
+        let mut binary = vec![BINARY_MAGIC];
+        binary.extend(7u16.to_be_bytes());
+        binary.extend(rmp_serde::to_vec(&value).unwrap());
+
+        for bytes in [json, binary] {
+            let decoded = Record::decode(&bytes).unwrap();
+
+            assert!(matches!(
+                &decoded.metadata[0],
+                RecordMetadata::HttpResponse(r)
+                    if r.error == Some(FetchError::new(FetchErrorKind::Other, "boom"))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_child_lineage() {
+        let seed = Record {
+            seed_uri: None,
+            parent_uri: None,
+            depth: 0,
+            ..record()
+        };
+        let child = seed.child("http://example.com/a".to_string(), HopType::Link);
+        let grandchild = child.child("http://example.com/b".to_string(), HopType::Redirect);
+
+        assert_eq!(child.seed_uri.as_deref(), Some("http://example.com/"));
+        assert_eq!(grandchild.seed_uri.as_deref(), Some("http://example.com/"));
+        assert_eq!(
+            grandchild.parent_uri.as_deref(),
+            Some("http://example.com/a")
+        );
+        assert_eq!(grandchild.depth, 2);
+        assert_eq!(grandchild.hop, HopType::Redirect);
+        assert!(grandchild.discovered_at.is_some());
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::types::structs::record::{HopType, Record};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Seed {
+    pub uri: String,
+    // Dispatched ahead of anything queued with a lower priority, including the links found
+    // along the way, which are queued at 0. Redis queues deliver in arrival order instead.
+    #[serde(default)]
+    pub priority: i64,
+    // Hops the crawl may go from this seed, on top of the crawl's budget
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    // How the seed was found, `sitemap` for pages listed by one
+    #[serde(default)]
+    pub hop: HopType,
+}
+
+impl Seed {
+    pub fn new(uri: &str) -> Self {
+        Self {
+            uri: uri.to_string(),
+            priority: 0,
+            max_depth: None,
+            hop: HopType::Seed,
+        }
+    }
+
+    pub fn record(&self, task_id: &str) -> Record {
+        Record {
+            uri: self.uri.clone(),
+            task_id: task_id.to_string(),
+            metadata: vec![],
+            parent_uri: None,
+            depth: 0,
+            max_depth: self.max_depth,
+            seed_uri: Some(self.uri.clone()),
+            hop: self.hop,
+            discovered_at: Some(Utc::now()),
+            deferrals: 0,
+        }
+    }
+}
@@ -2,10 +2,17 @@ use async_trait::async_trait;
 
 use crate::types::error::AppError;
 
+// Entries live in namespaces, one per crawl, and are only seen by lookups in the same
+// namespace.
 #[async_trait]
-pub trait CheckHashSet {
+pub trait CheckHashSet: Send + Sync {
     async fn contains_entities(
         &self,
+        namespace: &str,
         entities: Vec<String>,
     ) -> Result<Vec<(String, bool)>, AppError>;
+    // Records entities without checking whether they were there already
+    async fn add_entities(&self, namespace: &str, entities: Vec<String>) -> Result<(), AppError>;
+    // Forgets every entry of a namespace
+    async fn purge(&self, namespace: &str) -> Result<(), AppError>;
 }
@@ -0,0 +1,8 @@
+use async_trait::async_trait;
+
+use crate::types::{error::AppError, structs::dead_letter::DeadLetter};
+
+#[async_trait]
+pub trait DeadLetterSink: Send + Sync {
+    async fn put(&self, dead_letter: DeadLetter) -> Result<(), AppError>;
+}
@@ -1,8 +1,12 @@
+use async_trait::async_trait;
+
 use crate::types::error::AppError;
 
+// Returns each uri paired with whether it should be admitted to the frontier.
+#[async_trait]
 pub trait FrontierFilter {
     async fn perform(
-        self,
+        &mut self,
         uris: Vec<String>,
         origin: &str,
     ) -> Result<Vec<(String, bool)>, AppError>;
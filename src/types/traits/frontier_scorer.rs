@@ -1,5 +1,8 @@
+use async_trait::async_trait;
+
 use crate::types::error::AppError;
 
+#[async_trait]
 pub trait FrontierScorer {
     async fn score(self, uris: Vec<String>, origin: &str) -> Result<Vec<(String, i32)>, AppError>;
 }
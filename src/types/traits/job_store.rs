@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use crate::types::{error::AppError, structs::crawl_job::CrawlJob};
+
+#[async_trait]
+pub trait JobStore: Send + Sync {
+    async fn get(&self, task_id: &str) -> Result<Option<CrawlJob>, AppError>;
+    // Inserts the job, or replaces the one with the same task id
+    async fn put(&self, job: &CrawlJob) -> Result<(), AppError>;
+    // Every job, oldest first
+    async fn list(&self) -> Result<Vec<CrawlJob>, AppError>;
+    // Removing a job that doesn't exist is not an error
+    async fn delete(&self, task_id: &str) -> Result<(), AppError>;
+}
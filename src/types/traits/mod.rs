@@ -1,7 +1,11 @@
 pub mod check_hash_set;
+pub mod dead_letter_sink;
 pub mod frontier_filter;
 pub mod frontier_scorer;
+pub mod job_store;
 pub mod object_store;
+pub mod politeness_gate;
 pub mod queue;
 pub mod signal;
 pub mod task;
+pub mod validate;
@@ -25,4 +25,6 @@ pub trait ObjectStore: Send + Sync {
         key: &str,
     ) -> Result<Box<dyn AsyncReadSeek + Send + Unpin>, AppError>;
     async fn delete(&self, key: &str) -> Result<(), AppError>;
+    // Deletes every object whose key starts with `prefix`
+    async fn delete_prefix(&self, prefix: &str) -> Result<(), AppError>;
 }
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::types::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lease {
+    Granted,
+    // Someone else holds the host, or it was fetched too recently
+    Wait(Duration),
+}
+
+// Hands out per-host fetch leases, one holder per host at a time. Hosts live in
+// namespaces, one per crawl, like `HostGate` rows.
+#[async_trait]
+pub trait PolitenessGate: Send + Sync {
+    async fn try_acquire(
+        &self,
+        namespace: &str,
+        host: &str,
+        owner: &str,
+        lease: Duration,
+    ) -> Result<Lease, AppError>;
+    // Gives the lease back. The host can't be leased again for `delay`, even if `owner`
+    // lost the lease in the meantime.
+    async fn release(
+        &self,
+        namespace: &str,
+        host: &str,
+        owner: &str,
+        delay: Duration,
+    ) -> Result<(), AppError>;
+}
@@ -1,16 +1,34 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
 use crate::types::{
     error::AppError,
-    structs::{queue_status::QueueStatus, record::Record},
+    structs::{queue_message::QueueMessage, queue_status::QueueStatus, record::Record},
 };
 
-pub trait QueueGettable {
-    async fn get() -> Result<Record, AppError>;
-}
-
-pub trait QueuePuttable {
-    async fn put(record: Record) -> Result<(), AppError>;
-}
-
-pub trait QueueStatusReadable {
-    async fn status() -> Result<QueueStatus, AppError>;
+#[async_trait]
+pub trait Queue: Send + Sync {
+    async fn put(&self, record: Record) -> Result<(), AppError>;
+    // Higher priorities are delivered first by the memory and sqlite queues; redis queues
+    // deliver in arrival order.
+    async fn put_with_priority(&self, record: Record, _priority: i64) -> Result<(), AppError> {
+        self.put(record).await
+    }
+    // Queues a record that only becomes visible after `delay`.
+    async fn put_delayed(
+        &self,
+        record: Record,
+        priority: i64,
+        delay: Duration,
+    ) -> Result<(), AppError>;
+    // Leases the next visible message, waiting up to `wait` for one to arrive. A leased
+    // message becomes visible again if it isn't acked before the visibility timeout.
+    async fn get(&self, wait: Duration) -> Result<Option<QueueMessage>, AppError>;
+    async fn ack(&self, id: &str) -> Result<(), AppError>;
+    // Returns a leased message to the queue, visible again after `delay`.
+    async fn nack(&self, id: &str, delay: Duration) -> Result<(), AppError>;
+    async fn status(&self) -> Result<QueueStatus, AppError>;
+    // Deletes every message of a task, leased and dead lettered ones included.
+    async fn purge(&self, task_id: &str) -> Result<(), AppError>;
 }
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use cdrs_tokio::{
     cluster::{TcpConnectionManager, session::Session},
     load_balancing::RoundRobinLoadBalancingStrategy,
     query::{BatchQueryBuilder, QueryValues},
+    query_values,
     transport::TransportTcp,
+    types::{IntoRustByName, blob::Blob},
 };
 
 use crate::types::{error::AppError, structs::record::Record};
@@ -15,16 +18,27 @@ pub type DbSession = Session<
     RoundRobinLoadBalancingStrategy<TransportTcp, TcpConnectionManager>,
 >;
 
+// Rows are partitioned by the crawl they belong to together with `PARTITION_KEY`, so
+// crawls never see each other's signals. Every partition a crawl writes is also listed in
+// `signal_partitions`, which is how a purge finds them.
+#[async_trait]
 pub trait Signal: Sized + Send + Sync {
+    const TABLE: &'static str;
+    // Blob column that, with `task_id`, makes up the partition key
+    const PARTITION_KEY: &'static str;
     const CREATE_TABLE_QUERY: &'static str;
     const UPSERT_QUERY: &'static str;
 
     async fn from_record(session: Arc<DbSession>, record: Record) -> Result<Vec<Self>, AppError>;
 
+    // The row's task id and `PARTITION_KEY` value
+    fn partition(&self) -> (&str, &[u8]);
+
     fn bind_values(&self) -> QueryValues;
 
     async fn create_table(session: Arc<DbSession>) -> Result<(), AppError> {
         session.query(Self::CREATE_TABLE_QUERY).await?;
+        session.query(CREATE_PARTITIONS_QUERY).await?;
         Ok(())
     }
 
@@ -38,11 +52,18 @@ pub trait Signal: Sized + Send + Sync {
         }
 
         let prepared = session.prepare(Self::UPSERT_QUERY).await?;
+        let listed = session.prepare(INSERT_PARTITION_QUERY).await?;
 
         for chunk in rows.chunks(batch_size.max(1)) {
             let mut b = BatchQueryBuilder::new();
             for r in chunk {
+                let (task_id, key) = r.partition();
+
                 b = b.add_query_prepared(&prepared, r.bind_values());
+                b = b.add_query_prepared(
+                    &listed,
+                    query_values!(task_id.to_string(), Self::TABLE, key.to_vec()),
+                );
             }
             let batch = b.build()?;
             session.batch(batch).await?;
@@ -50,4 +71,58 @@ pub trait Signal: Sized + Send + Sync {
 
         Ok(())
     }
+
+    // Deletes every partition of one crawl
+    async fn purge(session: Arc<DbSession>, task_id: &str) -> Result<(), AppError> {
+        let result = session
+            .query_with_values(
+                "SELECT partition_key FROM signal_partitions WHERE task_id = ? AND table_name = ?",
+                query_values!(task_id.to_string(), Self::TABLE),
+            )
+            .await?;
+        let rows = result.response_body()?.into_rows().unwrap_or_default();
+
+        let prepared = session
+            .prepare(format!(
+                "DELETE FROM {} WHERE task_id = ? AND {} = ?",
+                Self::TABLE,
+                Self::PARTITION_KEY
+            ))
+            .await?;
+
+        for row in rows {
+            let key: Option<Blob> = row.get_by_name("partition_key")?;
+
+            if let Some(key) = key {
+                session
+                    .exec_with_values(
+                        &prepared,
+                        query_values!(task_id.to_string(), key.into_vec()),
+                    )
+                    .await?;
+            }
+        }
+
+        session
+            .query_with_values(
+                "DELETE FROM signal_partitions WHERE task_id = ? AND table_name = ?",
+                query_values!(task_id.to_string(), Self::TABLE),
+            )
+            .await?;
+
+        Ok(())
+    }
 }
+
+const CREATE_PARTITIONS_QUERY: &str = r#"
+    CREATE TABLE IF NOT EXISTS signal_partitions (
+        task_id        text,
+        table_name     text,
+        partition_key  blob,
+        PRIMARY KEY ((task_id, table_name), partition_key)
+    )
+"#;
+
+const INSERT_PARTITION_QUERY: &str = r#"
+    INSERT INTO signal_partitions (task_id, table_name, partition_key) VALUES (?, ?, ?)
+"#;
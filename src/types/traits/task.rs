@@ -5,4 +5,22 @@ use crate::types::{error::AppError, structs::record::Record};
 #[async_trait]
 pub trait Task {
     async fn on_message(&self, message: Record) -> Result<Record, AppError>;
+
+    // Tasks that split a record into several, or drop it, override this. Stages call it
+    // in place of `on_message`.
+    async fn fan_out(&self, message: Record) -> Result<Vec<Record>, AppError> {
+        Ok(vec![self.on_message(message).await?])
+    }
+
+    // A failure `on_message` recorded on the record rather than returned, so the record
+    // carries it if no attempt succeeds. Stages retry it like a returned error.
+    fn recorded_failure(&self, _record: &Record) -> Option<AppError> {
+        None
+    }
+
+    // Releases whatever the task holds outside this process, once no more records will
+    // be sent to it.
+    async fn close(&self) -> Result<(), AppError> {
+        Ok(())
+    }
 }
@@ -0,0 +1,5 @@
+use crate::types::error::AppError;
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), AppError>;
+}
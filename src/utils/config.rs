@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use tokio::fs::read_to_string;
+
+use crate::types::{error::AppError, traits::validate::Validate};
+
+// Loads a config from a JSON file when the extension says so, otherwise from TOML.
+pub async fn load_config<T: DeserializeOwned + Validate>(path: &Path) -> Result<T, AppError> {
+    let contents = read_to_string(path).await?;
+    let config: T = match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(&contents)?,
+        _ => toml::from_str(&contents)?,
+    };
+
+    config.validate()?;
+
+    Ok(config)
+}
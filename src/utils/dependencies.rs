@@ -5,10 +5,17 @@ use std::{
 
 use tokio::sync::Mutex;
 
-use crate::types::{error::AppError, traits::object_store::ObjectStore};
+use crate::{
+    services::object_store::metered::MeteredObjectStore,
+    types::{
+        error::AppError,
+        traits::{object_store::ObjectStore, queue::Queue},
+    },
+};
 
 pub struct DependencyManager {
     object_stores: HashMap<String, Arc<dyn ObjectStore>>,
+    queues: HashMap<String, Arc<dyn Queue>>,
 }
 
 static DEPENDENCIES: OnceLock<Arc<Mutex<DependencyManager>>> = OnceLock::new();
@@ -17,19 +24,25 @@ pub fn dependencies() -> &'static Arc<Mutex<DependencyManager>> {
     DEPENDENCIES.get_or_init(|| Arc::new(Mutex::new(DependencyManager::new())))
 }
 
+impl Default for DependencyManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DependencyManager {
     pub fn new() -> Self {
         Self {
             object_stores: HashMap::new(),
+            queues: HashMap::new(),
         }
     }
 
     pub fn get_object_store(&self, key: &str) -> Result<Arc<dyn ObjectStore>, AppError> {
-        Ok(self
-            .object_stores
+        self.object_stores
             .get(key)
             .cloned()
-            .ok_or(AppError::MissingDependency(key.to_string()))?)
+            .ok_or(AppError::MissingDependency(key.to_string()))
     }
 
     pub fn set_object_store(
@@ -37,8 +50,22 @@ impl DependencyManager {
         key: &str,
         store: Arc<dyn ObjectStore>,
     ) -> Result<(), AppError> {
+        let store = Arc::new(MeteredObjectStore::new(key, store));
         self.object_stores.insert(key.into(), store);
 
         Ok(())
     }
+
+    pub fn get_queue(&self, key: &str) -> Result<Arc<dyn Queue>, AppError> {
+        self.queues
+            .get(key)
+            .cloned()
+            .ok_or(AppError::MissingDependency(key.to_string()))
+    }
+
+    pub fn set_queue(&mut self, key: &str, queue: Arc<dyn Queue>) -> Result<(), AppError> {
+        self.queues.insert(key.into(), queue);
+
+        Ok(())
+    }
 }
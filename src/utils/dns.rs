@@ -0,0 +1,35 @@
+use std::{io, net::SocketAddr};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use thiserror::Error;
+use tokio::net::lookup_host;
+
+#[derive(Error, Debug)]
+#[error("dns error: failed to resolve {host}: {source}")]
+pub struct DnsError {
+    pub host: String,
+    #[source]
+    pub source: io::Error,
+}
+
+// Resolves with the system resolver like reqwest does by default, but fails with a
+// `DnsError` so a lookup failure can be told apart from other connection failures
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+
+        Box::pin(async move {
+            // The port is filled in by the client from the url
+            let resolved = lookup_host((host.as_str(), 0))
+                .await
+                .map(|addrs| addrs.collect::<Vec<SocketAddr>>());
+
+            match resolved {
+                Ok(addrs) => Ok(Box::new(addrs.into_iter()) as Addrs),
+                Err(source) => Err(Box::new(DnsError { host, source }) as _),
+            }
+        })
+    }
+}
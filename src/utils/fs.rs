@@ -51,9 +51,20 @@ impl TempDir {
     pub fn path(&self) -> &Path {
         &self.0
     }
+    // Like dropping it, but reports why the directory couldn't be removed
+    pub fn close(mut self) -> Result<(), AppError> {
+        let path = std::mem::take(&mut self.0);
+        remove_dir_all(path)?;
+
+        Ok(())
+    }
 }
 impl Drop for TempDir {
     fn drop(&mut self) {
+        if self.0.as_os_str().is_empty() {
+            return;
+        }
+
         let _ = remove_dir_all(&self.0);
     }
 }
@@ -0,0 +1,31 @@
+use tracing_subscriber::EnvFilter;
+
+use crate::types::{
+    configs::logging_config::{LogFormat, LoggingConfig},
+    error::AppError,
+};
+
+// Installs the global subscriber. Events go to stderr so they don't mix with the
+// summaries printed on stdout.
+pub fn init_logging(config: &LoggingConfig) -> Result<(), AppError> {
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::try_new(&config.filter)
+            .map_err(|e| AppError::invalid_config("logging.filter", &e.to_string()))?,
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+
+    let result = match config.format {
+        LogFormat::Human => builder.try_init(),
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(true)
+            .try_init(),
+    };
+
+    result.map_err(|e| AppError::Generic(format!("failed to set up logging: {}", e)))
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+pub enum MetricKind {
+    Counter,
+    // Upper bounds of the buckets, ascending
+    Histogram(&'static [f64]),
+}
+
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: MetricKind,
+}
+
+const LATENCY_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+const COUNT_BUCKETS: &[f64] = &[0.0, 1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0];
+
+pub const FETCH_DURATION: Metric = Metric {
+    name: "aetherscope_fetch_duration_seconds",
+    help: "Time from sending a request to storing the response body",
+    kind: MetricKind::Histogram(LATENCY_BUCKETS),
+};
+
+pub const FETCH_RESPONSES: Metric = Metric {
+    name: "aetherscope_fetch_responses_total",
+    help: "Fetches by status code class, or error when no response arrived",
+    kind: MetricKind::Counter,
+};
+
+pub const STORED_BYTES: Metric = Metric {
+    name: "aetherscope_object_store_bytes_total",
+    help: "Bytes written to each object store",
+    kind: MetricKind::Counter,
+};
+
+pub const EXTRACTED_URLS: Metric = Metric {
+    name: "aetherscope_extracted_urls",
+    help: "Urls extracted per page",
+    kind: MetricKind::Histogram(COUNT_BUCKETS),
+};
+
+pub const FILTER_DECISIONS: Metric = Metric {
+    name: "aetherscope_filter_decisions_total",
+    help: "Uris accepted or rejected by each frontier filter",
+    kind: MetricKind::Counter,
+};
+
+pub const HASH_SET_LOOKUPS: Metric = Metric {
+    name: "aetherscope_hash_set_lookups_total",
+    help: "Lookups in the sets used for deduplication, by whether the entry was already there",
+    kind: MetricKind::Counter,
+};
+
+pub const TASK_DURATION: Metric = Metric {
+    name: "aetherscope_task_duration_seconds",
+    help: "Time each task spends on a record, including retries",
+    kind: MetricKind::Histogram(LATENCY_BUCKETS),
+};
+
+pub const TASK_RECORDS: Metric = Metric {
+    name: "aetherscope_task_records_total",
+    help: "Records handled by each task by outcome, plus every retry",
+    kind: MetricKind::Counter,
+};
+
+// Every metric, in the order they are rendered
+const METRICS: &[&Metric] = &[
+    &FETCH_DURATION,
+    &FETCH_RESPONSES,
+    &STORED_BYTES,
+    &EXTRACTED_URLS,
+    &FILTER_DECISIONS,
+    &HASH_SET_LOOKUPS,
+    &TASK_DURATION,
+    &TASK_RECORDS,
+];
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Default)]
+struct Histogram {
+    // Observations per bucket, not cumulative
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+// Process-wide counters and histograms, rendered in the Prometheus text exposition
+// format.
+#[derive(Default)]
+pub struct Metrics {
+    counters: Mutex<BTreeMap<(&'static str, Labels), f64>>,
+    histograms: Mutex<BTreeMap<(&'static str, Labels), Histogram>>,
+}
+
+static METRICS_REGISTRY: OnceLock<Metrics> = OnceLock::new();
+
+pub fn metrics() -> &'static Metrics {
+    METRICS_REGISTRY.get_or_init(Metrics::default)
+}
+
+impl Metrics {
+    pub fn increment(&self, metric: &Metric, labels: &[(&'static str, &str)], by: f64) {
+        let mut counters = self.counters.lock().unwrap();
+
+        *counters.entry((metric.name, owned(labels))).or_default() += by;
+    }
+
+    pub fn observe(&self, metric: &Metric, labels: &[(&'static str, &str)], value: f64) {
+        let MetricKind::Histogram(bounds) = metric.kind else {
+            return;
+        };
+
+        let mut histograms = self.histograms.lock().unwrap();
+        let histogram = histograms
+            .entry((metric.name, owned(labels)))
+            .or_insert_with(|| Histogram {
+                buckets: vec![0; bounds.len() + 1],
+                ..Histogram::default()
+            });
+
+        let bucket = bounds
+            .iter()
+            .position(|b| value <= *b)
+            .unwrap_or(bounds.len());
+
+        histogram.buckets[bucket] += 1;
+        histogram.sum += value;
+        histogram.count += 1;
+    }
+
+    pub fn render(&self) -> String {
+        let counters = self.counters.lock().unwrap();
+        let histograms = self.histograms.lock().unwrap();
+        let mut out = String::new();
+
+        for metric in METRICS {
+            let _ = writeln!(out, "# HELP {} {}", metric.name, metric.help);
+
+            match metric.kind {
+                MetricKind::Counter => {
+                    let _ = writeln!(out, "# TYPE {} counter", metric.name);
+
+                    for ((_, labels), value) in
+                        counters.iter().filter(|((n, _), _)| *n == metric.name)
+                    {
+                        let _ = writeln!(
+                            out,
+                            "{}{} {}",
+                            metric.name,
+                            format_labels(labels, None),
+                            value
+                        );
+                    }
+                }
+                MetricKind::Histogram(bounds) => {
+                    let _ = writeln!(out, "# TYPE {} histogram", metric.name);
+
+                    for ((_, labels), histogram) in
+                        histograms.iter().filter(|((n, _), _)| *n == metric.name)
+                    {
+                        let mut cumulative = 0;
+
+                        for (i, count) in histogram.buckets.iter().enumerate() {
+                            cumulative += count;
+
+                            let le = match bounds.get(i) {
+                                Some(bound) => bound.to_string(),
+                                None => "+Inf".to_string(),
+                            };
+
+                            let _ = writeln!(
+                                out,
+                                "{}_bucket{} {}",
+                                metric.name,
+                                format_labels(labels, Some(&le)),
+                                cumulative
+                            );
+                        }
+
+                        let labels = format_labels(labels, None);
+                        let _ = writeln!(out, "{}_sum{} {}", metric.name, labels, histogram.sum);
+                        let _ =
+                            writeln!(out, "{}_count{} {}", metric.name, labels, histogram.count);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+// Records the latency and status code class of one fetch
+pub fn observe_fetch(fetcher: &str, elapsed: Duration, status: Option<i64>) {
+    metrics().observe(
+        &FETCH_DURATION,
+        &[("fetcher", fetcher)],
+        elapsed.as_secs_f64(),
+    );
+    metrics().increment(
+        &FETCH_RESPONSES,
+        &[("fetcher", fetcher), ("class", status_class(status))],
+        1.0,
+    );
+}
+
+// Status code class used as the `class` label of fetch metrics
+pub fn status_class(status: Option<i64>) -> &'static str {
+    match status {
+        Some(100..=199) => "1xx",
+        Some(200..=299) => "2xx",
+        Some(300..=399) => "3xx",
+        Some(400..=499) => "4xx",
+        Some(500..=599) => "5xx",
+        _ => "error",
+    }
+}
+
+fn owned(labels: &[(&'static str, &str)]) -> Labels {
+    labels.iter().map(|(k, v)| (*k, v.to_string())).collect()
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+        .collect();
+
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+
+    match pairs.is_empty() {
+        true => String::new(),
+        false => format!("{{{}}}", pairs.join(",")),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+
+        metrics.increment(
+            &FETCH_RESPONSES,
+            &[("fetcher", "http"), ("class", "2xx")],
+            1.0,
+        );
+        metrics.increment(
+            &FETCH_RESPONSES,
+            &[("fetcher", "http"), ("class", "2xx")],
+            2.0,
+        );
+        metrics.observe(&FETCH_DURATION, &[("fetcher", "http")], 0.07);
+        metrics.observe(&FETCH_DURATION, &[("fetcher", "http")], 60.0);
+
+        let text = metrics.render();
+
+        assert!(text.contains("# TYPE aetherscope_fetch_responses_total counter"));
+        assert!(
+            text.contains("aetherscope_fetch_responses_total{fetcher=\"http\",class=\"2xx\"} 3")
+        );
+        assert!(
+            text.contains(
+                "aetherscope_fetch_duration_seconds_bucket{fetcher=\"http\",le=\"0.05\"} 0"
+            )
+        );
+        assert!(
+            text.contains(
+                "aetherscope_fetch_duration_seconds_bucket{fetcher=\"http\",le=\"0.1\"} 1"
+            )
+        );
+        assert!(
+            text.contains(
+                "aetherscope_fetch_duration_seconds_bucket{fetcher=\"http\",le=\"+Inf\"} 2"
+            )
+        );
+        assert!(text.contains("aetherscope_fetch_duration_seconds_count{fetcher=\"http\"} 2"));
+    }
+}
@@ -1,5 +1,11 @@
+pub mod config;
 pub mod dependencies;
+pub mod dns;
 pub mod fs;
 pub mod fsm;
+pub mod logging;
+pub mod metrics;
+pub mod namespace;
+pub mod politeness;
 pub mod sync;
 pub mod web;
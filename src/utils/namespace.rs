@@ -0,0 +1,48 @@
+use uuid::Uuid;
+
+use crate::types::error::AppError;
+
+// Task ids name directories, redis keys and table rows, so they are kept to characters
+// that are safe in all of them.
+pub fn validate_task_id(task_id: &str) -> Result<(), AppError> {
+    if task_id.is_empty() {
+        return Err(AppError::invalid_config("task_id", "must not be empty"));
+    }
+
+    let safe = task_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    if !safe || task_id == "." || task_id == ".." {
+        return Err(AppError::invalid_config(
+            "task_id",
+            "may only contain letters, digits, `-`, `_` and `.`",
+        ));
+    }
+
+    Ok(())
+}
+
+// Prefix of every object stored by one crawl
+pub fn object_prefix(task_id: &str) -> String {
+    format!("{}/", task_id)
+}
+
+// Key for a new object stored by a crawl
+pub fn object_key(task_id: &str) -> String {
+    format!("{}{}", object_prefix(task_id), Uuid::new_v4())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_task_id() {
+        assert!(validate_task_id("docs-2024_v1.2").is_ok());
+
+        for task_id in ["", ".", "..", "../etc", "a/b", "a b"] {
+            assert!(validate_task_id(task_id).is_err(), "{:?}", task_id);
+        }
+    }
+}
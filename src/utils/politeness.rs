@@ -0,0 +1,245 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::Client;
+use robotxt::Robots;
+use tokio::{runtime::Handle, sync::OnceCell, time::sleep};
+use tracing::{debug, warn};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    services::politeness::{memory::MemoryPolitenessGate, redis::RedisPolitenessGate},
+    types::{
+        configs::tasks::politeness_config::{HostGateConfig, PolitenessConfig},
+        error::AppError,
+        traits::politeness_gate::{Lease, PolitenessGate},
+    },
+    utils::web::{extract_host, fetch_http_simple, get_robots_url},
+};
+
+const MAX_POLL: Duration = Duration::from_millis(250);
+
+// Spaces out fetches to the same host. Every fetch holds the host's lease while it runs,
+// and releasing it starts the host's delay.
+pub struct Politeness {
+    config: PolitenessConfig,
+    gate: Arc<dyn PolitenessGate>,
+    client: Client,
+    user_agent: String,
+    // Read once per host, however many fetches ask at the same time
+    crawl_delays: Mutex<HashMap<String, Arc<OnceCell<Option<Duration>>>>>,
+    // Times each host of each crawl has throttled in a row
+    throttles: Mutex<HashMap<(String, String), u32>>,
+}
+
+// A host leased for one request. Dropped without being given back, like when the fetch
+// holding it is cancelled, it's released in the background so the host isn't left
+// blocked until the lease runs out.
+pub struct HostLease {
+    // Taken once the lease is given back
+    gate: Option<Arc<dyn PolitenessGate>>,
+    namespace: String,
+    host: String,
+    // Every fetch is its own owner, so workers of one fetcher wait for each other too
+    owner: String,
+    delay: Duration,
+}
+
+impl HostLease {
+    async fn release(mut self, delay: Duration) -> Result<(), AppError> {
+        let Some(gate) = self.gate.take() else {
+            return Ok(());
+        };
+
+        gate.release(&self.namespace, &self.host, &self.owner, delay)
+            .await
+    }
+}
+
+impl Drop for HostLease {
+    fn drop(&mut self) {
+        let (Some(gate), Ok(runtime)) = (self.gate.take(), Handle::try_current()) else {
+            return;
+        };
+        let namespace = self.namespace.clone();
+        let host = self.host.clone();
+        let owner = self.owner.clone();
+        let delay = self.delay;
+
+        runtime.spawn(async move {
+            if let Err(e) = gate.release(&namespace, &host, &owner, delay).await {
+                warn!(host = %host, error = %e, "failed to release dropped host lease");
+            }
+        });
+    }
+}
+
+impl Politeness {
+    pub async fn new(
+        config: &PolitenessConfig,
+        client: Client,
+        user_agent: String,
+    ) -> Result<Self, AppError> {
+        let gate: Arc<dyn PolitenessGate> = match config.gate.clone() {
+            HostGateConfig::Memory => Arc::new(MemoryPolitenessGate::new()),
+            HostGateConfig::Redis(c) => Arc::new(RedisPolitenessGate::new(c).await?),
+        };
+
+        Ok(Self {
+            config: config.clone(),
+            gate,
+            client,
+            user_agent,
+            crawl_delays: Mutex::new(HashMap::new()),
+            throttles: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // Waits until the host of `uri` may be fetched and leases it
+    pub async fn acquire(&self, task_id: &str, uri: &str) -> Result<HostLease, AppError> {
+        let url = Url::parse(uri)?;
+        let host = extract_host(&url)?;
+        let delay = self.delay(&host).await;
+        let lease = Duration::from_millis(self.config.lease_ms);
+        let owner = Uuid::new_v4().to_string();
+
+        while let Lease::Wait(wait) = self.gate.try_acquire(task_id, &host, &owner, lease).await? {
+            debug!(host = %host, wait_ms = wait.as_millis() as u64, "waiting for host");
+            // A lease is usually given back long before it runs out, so look again soon
+            sleep(wait.min(MAX_POLL)).await;
+        }
+
+        Ok(HostLease {
+            gate: Some(self.gate.clone()),
+            namespace: task_id.to_string(),
+            host,
+            owner,
+            delay,
+        })
+    }
+
+    pub async fn release(&self, lease: HostLease) -> Result<(), AppError> {
+        self.throttles
+            .lock()
+            .unwrap()
+            .remove(&(lease.namespace.clone(), lease.host.clone()));
+
+        let delay = lease.delay;
+
+        lease.release(delay).await
+    }
+
+    // Releases a host that throttled the fetch, keeping everyone off it for longer the
+    // more often it happens
+    pub async fn back_off(
+        &self,
+        lease: HostLease,
+        retry_after: Option<Duration>,
+    ) -> Result<(), AppError> {
+        let strikes = {
+            let mut throttles = self.throttles.lock().unwrap();
+            let strikes = throttles
+                .entry((lease.namespace.clone(), lease.host.clone()))
+                .or_default();
+
+            *strikes += 1;
+            *strikes
+        };
+        let delay = throttle_backoff(&self.config, strikes)
+            .max(retry_after.unwrap_or_default())
+            .max(lease.delay);
+
+        debug!(host = %lease.host, strikes, delay_ms = delay.as_millis() as u64, "backing off host");
+
+        lease.release(delay).await
+    }
+
+    async fn delay(&self, host: &str) -> Duration {
+        let min_delay = Duration::from_millis(self.config.min_delay_ms);
+
+        if !self.config.crawl_delay {
+            return min_delay;
+        }
+
+        let cell = self
+            .crawl_delays
+            .lock()
+            .unwrap()
+            .entry(host.to_string())
+            .or_default()
+            .clone();
+        let crawl_delay = *cell.get_or_init(|| self.crawl_delay(host)).await;
+        let max_crawl_delay = Duration::from_millis(self.config.max_crawl_delay_ms);
+
+        crawl_delay.map_or(min_delay, |d| d.min(max_crawl_delay).max(min_delay))
+    }
+
+    // A robots.txt that can't be read sets no delay, the robots filter decides whether the
+    // host may be crawled at all
+    async fn crawl_delay(&self, host: &str) -> Option<Duration> {
+        let robots_url = get_robots_url(host).ok()?;
+
+        match fetch_http_simple(self.client.clone(), &robots_url).await {
+            Ok(contents) => Robots::from_bytes(contents.as_ref(), &self.user_agent).crawl_delay(),
+            Err(e) => {
+                debug!(host = %host, error = %e, "no crawl delay, robots.txt unavailable");
+                None
+            }
+        }
+    }
+}
+
+fn throttle_backoff(config: &PolitenessConfig, strikes: u32) -> Duration {
+    let exponential = config
+        .throttle_backoff_ms
+        .saturating_mul(1u64 << strikes.saturating_sub(1).min(32));
+
+    Duration::from_millis(exponential.min(config.max_throttle_backoff_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::timeout;
+
+    #[test]
+    fn test_throttle_backoff() {
+        let config = PolitenessConfig {
+            throttle_backoff_ms: 1_000,
+            max_throttle_backoff_ms: 5_000,
+            ..PolitenessConfig::default()
+        };
+
+        assert_eq!(throttle_backoff(&config, 1), Duration::from_secs(1));
+        assert_eq!(throttle_backoff(&config, 3), Duration::from_secs(4));
+        assert_eq!(throttle_backoff(&config, 4), Duration::from_secs(5));
+        assert_eq!(throttle_backoff(&config, 100), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_lease_is_released() {
+        let config = PolitenessConfig {
+            min_delay_ms: 0,
+            crawl_delay: false,
+            ..PolitenessConfig::default()
+        };
+        let politeness = Politeness::new(&config, Client::new(), "test".to_string())
+            .await
+            .unwrap();
+        let uri = "http://example.com/";
+
+        drop(politeness.acquire("docs", uri).await.unwrap());
+
+        // Without the release the host would stay leased for a minute
+        let lease = timeout(Duration::from_secs(1), politeness.acquire("docs", uri))
+            .await
+            .unwrap()
+            .unwrap();
+
+        politeness.release(lease).await.unwrap();
+    }
+}
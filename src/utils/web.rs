@@ -1,7 +1,8 @@
-use std::{net::IpAddr, sync::Arc};
+use std::{net::IpAddr, sync::Arc, time::Duration};
 
 use bytes::Bytes;
-use psl::{domain, domain_str};
+use chrono::{DateTime, Utc};
+use psl::domain_str;
 use reqwest::Client;
 use url::{ParseError, Url};
 
@@ -76,7 +77,7 @@ fn looks_like_domainish(s: &str) -> bool {
         return false;
     }
     // If it has a scheme already, Url::parse() would have succeeded earlier.
-    let end = s.find(|c| c == '?' || c == '#').unwrap_or_else(|| s.len());
+    let end = s.find(['?', '#']).unwrap_or_else(|| s.len());
     let head = &s[..end];
 
     if head.starts_with('[') {
@@ -111,6 +112,24 @@ pub async fn fetch_http_simple(client: Client, uri: &str) -> Result<Bytes, AppEr
     Ok(resp.bytes().await?)
 }
 
+// `Retry-After` is either a number of seconds or an HTTP date. Dates in the past mean
+// right away.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
 pub fn get_robots_url(input: &str) -> Result<String, AppError> {
     let url = Url::parse(input)?;
 
@@ -182,6 +201,27 @@ mod tests {
         Url::parse("http://example.com:8080/dir/index.html").unwrap()
     }
 
+    #[test]
+    fn retry_after_seconds_and_dates() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            parse_retry_after(" 120 ", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:29:30 GMT", now),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
     #[test]
     fn empty_href_returns_origin() {
         let o = origin_https();